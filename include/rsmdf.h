@@ -0,0 +1,65 @@
+/*
+ * C interface of rsmdf, see src/ffi.rs.
+ *
+ * Functions returning a count or index return -1 on failure, functions
+ * returning a pointer return NULL. rsmdf_last_error() then describes the
+ * failure. Strings handed out stay valid until the file is closed.
+ */
+
+#ifndef RSMDF_H
+#define RSMDF_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct RsmdfFile RsmdfFile;
+
+/* The message of the last failed call on this thread, or NULL. */
+const char *rsmdf_last_error(void);
+
+/* Opens an MDF 3 or 4 file, returns NULL on failure. */
+RsmdfFile *rsmdf_open(const char *path);
+
+/* Closes the file, NULL is ignored. */
+void rsmdf_close(RsmdfFile *file);
+
+int64_t rsmdf_channel_count(RsmdfFile *file);
+
+const char *rsmdf_channel_name(RsmdfFile *file, size_t index);
+
+/* Index of the channel at address, a name optionally qualified as
+ * "name@group_comment", "source::name" or "data_group/channel_group/name".
+ * Returns -1 if there is none or several. */
+int64_t rsmdf_find_channel(RsmdfFile *file, const char *address);
+
+const char *rsmdf_channel_unit(RsmdfFile *file, size_t index);
+
+const char *rsmdf_channel_comment(RsmdfFile *file, size_t index);
+
+/*
+ * Copies up to capacity physical values and timestamps of the channel into
+ * the buffers, either may be NULL. Returns the number of samples in the
+ * channel, which may be more than capacity. Text samples read as NaN.
+ */
+int64_t rsmdf_read_channel(RsmdfFile *file, size_t index, double *values,
+                           double *timestamps, size_t capacity);
+
+/*
+ * Like rsmdf_read_channel with buffers allocated by the library, free them
+ * with rsmdf_free_buffer. Returns the number of samples.
+ */
+int64_t rsmdf_read_channel_alloc(RsmdfFile *file, size_t index,
+                                 double **values, double **timestamps);
+
+/* Frees a buffer from rsmdf_read_channel_alloc, NULL is ignored. */
+void rsmdf_free_buffer(double *buffer, size_t length);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RSMDF_H */
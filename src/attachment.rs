@@ -0,0 +1,25 @@
+/// A file attached to the measurement. External attachments only carry the
+/// path in `file_name`, embedded ones keep their bytes in `data`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub file_name: String,
+    pub mime: String,
+    pub comment: String,
+    pub data: Vec<u8>,
+    /// `data` is deflate compressed, `original_size` is its inflated length.
+    pub compressed: bool,
+    pub original_size: u64,
+}
+
+impl Attachment {
+    pub fn embedded(file_name: &str, mime: &str, data: Vec<u8>) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            mime: mime.to_string(),
+            comment: String::new(),
+            original_size: data.len() as u64,
+            data,
+            compressed: false,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::record::Record;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conversion {
+    /// phys = offset + factor * raw
+    Linear { offset: f64, factor: f64 },
+    /// Maps exact raw values to text, falling back to `default`.
+    ValueToText {
+        values: Vec<f64>,
+        texts: Vec<String>,
+        default: String,
+    },
+    /// Interpolates linearly between `(raw, phys)` table entries, holding
+    /// the end values outside the table.
+    TabularInterpolation { raw: Vec<f64>, phys: Vec<f64> },
+    /// Takes the physical value of the nearest raw table entry.
+    Tabular { raw: Vec<f64>, phys: Vec<f64> },
+}
+
+impl Conversion {
+    pub fn convert(&self, raw: &Record) -> Record {
+        match self {
+            Conversion::Linear { offset, factor } => {
+                Record::Float64(offset + factor * raw.extract())
+            }
+            Conversion::ValueToText {
+                values,
+                texts,
+                default,
+            } => {
+                let raw = raw.extract();
+                let text = values
+                    .iter()
+                    .position(|x| *x == raw)
+                    .map_or(default, |i| &texts[i]);
+                Record::StringNullTerm(text.clone())
+            }
+            Conversion::TabularInterpolation { raw: keys, phys } => {
+                let raw = raw.extract();
+                let upper = keys.partition_point(|x| *x <= raw);
+                let value = if upper == 0 {
+                    phys[0]
+                } else if upper == keys.len() {
+                    phys[upper - 1]
+                } else {
+                    let (x0, x1) = (keys[upper - 1], keys[upper]);
+                    let (y0, y1) = (phys[upper - 1], phys[upper]);
+                    y0 + (y1 - y0) * (raw - x0) / (x1 - x0)
+                };
+                Record::Float64(value)
+            }
+            Conversion::Tabular { raw: keys, phys } => {
+                let raw = raw.extract();
+                let upper = keys.partition_point(|x| *x <= raw);
+                let index = if upper == 0 {
+                    0
+                } else if upper == keys.len() || raw - keys[upper - 1] <= keys[upper] - raw {
+                    upper - 1
+                } else {
+                    upper
+                };
+                Record::Float64(phys[index])
+            }
+        }
+    }
+
+    pub fn convert_all(&self, raw: &[Record]) -> Vec<Record> {
+        raw.iter().map(|x| self.convert(x)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Conversion;
+    use crate::record::Record;
+
+    #[test]
+    fn linear() {
+        let conversion = Conversion::Linear {
+            offset: 1.0,
+            factor: 0.5,
+        };
+
+        assert_eq!(conversion.convert(&Record::Uint(4)), Record::Float64(3.0));
+    }
+
+    #[test]
+    fn value_to_text() {
+        let conversion = Conversion::ValueToText {
+            values: vec![0.0, 1.0],
+            texts: vec!["Off".to_string(), "On".to_string()],
+            default: "Unknown".to_string(),
+        };
+
+        assert_eq!(
+            conversion.convert_all(&[Record::Uint(1), Record::Uint(7)]),
+            vec![
+                Record::StringNullTerm("On".to_string()),
+                Record::StringNullTerm("Unknown".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn tabular() {
+        let interpolated = Conversion::TabularInterpolation {
+            raw: vec![0.0, 10.0, 20.0],
+            phys: vec![100.0, 200.0, 0.0],
+        };
+
+        assert_eq!(
+            interpolated.convert_all(&[
+                Record::Int(-5),
+                Record::Uint(5),
+                Record::Uint(15),
+                Record::Uint(25)
+            ]),
+            vec![
+                Record::Float64(100.0),
+                Record::Float64(150.0),
+                Record::Float64(100.0),
+                Record::Float64(0.0)
+            ]
+        );
+
+        let nearest = Conversion::Tabular {
+            raw: vec![0.0, 10.0, 20.0],
+            phys: vec![100.0, 200.0, 0.0],
+        };
+
+        assert_eq!(
+            nearest.convert_all(&[Record::Uint(4), Record::Uint(6), Record::Uint(30)]),
+            vec![
+                Record::Float64(100.0),
+                Record::Float64(200.0),
+                Record::Float64(0.0)
+            ]
+        );
+    }
+}
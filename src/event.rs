@@ -0,0 +1,23 @@
+/// A trigger or marker in the measurement, `time` is in seconds from the start
+/// of the recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub time: f64,
+    /// Seconds recorded before and after a trigger event.
+    pub pre_trigger: f64,
+    pub post_trigger: f64,
+    pub name: String,
+    pub comment: String,
+}
+
+impl Event {
+    pub fn new(time: f64, name: &str, comment: &str) -> Self {
+        Self {
+            time,
+            pre_trigger: 0.0,
+            post_trigger: 0.0,
+            name: name.to_string(),
+            comment: comment.to_string(),
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+
+use super::{group_path, ExportOptions, Quoting, TimeFormat};
+use crate::mdf::Measurement;
+use crate::record::Record;
+use crate::signal::Signal;
+
+/// Writes one file per channel group, or a single file when
+/// [`ExportOptions::raster`] is set.
+pub fn write(
+    mut measurement: Measurement,
+    filename: &str,
+    options: &ExportOptions,
+) -> io::Result<()> {
+    let signals = |signals: Vec<Signal>| -> Vec<Signal> {
+        if options.raw {
+            signals
+        } else {
+            signals.iter().map(Signal::physical).collect()
+        }
+    };
+
+    match &options.raster {
+        Some(raster) => {
+            measurement
+                .resample(raster, options.interpolation, false)
+                .map_err(|x| io::Error::new(io::ErrorKind::InvalidInput, x))?;
+            let all = measurement.groups.into_iter().flat_map(|x| x.3).collect();
+
+            let mut file = BufWriter::new(File::create(filename)?);
+            write_table(&mut file, measurement.start_time, &signals(all), options)?;
+            file.flush()
+        }
+        None => {
+            for (index, (_, _, _, group)) in measurement.groups.into_iter().enumerate() {
+                let mut file = BufWriter::new(File::create(group_path(filename, index))?);
+                write_table(&mut file, measurement.start_time, &signals(group), options)?;
+                file.flush()?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Writes a header row of names and units followed by one row per timestamp
+/// of the first signal, the signals are expected to share their timestamps.
+pub fn write_table(
+    writer: &mut impl Write,
+    start_time: DateTime<Utc>,
+    signals: &[Signal],
+    options: &ExportOptions,
+) -> io::Result<()> {
+    let delimiter = options.delimiter.to_string();
+
+    let mut header = vec![match options.time_format {
+        TimeFormat::Relative => text("time [s]", options),
+        TimeFormat::Absolute => text("time", options),
+    }];
+    for signal in signals {
+        header.push(match signal.unit.as_str() {
+            "" => text(&signal.name, options),
+            unit => text(&format!("{} [{unit}]", signal.name), options),
+        });
+    }
+    writeln!(writer, "{}", header.join(&delimiter))?;
+
+    let timestamps = signals.first().map_or(&[][..], |x| &x.timestamps[..]);
+    for (index, &time) in timestamps.iter().enumerate() {
+        let mut row = vec![match options.time_format {
+            TimeFormat::Relative => number(time, options),
+            TimeFormat::Absolute => (start_time + Duration::nanoseconds((time * 1e9) as i64))
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        }];
+        for signal in signals {
+            row.push(match signal.samples.get(index) {
+                Some(sample) => record(sample, options),
+                None => String::new(),
+            });
+        }
+        writeln!(writer, "{}", row.join(&delimiter))?;
+    }
+
+    Ok(())
+}
+
+fn record(record: &Record, options: &ExportOptions) -> String {
+    match record {
+        Record::Uint(value) => value.to_string(),
+        Record::Int(value) => value.to_string(),
+        Record::Float32(value) => number(value, options),
+        Record::Float64(value) => number(value, options),
+        Record::StringNullTerm(value) => text(value, options),
+    }
+}
+
+fn number(value: impl Display, options: &ExportOptions) -> String {
+    let formatted = match options.precision {
+        Some(precision) => format!("{value:.precision$}"),
+        None => value.to_string(),
+    };
+
+    match options.decimal_separator {
+        '.' => formatted,
+        separator => formatted.replace('.', &separator.to_string()),
+    }
+}
+
+fn text(value: &str, options: &ExportOptions) -> String {
+    let quoted = match options.quoting {
+        Quoting::Always => true,
+        Quoting::Needed => value.contains([options.delimiter, '"', '\n', '\r']),
+        Quoting::Never => false,
+    };
+
+    if quoted {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::conversion::Conversion;
+    use crate::mdf::RasterType;
+    use crate::utils::TempFile;
+
+    fn signals() -> Vec<Signal> {
+        let speed = Signal::new(
+            vec![0.0, 0.5],
+            vec![Record::Uint(10), Record::Uint(20)],
+            "km/h".to_string(),
+            "speed".to_string(),
+            "".to_string(),
+            true,
+        )
+        .with_conversion(Conversion::Linear {
+            offset: 0.0,
+            factor: 0.25,
+        });
+        let gear = Signal::new(
+            vec![0.0, 0.5],
+            vec![
+                Record::StringNullTerm("first".to_string()),
+                Record::StringNullTerm("a, \"b\"".to_string()),
+            ],
+            "".to_string(),
+            "gear".to_string(),
+            "".to_string(),
+            false,
+        );
+        vec![speed.physical(), gear]
+    }
+
+    fn table(options: &ExportOptions) -> String {
+        let start_time = Utc.timestamp_nanos(1_600_000_000_000_000_000);
+        let mut stream = Vec::new();
+        write_table(&mut stream, start_time, &signals(), options).unwrap();
+        String::from_utf8(stream).unwrap()
+    }
+
+    #[test]
+    fn write() {
+        assert_eq!(
+            table(&ExportOptions::default()),
+            "time [s],speed [km/h],gear\n0,2.5,first\n0.5,5,\"a, \"\"b\"\"\"\n"
+        );
+
+        let options = ExportOptions {
+            delimiter: ';',
+            decimal_separator: ',',
+            precision: Some(2),
+            time_format: TimeFormat::Absolute,
+            quoting: Quoting::Always,
+            ..ExportOptions::default()
+        };
+        assert_eq!(
+            table(&options),
+            "\"time\";\"speed [km/h]\";\"gear\"\n\
+             2020-09-13T12:26:40Z;2,50;\"first\"\n\
+             2020-09-13T12:26:40.500Z;5,00;\"a, \"\"b\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn write_files() {
+        let start_time = Utc.timestamp_nanos(0);
+        let raw = signals()[0].clone();
+        let raw = Signal {
+            samples: vec![Record::Uint(10), Record::Uint(20)],
+            raw: true,
+            ..raw
+        };
+        let late = Signal {
+            timestamps: vec![1.0],
+            samples: vec![Record::Float64(1.5)],
+            name: "late".to_string(),
+            unit: "".to_string(),
+            raw: false,
+            ..raw.clone()
+        };
+        let measurement = || Measurement {
+            comment: String::new(),
+            start_time,
+            events: Vec::new(),
+            attachments: Vec::new(),
+            groups: vec![
+                (0, 0, String::new(), vec![raw.clone()]),
+                (1, 0, String::new(), vec![late.clone()]),
+            ],
+        };
+
+        let file = TempFile::create("export.csv");
+        let filename = file.path();
+        let options = ExportOptions {
+            raw: true,
+            ..ExportOptions::default()
+        };
+        super::write(measurement(), filename, &options).unwrap();
+        let first = TempFile::from(group_path(filename, 0));
+        let second = TempFile::from(group_path(filename, 1));
+        assert_eq!(
+            std::fs::read_to_string(first.path()).unwrap(),
+            "time [s],speed [km/h]\n0,10\n0.5,20\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(second.path()).unwrap(),
+            "time [s],late\n1,1.5\n"
+        );
+
+        let options = ExportOptions {
+            raster: Some(RasterType::Step(0.5)),
+            ..ExportOptions::default()
+        };
+        super::write(measurement(), filename, &options).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(filename).unwrap(),
+            "time [s],speed [km/h],late\n0,2.5,1.5\n0.5,5,1.5\n1,5,1.5\n"
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use std::io;
+
+use chrono::SecondsFormat;
+
+use super::ExportOptions;
+use crate::mdf::Measurement;
+use crate::record::Record;
+use crate::signal::Signal;
+
+/// Writes the measurement as a single JSON document with the samples of each
+/// channel group next to their shared timestamps.
+pub fn write(
+    mut measurement: Measurement,
+    filename: &str,
+    options: &ExportOptions,
+) -> io::Result<()> {
+    if let Some(raster) = &options.raster {
+        measurement
+            .resample(raster, options.interpolation, false)
+            .map_err(|x| io::Error::new(io::ErrorKind::InvalidInput, x))?;
+    }
+
+    fs::write(filename, document(&measurement, options.raw))
+}
+
+pub fn document(measurement: &Measurement, raw: bool) -> String {
+    let groups: Vec<String> = measurement
+        .groups
+        .iter()
+        .map(|(dg, cg, comment, signals)| {
+            let signals: Vec<Signal> = if raw {
+                signals.clone()
+            } else {
+                signals.iter().map(Signal::physical).collect()
+            };
+            let timestamps = signals.first().map_or(&[][..], |x| &x.timestamps[..]);
+            let channels: Vec<String> = signals
+                .iter()
+                .map(|signal| {
+                    object(&[
+                        ("name", string(&signal.name)),
+                        ("unit", string(&signal.unit)),
+                        ("comment", string(&signal.comment)),
+                        ("samples", array(signal.samples.iter().map(record))),
+                    ])
+                })
+                .collect();
+
+            object(&[
+                ("data_group", dg.to_string()),
+                ("channel_group", cg.to_string()),
+                ("comment", string(comment)),
+                ("timestamps", array(timestamps.iter().map(|&x| number(x)))),
+                ("channels", array(channels)),
+            ])
+        })
+        .collect();
+
+    object(&[
+        ("comment", string(&measurement.comment)),
+        (
+            "start_time",
+            string(
+                &measurement
+                    .start_time
+                    .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            ),
+        ),
+        ("groups", array(groups)),
+    ])
+}
+
+/// A JSON object from already encoded values.
+pub fn object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("{}:{value}", string(key)))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// A JSON array from already encoded values.
+pub fn array(values: impl IntoIterator<Item = String>) -> String {
+    format!("[{}]", values.into_iter().collect::<Vec<_>>().join(","))
+}
+
+pub fn string(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len() + 2);
+    encoded.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            '\r' => encoded.push_str("\\r"),
+            '\t' => encoded.push_str("\\t"),
+            c if c.is_control() => encoded.push_str(&format!("\\u{:04x}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+    encoded
+}
+
+/// JSON has no NaN or infinity, they become null.
+pub fn number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+fn record(record: &Record) -> String {
+    match record {
+        Record::Uint(value) => value.to_string(),
+        Record::Int(value) => value.to_string(),
+        Record::Float32(value) if value.is_finite() => value.to_string(),
+        Record::Float32(_) => "null".to_string(),
+        Record::Float64(value) => number(*value),
+        Record::StringNullTerm(value) => string(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn write() {
+        let signal = Signal::new(
+            vec![0.0, 0.5],
+            vec![Record::Float64(1.5), Record::Float64(f64::NAN)],
+            "V".to_string(),
+            "voltage".to_string(),
+            "line\n\"two\"".to_string(),
+            false,
+        );
+        let measurement = Measurement {
+            comment: String::new(),
+            start_time: Utc.timestamp_nanos(1_600_000_000_000_000_000),
+            events: Vec::new(),
+            attachments: Vec::new(),
+            groups: vec![(1, 0, "engine".to_string(), vec![signal])],
+        };
+
+        assert_eq!(
+            document(&measurement, false),
+            "{\"comment\":\"\",\"start_time\":\"2020-09-13T12:26:40Z\",\"groups\":[\
+             {\"data_group\":1,\"channel_group\":0,\"comment\":\"engine\",\"timestamps\":[0,0.5],\
+             \"channels\":[{\"name\":\"voltage\",\"unit\":\"V\",\
+             \"comment\":\"line\\n\\\"two\\\"\",\"samples\":[1.5,null]}]}]}"
+        );
+        assert_eq!(string("\u{1}"), "\"\\u0001\"");
+    }
+}
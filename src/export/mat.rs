@@ -0,0 +1,412 @@
+use std::fs;
+use std::io::{self, Write};
+
+use chrono::Utc;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use super::{ExportOptions, MatLayout};
+use crate::mdf::Measurement;
+use crate::record::Record;
+use crate::signal::Signal;
+use crate::utils;
+
+const MI_INT8: u32 = 1;
+const MI_UINT16: u32 = 4;
+const MI_INT32: u32 = 5;
+const MI_UINT32: u32 = 6;
+const MI_DOUBLE: u32 = 9;
+const MI_MATRIX: u32 = 14;
+const MI_COMPRESSED: u32 = 15;
+
+const MX_CELL: u32 = 1;
+const MX_STRUCT: u32 = 2;
+const MX_CHAR: u32 = 4;
+const MX_DOUBLE: u32 = 6;
+
+/// Longest variable name MATLAB accepts.
+const NAME_LENGTH: usize = 63;
+/// Struct field names are stored in fixed width, including a terminating NUL.
+const FIELD_LENGTH: usize = 32;
+
+/// The MATLAB values the exporter writes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Array {
+    /// Column major values of a `rows` x `columns` matrix.
+    Double {
+        rows: usize,
+        columns: usize,
+        values: Vec<f64>,
+    },
+    Char(String),
+    /// A column of cells.
+    Cell(Vec<Array>),
+    Struct(Vec<(String, Array)>),
+}
+
+/// Writes a MAT-file version 5 with one struct per channel or one matrix per
+/// channel group, see [`MatLayout`].
+pub fn write(
+    mut measurement: Measurement,
+    filename: &str,
+    options: &ExportOptions,
+) -> io::Result<()> {
+    if let Some(raster) = &options.raster {
+        measurement
+            .resample(raster, options.interpolation, false)
+            .map_err(|x| io::Error::new(io::ErrorKind::InvalidInput, x))?;
+    }
+
+    let mut stream = header();
+    let mut used = Vec::new();
+    for (index, (_, _, comment, signals)) in measurement.groups.iter().enumerate() {
+        let signals: Vec<Signal> = if options.raw {
+            signals.clone()
+        } else {
+            signals.iter().map(Signal::physical).collect()
+        };
+
+        match options.mat_layout {
+            MatLayout::Channels => {
+                for signal in &signals {
+                    let name = matlab_name(&signal.name, &mut used);
+                    variable(&mut stream, &name, &channel(signal), options.compress)?;
+                }
+            }
+            MatLayout::Groups => {
+                let name = match comment.as_str() {
+                    "" => format!("ChannelGroup_{index}"),
+                    comment => comment.to_string(),
+                };
+                let name = matlab_name(&name, &mut used);
+                variable(&mut stream, &name, &group(&signals), options.compress)?;
+            }
+        }
+    }
+
+    fs::write(filename, stream)
+}
+
+/// A struct with the time, data, unit and comment of the signal. Signals
+/// with any text sample become a cell array of strings.
+pub fn channel(signal: &Signal) -> Array {
+    let data = match is_text(signal) {
+        true => Array::Cell(
+            signal
+                .samples
+                .iter()
+                .map(|x| match x {
+                    Record::StringNullTerm(text) => Array::Char(text.clone()),
+                    number => Array::Char(number.extract().to_string()),
+                })
+                .collect(),
+        ),
+        false => column(signal.samples.iter().map(Record::extract).collect()),
+    };
+
+    Array::Struct(vec![
+        ("time".to_string(), column(signal.timestamps.clone())),
+        ("data".to_string(), data),
+        ("unit".to_string(), Array::Char(signal.unit.clone())),
+        ("comment".to_string(), Array::Char(signal.comment.clone())),
+    ])
+}
+
+/// A matrix with the time in the first column and one column per numeric
+/// signal, signals with any text sample are left out.
+pub fn group(signals: &[Signal]) -> Array {
+    let timestamps = signals.first().map_or(&[][..], |x| &x.timestamps[..]);
+    let mut values = timestamps.to_vec();
+    let mut columns = 1;
+    for signal in signals {
+        if is_text(signal) {
+            continue;
+        }
+        values.extend(signal.samples.iter().map(Record::extract));
+        columns += 1;
+    }
+
+    Array::Double {
+        rows: timestamps.len(),
+        columns,
+        values,
+    }
+}
+
+fn is_text(signal: &Signal) -> bool {
+    signal
+        .samples
+        .iter()
+        .any(|x| matches!(x, Record::StringNullTerm(_)))
+}
+
+fn column(values: Vec<f64>) -> Array {
+    Array::Double {
+        rows: values.len(),
+        columns: 1,
+        values,
+    }
+}
+
+/// Turns `name` into a valid MATLAB identifier not in `used`, and adds it to
+/// `used`.
+pub fn matlab_name(name: &str, used: &mut Vec<String>) -> String {
+    let mut safe: String = name
+        .chars()
+        .map(|x| if x.is_ascii_alphanumeric() { x } else { '_' })
+        .collect();
+    if !safe.starts_with(|x: char| x.is_ascii_alphabetic()) {
+        safe.insert(0, 'x');
+    }
+    safe.truncate(NAME_LENGTH);
+
+    let mut unique = safe.clone();
+    let mut counter = 1;
+    while used.contains(&unique) {
+        let suffix = format!("_{counter}");
+        let length = safe.len().min(NAME_LENGTH - suffix.len());
+        unique = format!("{}{suffix}", &safe[..length]);
+        counter += 1;
+    }
+
+    used.push(unique.clone());
+    unique
+}
+
+fn header() -> Vec<u8> {
+    let text = format!(
+        "MATLAB 5.0 MAT-file, Platform: rsmdf, Created on: {}",
+        Utc::now().format("%a %b %e %H:%M:%S %Y")
+    );
+    let mut stream = format!("{text:116}").into_bytes();
+    stream.truncate(116);
+    // No subsystem data
+    stream.extend([0; 8]);
+    utils::write(&mut stream, 0x0100_u16, true);
+    stream.extend(b"IM");
+    stream
+}
+
+fn variable(stream: &mut Vec<u8>, name: &str, value: &Array, compress: bool) -> io::Result<()> {
+    let matrix = matrix(name, value);
+    if !compress {
+        stream.extend(matrix);
+        return Ok(());
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&matrix)?;
+    let compressed = encoder.finish()?;
+    // Compressed elements are not padded
+    utils::write(stream, MI_COMPRESSED, true);
+    utils::write(stream, compressed.len() as u32, true);
+    stream.extend(compressed);
+    Ok(())
+}
+
+/// The miMATRIX element holding `value`.
+pub fn matrix(name: &str, value: &Array) -> Vec<u8> {
+    let (class, rows, columns) = match value {
+        Array::Double { rows, columns, .. } => (MX_DOUBLE, *rows, *columns),
+        Array::Char(text) => (MX_CHAR, 1, text.encode_utf16().count()),
+        Array::Cell(cells) => (MX_CELL, cells.len(), 1),
+        Array::Struct(_) => (MX_STRUCT, 1, 1),
+    };
+
+    let mut body = Vec::new();
+    let mut flags = Vec::new();
+    utils::write(&mut flags, class, true);
+    utils::write(&mut flags, 0_u32, true);
+    element(&mut body, MI_UINT32, &flags);
+    let mut dimensions = Vec::new();
+    utils::write(&mut dimensions, rows as i32, true);
+    utils::write(&mut dimensions, columns as i32, true);
+    element(&mut body, MI_INT32, &dimensions);
+    element(&mut body, MI_INT8, name.as_bytes());
+
+    match value {
+        Array::Double { values, .. } => {
+            let mut data = Vec::with_capacity(values.len() * 8);
+            for value in values {
+                utils::write(&mut data, *value, true);
+            }
+            element(&mut body, MI_DOUBLE, &data);
+        }
+        Array::Char(text) => {
+            let mut data = Vec::new();
+            for unit in text.encode_utf16() {
+                utils::write(&mut data, unit, true);
+            }
+            element(&mut body, MI_UINT16, &data);
+        }
+        Array::Cell(cells) => {
+            for cell in cells {
+                body.extend(matrix("", cell));
+            }
+        }
+        Array::Struct(fields) => {
+            let mut length = Vec::new();
+            utils::write(&mut length, FIELD_LENGTH as i32, true);
+            element(&mut body, MI_INT32, &length);
+            let mut names = Vec::new();
+            for (name, _) in fields {
+                let mut field = name.as_bytes()[..name.len().min(FIELD_LENGTH - 1)].to_vec();
+                field.resize(FIELD_LENGTH, 0);
+                names.extend(field);
+            }
+            element(&mut body, MI_INT8, &names);
+            for (_, field) in fields {
+                body.extend(matrix("", field));
+            }
+        }
+    }
+
+    let mut stream = Vec::new();
+    element(&mut stream, MI_MATRIX, &body);
+    stream
+}
+
+/// Writes the tag and `data` padded to 8 bytes.
+fn element(stream: &mut Vec<u8>, data_type: u32, data: &[u8]) {
+    utils::write(stream, data_type, true);
+    utils::write(stream, data.len() as u32, true);
+    stream.extend(data);
+    stream.resize(stream.len().next_multiple_of(8), 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use chrono::TimeZone;
+    use flate2::read::ZlibDecoder;
+
+    use super::*;
+    use crate::utils::TempFile;
+
+    fn signal() -> Signal {
+        Signal::new(
+            vec![0.0, 0.5],
+            vec![Record::Uint(1), Record::Uint(2)],
+            "V".to_string(),
+            "ASAM.M.voltage".to_string(),
+            "".to_string(),
+            false,
+        )
+    }
+
+    fn tag(stream: &[u8], position: usize) -> (u32, u32) {
+        let mut pos = position;
+        (
+            utils::read(stream, true, &mut pos),
+            utils::read(stream, true, &mut pos),
+        )
+    }
+
+    #[test]
+    fn write() {
+        let string = matrix("", &Array::Char("ab".to_string()));
+        assert_eq!(
+            string,
+            [
+                [14, 0, 0, 0, 56, 0, 0, 0],
+                [6, 0, 0, 0, 8, 0, 0, 0],
+                [4, 0, 0, 0, 0, 0, 0, 0],
+                [5, 0, 0, 0, 8, 0, 0, 0],
+                [1, 0, 0, 0, 2, 0, 0, 0],
+                [1, 0, 0, 0, 0, 0, 0, 0],
+                [4, 0, 0, 0, 4, 0, 0, 0],
+                [b'a', 0, b'b', 0, 0, 0, 0, 0],
+            ]
+            .concat()
+        );
+
+        let variable = matrix("ASAM_M_voltage", &channel(&signal()));
+        assert_eq!(variable.len() % 8, 0);
+        assert_eq!(tag(&variable, 0), (MI_MATRIX, variable.len() as u32 - 8));
+        assert_eq!(tag(&variable, 40), (MI_INT8, 14));
+        assert_eq!(&variable[48..62], b"ASAM_M_voltage");
+        // Field name length and the four names
+        assert_eq!(tag(&variable, 64), (MI_INT32, 4));
+        assert_eq!(tag(&variable, 80), (MI_INT8, 128));
+        assert_eq!(&variable[88..92], b"time");
+
+        let matrix = group(&[signal(), signal()]);
+        assert_eq!(
+            matrix,
+            Array::Double {
+                rows: 2,
+                columns: 3,
+                values: vec![0.0, 0.5, 1.0, 2.0, 1.0, 2.0],
+            }
+        );
+
+        let mut mixed = signal();
+        mixed.samples[1] = Record::StringNullTerm("high".to_string());
+        let Array::Struct(fields) = channel(&mixed) else {
+            panic!("Channels are structs");
+        };
+        assert_eq!(
+            fields[1].1,
+            Array::Cell(vec![
+                Array::Char("1".to_string()),
+                Array::Char("high".to_string())
+            ])
+        );
+        assert_eq!(group(&[signal(), mixed]), group(&[signal()]));
+    }
+
+    #[test]
+    fn write_file() {
+        let measurement = || Measurement {
+            comment: String::new(),
+            start_time: Utc.timestamp_nanos(0),
+            events: Vec::new(),
+            attachments: Vec::new(),
+            groups: vec![(0, 0, "engine".to_string(), vec![signal()])],
+        };
+        let file = TempFile::create("export.mat");
+        let filename = file.path();
+
+        super::write(measurement(), filename, &ExportOptions::default()).unwrap();
+        let stream = fs::read(filename).unwrap();
+        assert!(stream.starts_with(b"MATLAB 5.0 MAT-file"));
+        assert_eq!(&stream[124..128], &[0x00, 0x01, b'I', b'M']);
+        assert_eq!(
+            &stream[128..],
+            &matrix("ASAM_M_voltage", &channel(&signal()))[..]
+        );
+
+        let options = ExportOptions {
+            mat_layout: MatLayout::Groups,
+            compress: true,
+            ..ExportOptions::default()
+        };
+        super::write(measurement(), filename, &options).unwrap();
+        let stream = fs::read(filename).unwrap();
+        let (data_type, length) = tag(&stream, 128);
+        assert_eq!(data_type, MI_COMPRESSED);
+        assert_eq!(stream.len(), 136 + length as usize);
+        let mut decompressed = Vec::new();
+        ZlibDecoder::new(&stream[136..])
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, super::matrix("engine", &group(&[signal()])));
+    }
+
+    #[test]
+    fn names() {
+        let mut used = Vec::new();
+        assert_eq!(matlab_name("ASAM.M.speed", &mut used), "ASAM_M_speed");
+        assert_eq!(matlab_name("ASAM.M:speed", &mut used), "ASAM_M_speed_1");
+        assert_eq!(matlab_name("1st gear", &mut used), "x1st_gear");
+        assert_eq!(matlab_name("", &mut used), "x");
+
+        let long = "a".repeat(80);
+        assert_eq!(matlab_name(&long, &mut used), "a".repeat(63));
+        assert_eq!(
+            matlab_name(&long, &mut used),
+            format!("{}_1", "a".repeat(61))
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::mdf::{ChannelSelector, Measurement, RasterType};
+use crate::signal::Interpolation;
+
+pub mod csv;
+pub mod json;
+pub mod mat;
+
+/// Settings for [`crate::mdf::MDFFile::export`], formats ignore the settings
+/// they have no use for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOptions {
+    /// Writes all groups to one file after resampling onto the raster,
+    /// otherwise each channel group gets its own file.
+    pub raster: Option<RasterType>,
+    pub interpolation: Interpolation,
+    /// Writes the samples before the channel conversion.
+    pub raw: bool,
+    pub delimiter: char,
+    pub decimal_separator: char,
+    /// Digits after the decimal separator, all significant digits if `None`.
+    pub precision: Option<usize>,
+    pub time_format: TimeFormat,
+    pub quoting: Quoting,
+    pub mat_layout: MatLayout,
+    /// Compresses each MAT-file variable with zlib.
+    pub compress: bool,
+    /// Exports only the channels picked by any of these, all when empty.
+    pub channels: Vec<ChannelSelector>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            raster: None,
+            interpolation: Interpolation::RepeatPreviousSample,
+            raw: false,
+            delimiter: ',',
+            decimal_separator: '.',
+            precision: None,
+            time_format: TimeFormat::Relative,
+            quoting: Quoting::Needed,
+            mat_layout: MatLayout::Channels,
+            compress: false,
+            channels: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// Seconds from the start of the recording.
+    Relative,
+    /// ISO-8601 date and time in UTC.
+    Absolute,
+}
+
+/// When text samples and header cells are put in double quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quoting {
+    Always,
+    /// Only text containing the delimiter, a quote or a line break.
+    Needed,
+    Never,
+}
+
+/// How channels are laid out in a MAT-file, variable names are made MATLAB
+/// safe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatLayout {
+    /// A struct with time, data, unit and comment fields per channel.
+    Channels,
+    /// A matrix per channel group with the time in the first column, named
+    /// after the group comment.
+    Groups,
+}
+
+/// Writes `measurement` as `format`, "csv", "mat" or "json".
+pub fn export(
+    measurement: Measurement,
+    format: &str,
+    filename: &str,
+    options: &ExportOptions,
+) -> io::Result<()> {
+    match format {
+        "csv" => csv::write(measurement, filename, options),
+        "mat" => mat::write(measurement, filename, options),
+        "json" => json::write(measurement, filename, options),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Unsupported export format",
+        )),
+    }
+}
+
+/// `filename` with the channel group index added before the extension, e.g.
+/// "out.ChannelGroup_0.csv".
+pub fn group_path(filename: &str, index: usize) -> PathBuf {
+    let path = Path::new(filename);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!(
+            "{stem}.ChannelGroup_{index}.{}",
+            extension.to_string_lossy()
+        ),
+        None => format!("{stem}.ChannelGroup_{index}"),
+    };
+    path.with_file_name(name)
+}
@@ -0,0 +1,362 @@
+//! C interface, declared in `include/rsmdf.h`.
+//!
+//! Functions returning a count or index return -1 on failure and set the
+//! message returned by [`rsmdf_last_error`]. Strings handed out stay valid
+//! until the file is closed.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+use crate::mdf::{MDFFile, MDF};
+use crate::record::Record;
+use crate::signal::Signal;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// An open file, opaque to C.
+pub struct RsmdfFile {
+    mdf: MDF,
+    names: Vec<CString>,
+    /// Unit and comment of each channel, read on first use.
+    details: Vec<Option<(CString, CString)>>,
+}
+
+impl RsmdfFile {
+    fn signal(&self, index: usize) -> Result<Signal, String> {
+        let channel = self
+            .mdf
+            .channels
+            .get(index)
+            .ok_or(format!("Channel index {index} out of range"))?;
+        Ok(self.mdf.read_channel(channel).physical())
+    }
+
+    fn details(&mut self, index: usize) -> Result<&(CString, CString), String> {
+        if self.details.get(index).is_some_and(Option::is_none) {
+            // From the channel metadata, without reading the samples
+            let channel = &self.mdf.channels[index];
+            let info =
+                self.mdf
+                    .channel_info(channel.data_group, channel.channel_group, channel.channel);
+            self.details[index] = Some((c_string(&info.unit), c_string(&info.comment)));
+        }
+
+        self.details
+            .get(index)
+            .and_then(Option::as_ref)
+            .ok_or(format!("Channel index {index} out of range"))
+    }
+}
+
+fn c_string(text: &str) -> CString {
+    CString::new(text.replace('\0', "")).unwrap_or_default()
+}
+
+fn set_error(message: &str) {
+    LAST_ERROR.with(|x| *x.borrow_mut() = Some(c_string(message)));
+}
+
+/// Runs `f`, turning errors and panics into `fallback` and a last error.
+fn guard<T>(fallback: T, f: impl FnOnce() -> Result<T, String>) -> T {
+    LAST_ERROR.with(|x| *x.borrow_mut() = None);
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_error(&message);
+            fallback
+        }
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| panic.downcast_ref::<&str>().map(|x| x.to_string()))
+                .unwrap_or_else(|| "Unknown error".to_string());
+            set_error(&message);
+            fallback
+        }
+    }
+}
+
+fn file<'a>(file: *mut RsmdfFile) -> Result<&'a mut RsmdfFile, String> {
+    // Safety: callers pass a handle from `rsmdf_open` or null
+    unsafe { file.as_mut() }.ok_or("File handle is null".to_string())
+}
+
+fn sample(record: &Record) -> f64 {
+    match record {
+        Record::StringNullTerm(_) => f64::NAN,
+        record => record.extract(),
+    }
+}
+
+/// The message of the last failed call on this thread, or null.
+#[no_mangle]
+pub extern "C" fn rsmdf_last_error() -> *const c_char {
+    LAST_ERROR.with(|x| x.borrow().as_ref().map_or(ptr::null(), |x| x.as_ptr()))
+}
+
+/// Opens an MDF 3 or 4 file, returns null on failure.
+///
+/// # Safety
+/// `path` must be a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rsmdf_open(path: *const c_char) -> *mut RsmdfFile {
+    guard(ptr::null_mut(), || {
+        if path.is_null() {
+            return Err("Path is null".to_string());
+        }
+        let path = CStr::from_ptr(path)
+            .to_str()
+            .map_err(|_| "Path is not UTF-8".to_string())?;
+        if !Path::new(path).is_file() {
+            return Err(format!("{path} is not a file"));
+        }
+
+        let mdf = MDF::open(path).map_err(|x| x.to_string())?;
+        let names = mdf.channels.iter().map(|x| c_string(&x.name)).collect();
+        let details = vec![None; mdf.channels.len()];
+        Ok(Box::into_raw(Box::new(RsmdfFile {
+            mdf,
+            names,
+            details,
+        })))
+    })
+}
+
+/// Closes the file, null is ignored.
+///
+/// # Safety
+/// `file` must come from [`rsmdf_open`] and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn rsmdf_close(file: *mut RsmdfFile) {
+    if !file.is_null() {
+        drop(Box::from_raw(file));
+    }
+}
+
+/// # Safety
+/// `file` must come from [`rsmdf_open`].
+#[no_mangle]
+pub unsafe extern "C" fn rsmdf_channel_count(file: *mut RsmdfFile) -> i64 {
+    guard(-1, || Ok(self::file(file)?.names.len() as i64))
+}
+
+/// Name of the channel at `index`, or null.
+///
+/// # Safety
+/// `file` must come from [`rsmdf_open`].
+#[no_mangle]
+pub unsafe extern "C" fn rsmdf_channel_name(file: *mut RsmdfFile, index: usize) -> *const c_char {
+    guard(ptr::null(), || {
+        self::file(file)?
+            .names
+            .get(index)
+            .map(|x| x.as_ptr())
+            .ok_or(format!("Channel index {index} out of range"))
+    })
+}
+
+/// Index of the channel at `address`, a name optionally qualified as
+/// "name@group_comment", "source::name" or "data_group/channel_group/name".
+/// Returns -1 if there is none or several.
+///
+/// # Safety
+/// `file` must come from [`rsmdf_open`], `address` must be NUL terminated.
+#[no_mangle]
+pub unsafe extern "C" fn rsmdf_find_channel(file: *mut RsmdfFile, address: *const c_char) -> i64 {
+    guard(-1, || {
+        let file = self::file(file)?;
+        if address.is_null() {
+            return Err("Address is null".to_string());
+        }
+        let address = CStr::from_ptr(address)
+            .to_str()
+            .map_err(|_| "Address is not UTF-8".to_string())?;
+        let channel = file.mdf.search_channels(address)?;
+        file.mdf
+            .channels
+            .iter()
+            .position(|x| *x == channel)
+            .map(|x| x as i64)
+            .ok_or("Channel not found".to_string())
+    })
+}
+
+/// Unit of the channel at `index`, or null.
+///
+/// # Safety
+/// `file` must come from [`rsmdf_open`].
+#[no_mangle]
+pub unsafe extern "C" fn rsmdf_channel_unit(file: *mut RsmdfFile, index: usize) -> *const c_char {
+    guard(ptr::null(), || {
+        Ok(self::file(file)?.details(index)?.0.as_ptr())
+    })
+}
+
+/// Comment of the channel at `index`, or null.
+///
+/// # Safety
+/// `file` must come from [`rsmdf_open`].
+#[no_mangle]
+pub unsafe extern "C" fn rsmdf_channel_comment(
+    file: *mut RsmdfFile,
+    index: usize,
+) -> *const c_char {
+    guard(ptr::null(), || {
+        Ok(self::file(file)?.details(index)?.1.as_ptr())
+    })
+}
+
+/// Copies up to `capacity` physical values and timestamps of the channel at
+/// `index` into the buffers, either may be null. Returns the number of
+/// samples in the channel, which may be more than `capacity`. Text samples
+/// read as NaN.
+///
+/// # Safety
+/// `file` must come from [`rsmdf_open`], non null buffers must hold
+/// `capacity` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn rsmdf_read_channel(
+    file: *mut RsmdfFile,
+    index: usize,
+    values: *mut f64,
+    timestamps: *mut f64,
+    capacity: usize,
+) -> i64 {
+    guard(-1, || {
+        let signal = self::file(file)?.signal(index)?;
+        let count = signal.len().min(capacity);
+        if !values.is_null() {
+            let values = std::slice::from_raw_parts_mut(values, count);
+            for (value, record) in values.iter_mut().zip(&signal.samples) {
+                *value = sample(record);
+            }
+        }
+        if !timestamps.is_null() {
+            std::slice::from_raw_parts_mut(timestamps, count)
+                .copy_from_slice(&signal.timestamps[..count]);
+        }
+        Ok(signal.len() as i64)
+    })
+}
+
+/// Like [`rsmdf_read_channel`] with buffers allocated by the library, free
+/// them with [`rsmdf_free_buffer`]. Returns the number of samples.
+///
+/// # Safety
+/// `file` must come from [`rsmdf_open`], `values` and `timestamps` must be
+/// valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn rsmdf_read_channel_alloc(
+    file: *mut RsmdfFile,
+    index: usize,
+    values: *mut *mut f64,
+    timestamps: *mut *mut f64,
+) -> i64 {
+    guard(-1, || {
+        if values.is_null() || timestamps.is_null() {
+            return Err("Output pointer is null".to_string());
+        }
+        let signal = self::file(file)?.signal(index)?;
+        let samples: Box<[f64]> = signal.samples.iter().map(sample).collect();
+        *values = Box::into_raw(samples).cast();
+        *timestamps = Box::into_raw(signal.timestamps.into_boxed_slice()).cast();
+        Ok(signal.samples.len() as i64)
+    })
+}
+
+/// Frees a buffer from [`rsmdf_read_channel_alloc`], null is ignored.
+///
+/// # Safety
+/// `buffer` and `length` must be as returned by
+/// [`rsmdf_read_channel_alloc`].
+#[no_mangle]
+pub unsafe extern "C" fn rsmdf_free_buffer(buffer: *mut f64, length: usize) {
+    if !buffer.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(buffer, length)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header() {
+        let header = include_str!("../include/rsmdf.h");
+        let source = include_str!("ffi.rs");
+
+        let exported: Vec<&str> = source
+            .split("extern \"C\" fn ")
+            .skip(1)
+            .map(|x| &x[..x.find('(').unwrap()])
+            .collect();
+        assert_eq!(exported.len(), 11);
+        for name in exported {
+            assert!(header.contains(&format!("{name}(")), "{name} missing");
+        }
+    }
+
+    #[test]
+    fn read() {
+        unsafe {
+            let path = c"example_files/ASAP2_Demo_V171.mf4";
+            let file = rsmdf_open(path.as_ptr());
+            assert!(!file.is_null());
+            assert!(rsmdf_last_error().is_null());
+
+            let index = rsmdf_find_channel(file, c"ASAM.M.SCALAR.UBYTE.IDENTICAL".as_ptr());
+            assert!(index > 0);
+            let index = index as usize;
+            assert_eq!(CStr::from_ptr(rsmdf_channel_unit(file, index)), c"hours");
+            let signal = (*file).signal(index).unwrap();
+            assert_eq!(
+                CStr::from_ptr(rsmdf_channel_comment(file, index)).to_str(),
+                Ok(signal.comment.as_str())
+            );
+
+            let length = rsmdf_read_channel(file, index, ptr::null_mut(), ptr::null_mut(), 0);
+            let mut values = vec![0.0; length as usize];
+            let mut timestamps = vec![0.0; length as usize];
+            let read = rsmdf_read_channel(
+                file,
+                index,
+                values.as_mut_ptr(),
+                timestamps.as_mut_ptr(),
+                values.len(),
+            );
+            assert_eq!(read, length);
+
+            let (mut owned, mut owned_timestamps) = (ptr::null_mut(), ptr::null_mut());
+            let count = rsmdf_read_channel_alloc(file, index, &mut owned, &mut owned_timestamps);
+            assert_eq!(count, length);
+            assert_eq!(std::slice::from_raw_parts(owned, values.len()), &values[..]);
+            assert_eq!(
+                std::slice::from_raw_parts(owned_timestamps, values.len()),
+                &timestamps[..]
+            );
+            rsmdf_free_buffer(owned, count as usize);
+            rsmdf_free_buffer(owned_timestamps, count as usize);
+
+            assert_eq!(
+                rsmdf_read_channel(file, 100_000, ptr::null_mut(), ptr::null_mut(), 0),
+                -1
+            );
+            assert!(!rsmdf_last_error().is_null());
+            assert!(rsmdf_channel_name(file, 100_000).is_null());
+            rsmdf_close(file);
+
+            assert!(rsmdf_open(c"missing.mf4".as_ptr()).is_null());
+            assert_eq!(
+                CStr::from_ptr(rsmdf_last_error()),
+                c"missing.mf4 is not a file"
+            );
+            assert_eq!(rsmdf_channel_count(ptr::null_mut()), -1);
+        }
+    }
+}
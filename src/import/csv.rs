@@ -0,0 +1,273 @@
+use chrono::{DateTime, Utc};
+
+use super::ImportOptions;
+use crate::record::Record;
+use crate::signal::Signal;
+
+/// Header names taken as the time column, compared ignoring case.
+const TIME_NAMES: [&str; 4] = ["time", "t", "timestamp", "timestamps"];
+
+/// The signals of a CSV file, all on the timebase of its time column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    /// Set when the time column holds ISO-8601 dates, the timestamps are
+    /// then seconds from the first one.
+    pub start_time: Option<DateTime<Utc>>,
+    pub signals: Vec<Signal>,
+}
+
+/// Reads a CSV file with a header row of names, units given as "name [unit]"
+/// or "name (unit)". Columns holding only integers become integer signals,
+/// only numbers float64 signals with NaN for empty cells, anything else text.
+pub fn read(text: &str, options: &ImportOptions) -> Result<Table, &'static str> {
+    let delimiter = options.delimiter.unwrap_or_else(|| detect_delimiter(text));
+    let mut rows = rows(text, delimiter).into_iter();
+    let header = rows.next().ok_or("The CSV file is empty")?;
+    let rows: Vec<Vec<String>> = rows
+        .filter(|x| x.iter().any(|cell| !cell.trim().is_empty()))
+        .collect();
+    let columns: Vec<Vec<&str>> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row.get(i).map_or("", |x| x.trim()))
+                .collect()
+        })
+        .collect();
+
+    let time = time_column(&header, &columns, options)?;
+    let (start_time, timestamps) = timestamps(&columns[time], options)?;
+
+    let signals = header
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != time)
+        .map(|(i, cell)| {
+            let (name, unit) = name_unit(cell);
+            Signal::new(
+                timestamps.clone(),
+                samples(&columns[i], options),
+                unit,
+                name,
+                String::new(),
+                false,
+            )
+        })
+        .collect();
+
+    Ok(Table {
+        start_time,
+        signals,
+    })
+}
+
+/// Splits `text` into rows of fields, fields may be quoted with `"` and
+/// contain doubled quotes, delimiters and line breaks.
+fn rows(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if quoted => field.push(c),
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
+fn detect_delimiter(text: &str) -> char {
+    let header = text.lines().next().unwrap_or_default();
+    [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|&x| header.matches(x).count())
+        .filter(|&x| header.contains(x))
+        .unwrap_or(',')
+}
+
+/// Splits a header cell into name and unit.
+fn name_unit(cell: &str) -> (String, String) {
+    let cell = cell.trim();
+    for (open, close) in [('[', ']'), ('(', ')')] {
+        if let (Some(start), true) = (cell.rfind(open), cell.ends_with(close)) {
+            let unit = &cell[start + 1..cell.len() - 1];
+            return (cell[..start].trim().to_string(), unit.trim().to_string());
+        }
+    }
+
+    (cell.to_string(), String::new())
+}
+
+/// The column named in the options, otherwise one with a time like name or
+/// the first numeric column that never decreases.
+fn time_column(
+    header: &[String],
+    columns: &[Vec<&str>],
+    options: &ImportOptions,
+) -> Result<usize, &'static str> {
+    let names: Vec<String> = header.iter().map(|x| name_unit(x).0).collect();
+    if let Some(time) = &options.time_column {
+        return names
+            .iter()
+            .position(|x| x == time)
+            .ok_or("Time column not found");
+    }
+
+    names
+        .iter()
+        .position(|x| TIME_NAMES.contains(&x.to_lowercase().as_str()))
+        .or_else(|| {
+            columns.iter().position(|column| {
+                let values: Option<Vec<f64>> = column.iter().map(|x| number(x, options)).collect();
+                values.is_some_and(|x| !x.is_empty() && x.windows(2).all(|x| x[0] <= x[1]))
+            })
+        })
+        .ok_or("No time column found")
+}
+
+fn timestamps(
+    column: &[&str],
+    options: &ImportOptions,
+) -> Result<(Option<DateTime<Utc>>, Vec<f64>), &'static str> {
+    if let Some(timestamps) = column.iter().map(|x| number(x, options)).collect() {
+        return Ok((None, timestamps));
+    }
+
+    let dates: Option<Vec<DateTime<Utc>>> = column
+        .iter()
+        .map(|x| {
+            DateTime::parse_from_rfc3339(x)
+                .ok()
+                .map(|x| x.with_timezone(&Utc))
+        })
+        .collect();
+    let dates = dates.ok_or("The time column holds neither numbers nor dates")?;
+    let start_time = match dates.first() {
+        Some(&start_time) => start_time,
+        None => return Ok((None, Vec::new())),
+    };
+    let timestamps = dates
+        .iter()
+        .map(|x| (*x - start_time).num_nanoseconds().unwrap_or(i64::MAX) as f64 / 1e9)
+        .collect();
+
+    Ok((Some(start_time), timestamps))
+}
+
+fn samples(column: &[&str], options: &ImportOptions) -> Vec<Record> {
+    let filled: Vec<&str> = column.iter().copied().filter(|x| !x.is_empty()).collect();
+    let complete = filled.len() == column.len();
+
+    if let (true, Some(integers)) = (
+        complete,
+        filled
+            .iter()
+            .map(|x| x.parse::<i64>().ok())
+            .collect::<Option<Vec<i64>>>(),
+    ) {
+        if integers.iter().all(|&x| x >= 0) {
+            return integers
+                .into_iter()
+                .map(|x| Record::Uint(x as u64))
+                .collect();
+        }
+        return integers.into_iter().map(Record::Int).collect();
+    }
+
+    if filled.iter().all(|x| number(x, options).is_some()) {
+        return column
+            .iter()
+            .map(|x| Record::Float64(number(x, options).unwrap_or(f64::NAN)))
+            .collect();
+    }
+
+    column
+        .iter()
+        .map(|x| Record::StringNullTerm(x.to_string()))
+        .collect()
+}
+
+fn number(cell: &str, options: &ImportOptions) -> Option<f64> {
+    match options.decimal_separator {
+        '.' => cell.parse().ok(),
+        separator => cell.replace(separator, ".").parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn read() {
+        let text = "Time [s];speed (km/h);gear;count;offset\r\n\
+                    0,0;1,5;\"first; low\";1;-1\r\n\
+                    0,5;;second;2;3\r\n\
+                    \r\n\
+                    1,0;2,25;\"\"\"third\"\"\";3;4\r\n";
+        let options = ImportOptions {
+            decimal_separator: ',',
+            ..ImportOptions::default()
+        };
+        let table = super::read(text, &options).unwrap();
+
+        assert_eq!(table.start_time, None);
+        let names: Vec<&str> = table.signals.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(names, vec!["speed", "gear", "count", "offset"]);
+        assert_eq!(table.signals[0].unit, "km/h");
+        assert_eq!(table.signals[0].timestamps, vec![0.0, 0.5, 1.0]);
+
+        let speed = &table.signals[0].samples;
+        assert_eq!(speed[0], Record::Float64(1.5));
+        assert!(matches!(speed[1], Record::Float64(x) if x.is_nan()));
+        assert_eq!(speed[2], Record::Float64(2.25));
+        assert_eq!(
+            table.signals[1].samples,
+            ["first; low", "second", "\"third\""].map(|x| Record::StringNullTerm(x.to_string()))
+        );
+        assert_eq!(table.signals[2].samples, [1, 2, 3].map(Record::Uint));
+        assert_eq!(table.signals[3].samples, [-1, 3, 4].map(Record::Int));
+    }
+
+    #[test]
+    fn read_time() {
+        let text = "when,level\n2020-09-13T12:26:40Z,1\n2020-09-13T12:26:40.500Z,2\n";
+        let options = ImportOptions {
+            time_column: Some("when".to_string()),
+            ..ImportOptions::default()
+        };
+        let table = super::read(text, &options).unwrap();
+        assert_eq!(
+            table.start_time,
+            Some(Utc.timestamp_nanos(1_600_000_000_000_000_000))
+        );
+        assert_eq!(table.signals[0].timestamps, vec![0.0, 0.5]);
+
+        let table = super::read("a\tb\n3\t1\n2\t2\n", &ImportOptions::default()).unwrap();
+        assert_eq!(table.signals[0].name, "a");
+        assert_eq!(table.signals[0].timestamps, vec![1.0, 2.0]);
+
+        assert!(super::read("a,b\nx,y\n", &ImportOptions::default()).is_err());
+        assert!(super::read("", &ImportOptions::default()).is_err());
+    }
+}
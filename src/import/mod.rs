@@ -0,0 +1,21 @@
+pub mod csv;
+
+/// Settings for [`crate::mdf::MDF::from_csv`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportOptions {
+    /// Detected from the header row if `None`, one of ',', ';' or tab.
+    pub delimiter: Option<char>,
+    pub decimal_separator: char,
+    /// Header name of the time column, detected if `None`.
+    pub time_column: Option<String>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            decimal_separator: '.',
+            time_column: None,
+        }
+    }
+}
@@ -1,8 +1,15 @@
+pub mod attachment;
+pub mod conversion;
+pub mod event;
+pub mod export;
+pub mod ffi;
+pub mod import;
 pub mod mdf;
-mod mdf3;
+pub mod mdf3;
 
 pub mod record;
 pub mod signal;
+pub mod storage;
 pub mod utils;
 
-mod mdf4;
+pub mod mdf4;
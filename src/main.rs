@@ -1,29 +1,404 @@
-use std::time::Instant;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
 
-use rsmdf::{mdf::MDFFile, mdf::MDF};
+use chrono::SecondsFormat;
+use rsmdf::export::json::{array, object, string};
+use rsmdf::export::ExportOptions;
+use rsmdf::mdf::{ChannelSearch, ChannelSelector, MDFFile, RasterType, MDF};
 
-fn main() {
-    let mdf = MDF::new("./example_files/ASAP2_Demo_V171_deflate.mf4");
+const USAGE: &str = "Usage: rsmdf <command> <file> [arguments]
 
-    mdf.list_channels();
+Commands:
+  info <file> [--json]                  Version, start time and channel groups
+  list <file> [--pattern GLOB | --regex REGEX] [--unit GLOB] [--ignore-case]
+      [--json]                          Channels with their units
+  export <file> <output> [channel or pattern]... [--format csv|mat|json]
+      [--raster SECONDS] [--raw]
+  cut <file> <output> [--start SECONDS] [--end SECONDS] [--include-ends]
+      [--time-from-zero]
+  filter <file> <output> <channel or pattern>...
+  convert <file> <output> --version 3.30|4.10|4.20
+  validate <file> [--json]
 
-    let start = Instant::now();
-    // let test = mdf.read(0, 0, 1);
+Channels are given as name, name@group_comment, source::name or
+data_group/channel_group/name, patterns take * and ? wildcards.
 
-    // let channel = mdf.search_channels("ASAM.M.SCALAR.SBYTE.IDENTICAL.DISCRETE");
-    // let channel = match channel {
-    //     Ok(x) => x,
-    //     Err(e) => panic!("{}", e),
-    // };
-    // let test = mdf.read_channel(channel);
+Exit codes: 0 success, 1 validation issues found, 2 error";
 
-    for channel in &mdf.channels() {
-        let test = &mdf.read_channel(channel);
-        println!("{}", test.comment);
+/// Options taking a value, everything else starting with "--" is a flag.
+const VALUE_OPTIONS: [&str; 8] = [
+    "pattern", "regex", "unit", "format", "raster", "start", "end", "version",
+];
+const FLAGS: [&str; 5] = [
+    "json",
+    "raw",
+    "include-ends",
+    "time-from-zero",
+    "ignore-case",
+];
+
+/// Commands printing their results, which can be printed as JSON.
+const JSON_COMMANDS: [&str; 3] = ["info", "list", "validate"];
+
+#[derive(Debug, PartialEq)]
+struct Arguments {
+    command: String,
+    positional: Vec<String>,
+    values: HashMap<String, String>,
+    flags: Vec<String>,
+}
+
+impl Arguments {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut args = args.iter();
+        let command = args.next().ok_or("No command given")?.clone();
+
+        let mut positional = Vec::new();
+        let mut values = HashMap::new();
+        let mut flags = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if VALUE_OPTIONS.contains(&name) => {
+                    let value = args.next().ok_or(format!("--{name} needs a value"))?;
+                    values.insert(name.to_string(), value.clone());
+                }
+                Some(name) if FLAGS.contains(&name) => flags.push(name.to_string()),
+                Some(name) => return Err(format!("Unknown option --{name}")),
+                None => positional.push(arg.clone()),
+            }
+        }
+
+        Ok(Self {
+            command,
+            positional,
+            values,
+            flags,
+        })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|x| x == name)
+    }
+
+    fn number(&self, name: &str) -> Result<Option<f64>, String> {
+        self.values
+            .get(name)
+            .map(|x| x.parse().map_err(|_| format!("--{name} needs a number")))
+            .transpose()
+    }
+
+    /// The positional argument at `index`, named `name` in errors.
+    fn positional(&self, index: usize, name: &str) -> Result<&str, String> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or(format!("Missing {name}"))
+    }
+
+    /// The channels given after the input and output file.
+    fn selectors(&self) -> Vec<ChannelSelector> {
+        self.positional
+            .iter()
+            .skip(2)
+            .map(|x| match x.contains(['*', '?']) {
+                true => ChannelSelector::Pattern(x.clone()),
+                false => ChannelSelector::Address(x.clone()),
+            })
+            .collect()
+    }
+
+    fn open(&self) -> Result<MDF, String> {
+        let path = self.positional(0, "input file")?;
+        if !Path::new(path).is_file() {
+            return Err(format!("{path} is not a file"));
+        }
+        MDF::open(path).map_err(|x| format!("{path}: {x}"))
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args[0] == "--help" || args[0] == "help" {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    match Arguments::parse(&args).and_then(|x| run(&x)) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &Arguments) -> Result<ExitCode, String> {
+    let command: fn(&Arguments) -> Result<ExitCode, String> = match args.command.as_str() {
+        "info" => info,
+        "list" => list,
+        "export" => export,
+        "cut" => cut,
+        "filter" => filter,
+        "convert" => convert,
+        "validate" => validate,
+        command => return Err(format!("Unknown command {command}")),
+    };
+    if args.flag("json") && !JSON_COMMANDS.contains(&args.command.as_str()) {
+        return Err(format!("{} has no --json output", args.command));
+    }
+
+    command(args)
+}
+
+fn info(args: &Arguments) -> Result<ExitCode, String> {
+    let mdf = args.open()?;
+    let size = fs::metadata(args.positional(0, "input file")?)
+        .map_err(|x| x.to_string())?
+        .len();
+    let start_time = mdf
+        .start_time()
+        .to_rfc3339_opts(SecondsFormat::AutoSi, true);
+
+    let mut groups: Vec<(usize, usize, usize)> = Vec::new();
+    for channel in &mdf.channels {
+        match groups.last_mut() {
+            Some((dg, cg, count)) if *dg == channel.data_group && *cg == channel.channel_group => {
+                *count += 1
+            }
+            _ => groups.push((channel.data_group, channel.channel_group, 1)),
+        }
+    }
+    let samples = |dg: usize, cg: usize| mdf.record_count(dg, cg);
+
+    if args.flag("json") {
+        let groups = groups.iter().map(|&(dg, cg, channels)| {
+            object(&[
+                ("data_group", dg.to_string()),
+                ("channel_group", cg.to_string()),
+                ("comment", string(&mdf.group_comment(dg, cg))),
+                ("channels", channels.to_string()),
+                ("samples", samples(dg, cg).to_string()),
+            ])
+        });
+        println!(
+            "{}",
+            object(&[
+                ("version", string(&mdf.version())),
+                ("size", size.to_string()),
+                ("start_time", string(&start_time)),
+                ("comment", string(&mdf.comment())),
+                ("groups", array(groups)),
+            ])
+        );
+    } else {
+        println!("Version:    {}", mdf.version());
+        println!("Size:       {size} bytes");
+        println!("Start time: {start_time}");
+        println!("Comment:    {}", mdf.comment());
+        println!("Groups:");
+        for (dg, cg, channels) in groups {
+            println!(
+                "  {dg}/{cg} {:?}: {channels} channels, {} samples",
+                mdf.group_comment(dg, cg),
+                samples(dg, cg)
+            );
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn list(args: &Arguments) -> Result<ExitCode, String> {
+    let mdf = args.open()?;
+    let mut search = match (args.values.get("pattern"), args.values.get("regex")) {
+        (Some(_), Some(_)) => return Err("Give either --pattern or --regex".to_string()),
+        (Some(pattern), None) => ChannelSearch::glob(pattern),
+        (None, Some(regex)) => ChannelSearch::regex(regex),
+        (None, None) => ChannelSearch::default(),
+    };
+    search.unit = args.values.get("unit").cloned();
+    search.ignore_case = args.flag("ignore-case");
+    let channels = mdf.find_channels(&search)?;
+
+    if args.flag("json") {
+        let channels = channels.iter().map(|x| {
+            object(&[
+                ("name", string(&x.channel.name)),
+                ("unit", string(&x.info.unit)),
+                ("group_comment", string(&x.group_comment)),
+                ("data_group", x.channel.data_group.to_string()),
+                ("channel_group", x.channel.channel_group.to_string()),
+                ("channel", x.channel.channel.to_string()),
+            ])
+        });
+        println!("{}", array(channels));
+    } else {
+        for x in channels {
+            println!(
+                "{}/{}/{}\t{}\t{}",
+                x.channel.data_group,
+                x.channel.channel_group,
+                x.channel.channel,
+                x.channel.name,
+                x.info.unit
+            );
+        }
     }
 
-    let test = mdf.read_channel(&mdf.channels[0]);
+    Ok(ExitCode::SUCCESS)
+}
+
+fn export(args: &Arguments) -> Result<ExitCode, String> {
+    let mdf = args.open()?;
+    let output = args.positional(1, "output file")?;
+    let format = match args.values.get("format") {
+        Some(format) => format.clone(),
+        None => Path::new(output)
+            .extension()
+            .map(|x| x.to_string_lossy().to_lowercase())
+            .ok_or("Give --format or an output file extension")?,
+    };
+    let options = ExportOptions {
+        raster: args.number("raster")?.map(RasterType::Step),
+        raw: args.flag("raw"),
+        channels: args.selectors(),
+        ..ExportOptions::default()
+    };
 
-    println!("Max Time: {}", test.max_time());
-    println!("Took: {:?}", start.elapsed());
+    mdf.export(&format, output, &options)
+        .map_err(|x| x.to_string())?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn cut(args: &Arguments) -> Result<ExitCode, String> {
+    let mdf = args.open()?;
+    let output = args.positional(1, "output file")?;
+    let start = args.number("start")?.unwrap_or(0.0);
+    let end = args.number("end")?.unwrap_or(f64::INFINITY);
+
+    let cut = mdf.cut(
+        start,
+        end,
+        args.flag("include-ends"),
+        args.flag("time-from-zero"),
+    );
+    for warning in &cut.warnings {
+        eprintln!("warning: {warning:?}");
+    }
+    cut.save(output).map_err(|x| x.to_string())?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn filter(args: &Arguments) -> Result<ExitCode, String> {
+    let mdf = args.open()?;
+    let output = args.positional(1, "output file")?;
+    let selectors = args.selectors();
+    if selectors.is_empty() {
+        return Err("Missing channels to keep".to_string());
+    }
+
+    mdf.filter(&selectors)
+        .save(output)
+        .map_err(|x| x.to_string())?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn convert(args: &Arguments) -> Result<ExitCode, String> {
+    let mdf = args.open()?;
+    let output = args.positional(1, "output file")?;
+    let version = args.values.get("version").ok_or("Missing --version")?;
+
+    let converted = mdf.convert(version)?;
+    for warning in &converted.warnings {
+        eprintln!("warning: {warning:?}");
+    }
+    converted.save(output).map_err(|x| x.to_string())?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn validate(args: &Arguments) -> Result<ExitCode, String> {
+    let mdf = args.open()?;
+    let issues = mdf.validate();
+
+    if args.flag("json") {
+        let list = issues.iter().map(|x| string(&x.to_string()));
+        println!(
+            "{}",
+            object(&[
+                ("valid", issues.is_empty().to_string()),
+                ("issues", array(list)),
+            ])
+        );
+    } else if issues.is_empty() {
+        println!("OK");
+    } else {
+        for issue in &issues {
+            println!("{issue}");
+        }
+    }
+
+    Ok(match issues.is_empty() {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::from(1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Arguments, String> {
+        let args: Vec<String> = args.iter().map(|x| x.to_string()).collect();
+        Arguments::parse(&args)
+    }
+
+    #[test]
+    fn arguments() {
+        let args = parse(&["cut", "in.mf4", "--start", "1.5", "out.mf4", "--json"]).unwrap();
+        assert_eq!(args.command, "cut");
+        assert_eq!(args.positional, vec!["in.mf4", "out.mf4"]);
+        assert_eq!(args.number("start"), Ok(Some(1.5)));
+        assert_eq!(args.number("end"), Ok(None));
+        assert!(args.flag("json"));
+        assert!(!args.flag("raw"));
+
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["cut", "--start"]).is_err());
+        assert!(parse(&["cut", "--unknown"]).is_err());
+        assert!(parse(&["cut", "--start", "x"])
+            .unwrap()
+            .number("start")
+            .is_err());
+    }
+
+    #[test]
+    fn json() {
+        let run = |args: &[&str]| run(&parse(args).unwrap());
+        for command in ["export", "cut", "filter", "convert"] {
+            assert_eq!(
+                run(&[command, "in.mf4", "out.mf4", "--json"]),
+                Err(format!("{command} has no --json output"))
+            );
+        }
+        assert_eq!(
+            run(&["info", "in.mf4", "--json"]),
+            Err("in.mf4 is not a file".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_file() {
+        let run = |args: &[&str]| run(&parse(args).unwrap());
+        assert_eq!(
+            run(&["info", "README.md"]),
+            Err("README.md: Not an MDF file".to_string())
+        );
+        assert_eq!(
+            run(&["validate", "Cargo.toml", "--json"]),
+            Err("Cargo.toml: Not an MDF file".to_string())
+        );
+    }
 }
@@ -1,10 +1,25 @@
-use std::fs::File;
-use std::io::Read;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io;
+use std::ops::Range;
+use std::path::Path;
 
+use chrono::{DateTime, Duration, Utc};
+use regex::RegexBuilder;
+
+use crate::attachment::Attachment;
+use crate::conversion::Conversion;
+use crate::event::Event;
+use crate::export::ExportOptions;
+use crate::import::{csv, ImportOptions};
 use crate::mdf3::mdf3_file::MDF3;
+use crate::mdf3::mdf3_writer::Mdf3Writer;
 use crate::mdf4::mdf4_file::MDF4;
-use crate::record::Record;
-use crate::signal::Signal;
+use crate::mdf4::mdf4_writer::Mdf4Writer;
+use crate::record::{DataType, Record, RecordView};
+use crate::signal::{Interpolation, Signal, Source};
+use crate::storage::{Storage, Stream};
 use crate::utils;
 
 #[derive(PartialEq)]
@@ -19,34 +34,56 @@ enum MDFType {
 }
 
 impl MDFType {
-    fn check_version(filepath: &str) -> MDFVersion {
-        let mut file = File::open(filepath).expect("Could not read file");
-        let mut id_stream = [0_u8; 128];
-        file.read_exact(&mut id_stream).unwrap();
+    fn rebuild(&self, measurement: &Measurement) -> Self {
+        match self {
+            Self::MDF3(file) => Self::MDF3(file.rebuild(measurement)),
+            Self::MDF4(file) => Self::MDF4(file.rebuild(measurement)),
+        }
+    }
 
+    fn version(id_stream: &dyn Stream) -> Result<MDFVersion, &'static str> {
+        if id_stream.len() < 64 {
+            return Err("Not an MDF file");
+        }
         let mut pos = 0;
         let little_endian = true;
 
-        let id_file: [u8; 8] = utils::read(&id_stream, little_endian, &mut pos);
-        let id_vers: [u8; 8] = utils::read(&id_stream, little_endian, &mut pos);
-        let _id_prog: [u8; 8] = utils::read(&id_stream, little_endian, &mut pos);
-        let _id_reserved1: [u8; 4] = utils::read(&id_stream, little_endian, &mut pos);
-        let _id_ver: u16 = utils::read(&id_stream, little_endian, &mut pos);
-        let _id_reserved2: [u8; 34] = utils::read(&id_stream, little_endian, &mut pos);
+        let id_file: [u8; 8] = utils::read(id_stream, little_endian, &mut pos);
+        let id_vers: [u8; 8] = utils::read(id_stream, little_endian, &mut pos);
+        let _id_prog: [u8; 8] = utils::read(id_stream, little_endian, &mut pos);
+        let _id_reserved1: [u8; 4] = utils::read(id_stream, little_endian, &mut pos);
+        let _id_ver: u16 = utils::read(id_stream, little_endian, &mut pos);
+        let _id_reserved2: [u8; 34] = utils::read(id_stream, little_endian, &mut pos);
 
-        if !utils::eq(&id_file, &[b'M', b'D', b'F', b' ', b' ', b' ', b' ', b' ']) {
-            panic!("Error: Unknown file type");
+        if !utils::eq(&id_file, b"MDF     ") {
+            return Err("Not an MDF file");
         }
 
         let s = String::from_utf8_lossy(&id_vers).into_owned();
-        let mut version = s.split('.');
-        let major_version = version.next().unwrap().parse::<usize>().unwrap();
-        //let _minor_version = version.next().unwrap().parse::<usize>().unwrap();
+        let major_version = s.split('.').next().map(|x| x.trim().parse::<usize>());
 
         match major_version {
-            3 => MDFVersion::MDF3,
-            4 => MDFVersion::MDF4,
-            _ => panic!("Unknown MDF file version"),
+            Some(Ok(3)) => Ok(MDFVersion::MDF3),
+            Some(Ok(4)) => Ok(MDFVersion::MDF4),
+            _ => Err("Unsupported MDF version"),
+        }
+    }
+
+    fn from_bytes(stream: Vec<u8>) -> Self {
+        Self::from_storage(Storage::Memory(stream)).unwrap_or_else(|x| panic!("{x}"))
+    }
+
+    fn from_storage(stream: Storage) -> Result<Self, &'static str> {
+        match Self::version(&stream)? {
+            MDFVersion::MDF3 => MDF3::from_storage(stream).map(MDFType::MDF3),
+            MDFVersion::MDF4 => MDF4::from_storage(stream).map(MDFType::MDF4),
+        }
+    }
+
+    fn storage(&self) -> &Storage {
+        match self {
+            Self::MDF3(file) => file.storage(),
+            Self::MDF4(file) => file.storage(),
         }
     }
 }
@@ -77,13 +114,76 @@ impl MDFFile for MDFType {
         }
     }
 
-    #[must_use]
+    fn records(&self, datagroup: usize, channel_grp: usize) -> RecordView<'_> {
+        match self {
+            Self::MDF3(file) => file.records(datagroup, channel_grp),
+            Self::MDF4(file) => file.records(datagroup, channel_grp),
+        }
+    }
+
     fn new(filepath: &str) -> Self {
-        let version = MDFType::check_version(filepath);
+        Self::from_storage(Storage::new(filepath).expect("Could not read file"))
+            .unwrap_or_else(|x| panic!("{x}"))
+    }
+
+    fn comment(&self) -> String {
+        match self {
+            Self::MDF3(file) => file.comment(),
+            Self::MDF4(file) => file.comment(),
+        }
+    }
+
+    fn start_time(&self) -> DateTime<Utc> {
+        match self {
+            Self::MDF3(file) => file.start_time(),
+            Self::MDF4(file) => file.start_time(),
+        }
+    }
+
+    fn events(&self) -> Vec<Event> {
+        match self {
+            Self::MDF3(file) => file.events(),
+            Self::MDF4(file) => file.events(),
+        }
+    }
+
+    fn attachments(&self) -> Vec<Attachment> {
+        match self {
+            Self::MDF3(file) => file.attachments(),
+            Self::MDF4(file) => file.attachments(),
+        }
+    }
+
+    fn group_comment(&self, datagroup: usize, channel_grp: usize) -> String {
+        match self {
+            Self::MDF3(file) => file.group_comment(datagroup, channel_grp),
+            Self::MDF4(file) => file.group_comment(datagroup, channel_grp),
+        }
+    }
+
+    fn record_count(&self, datagroup: usize, channel_grp: usize) -> usize {
+        match self {
+            Self::MDF3(file) => file.record_count(datagroup, channel_grp),
+            Self::MDF4(file) => file.record_count(datagroup, channel_grp),
+        }
+    }
 
-        match version {
-            MDFVersion::MDF3 => MDFType::MDF3(MDF3::new(filepath)),
-            MDFVersion::MDF4 => MDFType::MDF4(MDF4::new(filepath)),
+    fn channel_info(&self, datagroup: usize, channel_grp: usize, channel: usize) -> ChannelInfo {
+        match self {
+            Self::MDF3(file) => file.channel_info(datagroup, channel_grp, channel),
+            Self::MDF4(file) => file.channel_info(datagroup, channel_grp, channel),
+        }
+    }
+
+    fn has_unsupported_conversion(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channel: usize,
+    ) -> bool {
+        match self {
+            Self::MDF3(file) => file.has_unsupported_conversion(datagroup, channel_grp, channel),
+            Self::MDF4(file) => file.has_unsupported_conversion(datagroup, channel_grp, channel),
         }
     }
 
@@ -108,7 +208,6 @@ impl MDFFile for MDFType {
         }
     }
 
-    #[must_use]
     fn read(&self, datagroup: usize, channel_grp: usize, channel: usize) -> Signal {
         match self {
             Self::MDF3(file) => file.read(datagroup, channel_grp, channel),
@@ -116,71 +215,526 @@ impl MDFFile for MDFType {
         }
     }
 
-    fn cut(&self, start: f64, end: f64, include_ends: bool, time_from_zero: bool) {
+    fn cut(&self, start: f64, end: f64, include_ends: bool, time_from_zero: bool) -> Self {
         match self {
-            Self::MDF3(file) => file.cut(start, end, include_ends, time_from_zero),
-            Self::MDF4(file) => file.cut(start, end, include_ends, time_from_zero),
+            Self::MDF3(file) => Self::MDF3(file.cut(start, end, include_ends, time_from_zero)),
+            Self::MDF4(file) => Self::MDF4(file.cut(start, end, include_ends, time_from_zero)),
         }
     }
 
-    fn export(&self, format: &str, filename: &str) {
+    fn export(&self, format: &str, filename: &str, options: &ExportOptions) -> io::Result<()> {
         match self {
-            Self::MDF3(file) => file.export(format, filename),
-            Self::MDF4(file) => file.export(format, filename),
+            Self::MDF3(file) => file.export(format, filename, options),
+            Self::MDF4(file) => file.export(format, filename, options),
         }
     }
 
-    fn filter(&self, channels: &str) {
+    fn filter(&self, channels: &[ChannelSelector]) -> Self {
         match self {
-            Self::MDF3(file) => file.filter(channels),
-            Self::MDF4(file) => file.filter(channels),
+            Self::MDF3(file) => Self::MDF3(file.filter(channels)),
+            Self::MDF4(file) => Self::MDF4(file.filter(channels)),
         }
     }
 
-    #[must_use]
-    fn resample(&self, raster: RasterType, version: &str, time_from_zero: bool) -> Self {
+    fn resample(
+        &self,
+        raster: &RasterType,
+        interpolation: Interpolation,
+        time_from_zero: bool,
+    ) -> Result<Self, &'static str> {
         match self {
-            Self::MDF3(file) => Self::MDF3(file.resample(raster, version, time_from_zero)),
-            Self::MDF4(file) => Self::MDF4(file.resample(raster, version, time_from_zero)),
+            Self::MDF3(file) => Ok(Self::MDF3(file.resample(
+                raster,
+                interpolation,
+                time_from_zero,
+            )?)),
+            Self::MDF4(file) => Ok(Self::MDF4(file.resample(
+                raster,
+                interpolation,
+                time_from_zero,
+            )?)),
+        }
+    }
+
+    fn select(
+        &self,
+        channels: &[MdfChannel],
+        record_offset: usize,
+        record_count: Option<usize>,
+        raw: bool,
+    ) -> Result<Vec<Signal>, &'static str> {
+        match self {
+            Self::MDF3(file) => file.select(channels, record_offset, record_count, raw),
+            Self::MDF4(file) => file.select(channels, record_offset, record_count, raw),
+        }
+    }
+
+    fn read_range(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channel: usize,
+        t_start: f64,
+        t_end: f64,
+    ) -> Result<Signal, &'static str> {
+        match self {
+            Self::MDF3(file) => file.read_range(datagroup, channel_grp, channel, t_start, t_end),
+            Self::MDF4(file) => file.read_range(datagroup, channel_grp, channel, t_start, t_end),
+        }
+    }
+
+    fn chunks(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channels: &[MdfChannel],
+        chunk_records: usize,
+    ) -> Result<RecordChunks<'_>, &'static str> {
+        match self {
+            Self::MDF3(file) => file.chunks(datagroup, channel_grp, channels, chunk_records),
+            Self::MDF4(file) => file.chunks(datagroup, channel_grp, channels, chunk_records),
+        }
+    }
+}
+
+/// Something [`MDF::convert`] could not carry over to the target version, or
+/// a group [`MDFFile::cut`] and [`MDFFile::resample`] leave out.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConversionWarning {
+    /// The channel conversion has no equivalent, raw values are written.
+    Conversion { channel: String },
+    /// MDF 3 sources keep a name under 80 and a path under 32 bytes, and no
+    /// comment.
+    Source { channel: String },
+    /// MDF 3 events have no name and share a single comment.
+    EventText { time: f64 },
+    /// MDF 4 point events have no pre and post trigger times.
+    EventWindow { time: f64 },
+    /// Attachments are not written.
+    Attachments { count: usize },
+    /// The channel group holds only its master channel or cannot be read,
+    /// and is left out.
+    Group {
+        data_group: usize,
+        channel_group: usize,
+    },
+}
+
+/// Why [`MDF::concatenate`] or [`MDF::stack`] could not merge the files,
+/// `file` is an index into the files passed in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutMismatch {
+    NoFiles,
+    /// The file has a different number of channel groups than the first one.
+    GroupCount {
+        file: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The channel names of the group differ from the first file.
+    Channels {
+        file: usize,
+        group: usize,
+    },
+    /// The file starts before the previous one ends.
+    Overlap {
+        file: usize,
+    },
+    /// A channel of the group has the name of one in an earlier file, which
+    /// [`MDF::stack`] cannot tell apart.
+    Duplicate {
+        file: usize,
+        group: usize,
+    },
+}
+
+/// A problem [`MDF::validate`] found in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// The channel has a different number of samples than timestamps.
+    SampleCount {
+        channel: String,
+        samples: usize,
+        timestamps: usize,
+    },
+    /// The timestamps of the group decrease at `index`.
+    TimeOrder {
+        data_group: usize,
+        channel_group: usize,
+        index: usize,
+    },
+    /// The channel conversion is not supported, samples stay raw.
+    Conversion { channel: String },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SampleCount {
+                channel,
+                samples,
+                timestamps,
+            } => write!(
+                f,
+                "{channel}: {samples} samples for {timestamps} timestamps"
+            ),
+            Self::TimeOrder {
+                data_group,
+                channel_group,
+                index,
+            } => write!(
+                f,
+                "group {data_group}/{channel_group}: time decreases at sample {index}"
+            ),
+            Self::Conversion { channel } => write!(f, "{channel}: unsupported conversion"),
         }
     }
-    // #[must_use]
-    // fn select(
-    //     &self,
-    //     channels: ChannelsType,
-    //     record_offset: isize,
-    //     raw: bool,
-    //     copy_master: bool,
-    //     ignore_value2text_conversions: bool,
-    //     record_count: isize,
-    //     validate: bool,
-    // ) -> Vec<Signal>;
 }
 
 pub struct MDF {
     pub filepath: String,
     file: MDFType,
     pub channels: Vec<MdfChannel>,
+    /// Filled by [`MDF::convert`], [`MDFFile::cut`] and
+    /// [`MDFFile::resample`], empty for files read from disk.
+    pub warnings: Vec<ConversionWarning>,
 }
 
 impl MDF {
-    pub fn search_channels(&self, channel_name: &str) -> Result<MdfChannel, &'static str> {
-        let mut channels_match = Vec::with_capacity(self.channels.len());
+    /// Opens the file at `filepath` like [`MDFFile::new`], but an error
+    /// instead of a panic if it cannot be read, is no MDF file of a
+    /// supported version or its blocks are linked in a corrupt way.
+    pub fn open(filepath: &str) -> Result<Self, &'static str> {
+        let storage = Storage::new(filepath).map_err(|_| "Could not read file")?;
+        let mut mdf = Self::from_file(MDFType::from_storage(storage)?);
+        mdf.filepath = filepath.to_string();
+        Ok(mdf)
+    }
+
+    /// Rewrites the measurement as MDF "3.30", "4.10" or "4.20", any other
+    /// 3.x version is written as 3.30. The result lives in memory until
+    /// [`MDF::save`] is called, see [`MDF::warnings`] for what got lost.
+    pub fn convert(&self, version: &str) -> Result<MDF, &'static str> {
+        let version = match version {
+            v if v.starts_with("3.") => 330,
+            "4.1" | "4.10" => 410,
+            "4.2" | "4.20" => 420,
+            _ => return Err("Unsupported MDF version"),
+        };
+        let to_mdf3 = version < 400;
+        let from_mdf3 = matches!(self.file, MDFType::MDF3(_));
+
+        let measurement = Measurement::read(&self.file);
 
+        let mut warnings = self.skipped();
         for channel in &self.channels {
-            if channel.name.eq(&channel_name) {
-                channels_match.push(channel.clone());
+            let (dg, cg, cn) = (channel.data_group, channel.channel_group, channel.channel);
+            if self.file.has_unsupported_conversion(dg, cg, cn) {
+                warnings.push(ConversionWarning::Conversion {
+                    channel: channel.name.clone(),
+                });
+            }
+        }
+        for (_, _, _, signals) in &measurement.groups {
+            for signal in signals {
+                match &signal.source {
+                    Some(source) if to_mdf3 && !Mdf3Writer::source_fits(source) => {
+                        warnings.push(ConversionWarning::Source {
+                            channel: signal.name.clone(),
+                        })
+                    }
+                    _ => {}
+                }
             }
         }
 
-        match channels_match.len() {
+        let bytes = if to_mdf3 {
+            if !measurement.attachments.is_empty() {
+                warnings.push(ConversionWarning::Attachments {
+                    count: measurement.attachments.len(),
+                });
+            }
+            let trigger_comment = Mdf3Writer::merge_comments(&measurement.events);
+            for event in &measurement.events {
+                if !event.name.is_empty() || event.comment != trigger_comment {
+                    warnings.push(ConversionWarning::EventText { time: event.time });
+                }
+            }
+            measurement.mdf3(true)
+        } else {
+            for event in &measurement.events {
+                if from_mdf3 && (event.pre_trigger != 0.0 || event.post_trigger != 0.0) {
+                    warnings.push(ConversionWarning::EventWindow { time: event.time });
+                }
+            }
+            measurement.mdf4(version)?
+        };
+
+        let mut mdf = Self::from_bytes(bytes);
+        mdf.warnings = warnings;
+        Ok(mdf)
+    }
+
+    /// The version from the identification block, e.g. "4.10".
+    pub fn version(&self) -> String {
+        String::from_utf8_lossy(&self.file.storage().bytes(8, 8))
+            .trim_end_matches(['\0', ' '])
+            .to_string()
+    }
+
+    /// Reads every channel and checks sample counts, time order and
+    /// conversions.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let mut checked = Vec::new();
+        for channel in &self.channels {
+            let (dg, cg, cn) = (channel.data_group, channel.channel_group, channel.channel);
+            let signal = self.file.read(dg, cg, cn);
+
+            if signal.samples.len() != signal.timestamps.len() {
+                issues.push(ValidationIssue::SampleCount {
+                    channel: channel.name.clone(),
+                    samples: signal.samples.len(),
+                    timestamps: signal.timestamps.len(),
+                });
+            }
+            if !checked.contains(&(dg, cg)) {
+                checked.push((dg, cg));
+                if let Some(index) = signal.timestamps.windows(2).position(|x| x[1] < x[0]) {
+                    issues.push(ValidationIssue::TimeOrder {
+                        data_group: dg,
+                        channel_group: cg,
+                        index: index + 1,
+                    });
+                }
+            }
+            if self.file.has_unsupported_conversion(dg, cg, cn) {
+                issues.push(ValidationIssue::Conversion {
+                    channel: channel.name.clone(),
+                });
+            }
+        }
+
+        issues
+    }
+
+    /// Builds an MDF 4.10 file in memory from a CSV file, see
+    /// [`crate::import::csv::read`]. The channels go into one group named
+    /// after the file, use [`MDF::save`] to write it.
+    pub fn from_csv(filepath: &str, options: &ImportOptions) -> io::Result<MDF> {
+        let invalid = |x| io::Error::new(io::ErrorKind::InvalidData, x);
+        let text = fs::read_to_string(filepath)?;
+        let table = csv::read(&text, options).map_err(invalid)?;
+
+        let mut writer = Mdf4Writer::new();
+        if let Some(start_time) = table.start_time {
+            writer.set_start_time(start_time).map_err(invalid)?;
+        }
+        let name = Path::new(filepath).file_stem().unwrap_or_default();
+        writer
+            .add_channel_group(&name.to_string_lossy(), table.signals)
+            .map_err(invalid)?;
+
+        Ok(Self::from_bytes(writer.to_bytes()))
+    }
+
+    /// Appends the channel groups of `files` one after the other in
+    /// [`MDFFile::start_time`] order. All files need the same channel groups,
+    /// the result has the version of the earliest one.
+    pub fn concatenate(files: &[MDF]) -> Result<MDF, LayoutMismatch> {
+        let mut measurements = Self::aligned(files)?.into_iter();
+        let (first, mut merged) = measurements.next().ok_or(LayoutMismatch::NoFiles)?;
+
+        for (file, measurement) in measurements {
+            if measurement.groups.len() != merged.groups.len() {
+                return Err(LayoutMismatch::GroupCount {
+                    file,
+                    expected: merged.groups.len(),
+                    found: measurement.groups.len(),
+                });
+            }
+
+            for (group, (target, source)) in
+                merged.groups.iter_mut().zip(measurement.groups).enumerate()
+            {
+                let names = |signals: &[Signal]| -> Vec<String> {
+                    signals.iter().map(|x| x.name.clone()).collect()
+                };
+                if names(&target.3) != names(&source.3) {
+                    return Err(LayoutMismatch::Channels { file, group });
+                }
+
+                for (target, source) in target.3.iter_mut().zip(source.3) {
+                    target
+                        .extend(source)
+                        .map_err(|_| LayoutMismatch::Overlap { file })?;
+                }
+            }
+
+            merged.merge_extras(measurement.events, measurement.attachments);
+        }
+
+        Ok(Self::from_file(files[first].file.rebuild(&merged)))
+    }
+
+    /// Puts the channel groups of all `files` side by side on the timebase
+    /// of the earliest [`MDFFile::start_time`], whose version the result has.
+    /// The files need channels of different names.
+    pub fn stack(files: &[MDF]) -> Result<MDF, LayoutMismatch> {
+        let mut measurements = Self::aligned(files)?.into_iter();
+        let (first, mut merged) = measurements.next().ok_or(LayoutMismatch::NoFiles)?;
+
+        for (file, measurement) in measurements {
+            for (group, (_, _, _, signals)) in measurement.groups.iter().enumerate() {
+                let mut stacked = merged.groups.iter().flat_map(|x| &x.3);
+                if stacked.any(|x| signals.iter().any(|y| y.name == x.name)) {
+                    return Err(LayoutMismatch::Duplicate { file, group });
+                }
+            }
+            merged.groups.extend(measurement.groups);
+            merged.merge_extras(measurement.events, measurement.attachments);
+        }
+
+        Ok(Self::from_file(files[first].file.rebuild(&merged)))
+    }
+
+    /// Reads `files` in start time order with their timestamps shifted to
+    /// the start of the earliest one, keeping the index of each file.
+    fn aligned(files: &[MDF]) -> Result<Vec<(usize, Measurement)>, LayoutMismatch> {
+        let mut order: Vec<usize> = (0..files.len()).collect();
+        order.sort_by_key(|&x| files[x].file.start_time());
+        let first = order.first().ok_or(LayoutMismatch::NoFiles)?;
+        let start_time = files[*first].file.start_time();
+
+        Ok(order
+            .into_iter()
+            .map(|index| {
+                let mut measurement = Measurement::read(&files[index].file);
+                let offset = (measurement.start_time - start_time)
+                    .num_nanoseconds()
+                    .unwrap_or(i64::MAX) as f64
+                    / 1e9;
+                measurement.shift(offset);
+                measurement.start_time = start_time;
+                (index, measurement)
+            })
+            .collect())
+    }
+
+    /// Like [`MDFFile::cut`] with bounds given as absolute times instead of
+    /// seconds from the start of the recording.
+    #[must_use]
+    pub fn cut_at(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        include_ends: bool,
+        time_from_zero: bool,
+    ) -> MDF {
+        let start_time = self.file.start_time();
+        let seconds = |time: DateTime<Utc>| {
+            (time - start_time).num_nanoseconds().unwrap_or(i64::MAX) as f64 / 1e9
+        };
+        self.cut(seconds(start), seconds(end), include_ends, time_from_zero)
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self::from_file(MDFType::from_bytes(bytes))
+    }
+
+    /// The groups the rewritten file leaves out, see [`Measurement::skipped`].
+    fn skipped(&self) -> Vec<ConversionWarning> {
+        Measurement::skipped(&self.file)
+            .into_iter()
+            .map(|(data_group, channel_group)| ConversionWarning::Group {
+                data_group,
+                channel_group,
+            })
+            .collect()
+    }
+
+    fn from_file(file: MDFType) -> Self {
+        Self {
+            filepath: String::new(),
+            channels: file.channels(),
+            file,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Writes the file, e.g. the result of [`MDF::convert`], to `filepath`.
+    pub fn save(&self, filepath: &str) -> io::Result<()> {
+        let mut file = File::create(filepath)?;
+        self.file.storage().write_to(&mut file)
+    }
+
+    /// The single channel at `address`, see [`ChannelSelector`] for the
+    /// forms an address takes.
+    pub fn search_channels(&self, address: &str) -> Result<MdfChannel, &'static str> {
+        self.single(address)
+    }
+
+    /// The single channel picked by `selector`.
+    fn single(&self, selector: impl Into<ChannelSelector>) -> Result<MdfChannel, &'static str> {
+        let mut found = selector.into().select(self, &self.channels);
+
+        match found.len() {
             0 => Err("Channel not found"),
-            1 => Ok(channels_match[0].clone()),
-            l if 1 < l => Err("Multiple matches found"),
-            _ => Err(r#"Unknown error measuring length of matching channels"#),
+            1 => Ok(found.remove(0)),
+            _ => Err("Multiple matches found"),
         }
     }
 
+    /// Reads the channel at `address`, see [`MDF::search_channels`].
+    pub fn get(&self, address: &str) -> Result<Signal, &'static str> {
+        Ok(self.read_channel(&self.search_channels(address)?))
+    }
+
+    /// Every channel passing the filters of `search`, in file order. Fails
+    /// only on an invalid regular expression.
+    pub fn find_channels(&self, search: &ChannelSearch) -> Result<Vec<ChannelMatch>, &'static str> {
+        let name_matches = search.name_matcher()?;
+        let mut group_comments = HashMap::new();
+
+        let mut found = Vec::new();
+        for channel in &self.channels {
+            let (dg, cg) = (channel.data_group, channel.channel_group);
+            if !search.display_name && !name_matches(&channel.name) {
+                continue;
+            }
+            let info = self.file.channel_info(dg, cg, channel.channel);
+            if search.display_name
+                && !name_matches(&channel.name)
+                && !name_matches(&info.display_name)
+            {
+                continue;
+            }
+
+            let group_comment = group_comments
+                .entry((dg, cg))
+                .or_insert_with(|| self.file.group_comment(dg, cg))
+                .clone();
+            let (source_name, source_path) = info
+                .source
+                .as_ref()
+                .map_or(("", ""), |x| (x.name.as_str(), x.path.as_str()));
+            if search.filter(&search.unit, &[&info.unit])
+                && search.filter(&search.source, &[source_name, source_path])
+                && search.filter(&search.group_comment, &[&group_comment])
+                && search.data_type.is_none_or(|x| x == info.data_type)
+                && search.master.is_none_or(|x| x == info.master)
+            {
+                found.push(ChannelMatch {
+                    channel: channel.clone(),
+                    group_comment,
+                    info,
+                });
+            }
+        }
+
+        Ok(found)
+    }
+
     pub fn list_channels(&self) {
         for channel in &self.channels {
             println!(
@@ -191,12 +745,79 @@ impl MDF {
     }
 
     pub fn read_channel(&self, channel: &MdfChannel) -> Signal {
-        self.file.read(
-            channel.data_group as usize,
-            channel.channel_group as usize,
-            channel.channel as usize,
+        self.file
+            .read(channel.data_group, channel.channel_group, channel.channel)
+    }
+
+    /// The channels picked by any of `selectors`, each once, in the order
+    /// of the selectors.
+    pub fn resolve<S: Into<ChannelSelector> + Clone>(&self, selectors: &[S]) -> Vec<MdfChannel> {
+        let mut found: Vec<MdfChannel> = Vec::new();
+        for selector in selectors {
+            for channel in selector.clone().into().select(self, &self.channels) {
+                if !found.contains(&channel) {
+                    found.push(channel);
+                }
+            }
+        }
+        found
+    }
+
+    /// Reads the channels picked by `channels`, see [`MDFFile::select`].
+    pub fn select<S: Into<ChannelSelector> + Clone>(
+        &self,
+        channels: &[S],
+        record_offset: usize,
+        record_count: Option<usize>,
+        raw: bool,
+    ) -> Result<Vec<Signal>, &'static str> {
+        self.file
+            .select(&self.resolve(channels), record_offset, record_count, raw)
+    }
+
+    /// The samples of `channel` from `t_start` to `t_end` seconds, reading
+    /// only the parts of the file that cover the window. Fails unless the
+    /// selector picks a single channel, see [`MDFFile::read_range`].
+    pub fn read_range(
+        &self,
+        channel: impl Into<ChannelSelector>,
+        t_start: f64,
+        t_end: f64,
+    ) -> Result<Signal, &'static str> {
+        let channel = self.single(channel)?;
+        self.file.read_range(
+            channel.data_group,
+            channel.channel_group,
+            channel.channel,
+            t_start,
+            t_end,
         )
     }
+
+    /// Reads `channels` in chunks of up to `chunk_records` records, one
+    /// channel group after the other. Only the data block being decoded is
+    /// held in memory, so files larger than memory can be processed. An
+    /// error if a group cannot be read, see [`MDFFile::chunks`].
+    pub fn iter_chunks<S: Into<ChannelSelector> + Clone>(
+        &self,
+        channels: &[S],
+        chunk_records: usize,
+    ) -> Result<impl Iterator<Item = Chunk> + '_, &'static str> {
+        let channels = self.resolve(channels);
+        let chunks = groups(&channels)
+            .into_iter()
+            .map(|(datagroup, channel_grp)| {
+                let selected: Vec<MdfChannel> = channels
+                    .iter()
+                    .filter(|x| x.data_group == datagroup && x.channel_group == channel_grp)
+                    .cloned()
+                    .collect();
+                self.file
+                    .chunks(datagroup, channel_grp, &selected, chunk_records)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(chunks.into_iter().flatten())
+    }
 }
 
 impl MDFFile for MDF {
@@ -216,13 +837,50 @@ impl MDFFile for MDF {
         self.file.read_channel(datagroup, channel_grp, channel)
     }
 
+    fn records(&self, datagroup: usize, channel_grp: usize) -> RecordView<'_> {
+        self.file.records(datagroup, channel_grp)
+    }
+
     fn new(filepath: &str) -> Self {
-        let file = MDFType::new(filepath);
-        Self {
-            filepath: filepath.to_string(),
-            channels: file.channels(),
-            file,
-        }
+        Self::open(filepath).unwrap_or_else(|x| panic!("{x}"))
+    }
+
+    fn comment(&self) -> String {
+        self.file.comment()
+    }
+
+    fn start_time(&self) -> DateTime<Utc> {
+        self.file.start_time()
+    }
+
+    fn events(&self) -> Vec<Event> {
+        self.file.events()
+    }
+
+    fn attachments(&self) -> Vec<Attachment> {
+        self.file.attachments()
+    }
+
+    fn group_comment(&self, datagroup: usize, channel_grp: usize) -> String {
+        self.file.group_comment(datagroup, channel_grp)
+    }
+
+    fn record_count(&self, datagroup: usize, channel_grp: usize) -> usize {
+        self.file.record_count(datagroup, channel_grp)
+    }
+
+    fn channel_info(&self, datagroup: usize, channel_grp: usize, channel: usize) -> ChannelInfo {
+        self.file.channel_info(datagroup, channel_grp, channel)
+    }
+
+    fn has_unsupported_conversion(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channel: usize,
+    ) -> bool {
+        self.file
+            .has_unsupported_conversion(datagroup, channel_grp, channel)
     }
 
     fn read_all(&mut self) {
@@ -241,43 +899,60 @@ impl MDFFile for MDF {
         self.file.read(datagroup, channel_grp, channel)
     }
 
-    fn cut(&self, start: f64, end: f64, include_ends: bool, time_from_zero: bool) {
-        self.file.cut(start, end, include_ends, time_from_zero)
+    fn cut(&self, start: f64, end: f64, include_ends: bool, time_from_zero: bool) -> Self {
+        let mut mdf = Self::from_file(self.file.cut(start, end, include_ends, time_from_zero));
+        mdf.warnings = self.skipped();
+        mdf
     }
 
-    fn export(&self, format: &str, filename: &str) {
-        self.file.export(format, filename)
+    fn export(&self, format: &str, filename: &str, options: &ExportOptions) -> io::Result<()> {
+        self.file.export(format, filename, options)
     }
-    fn filter(&self, channels: &str) {
-        self.file.filter(channels)
+    fn filter(&self, channels: &[ChannelSelector]) -> Self {
+        Self::from_file(self.file.filter(channels))
+    }
+    fn resample(
+        &self,
+        raster: &RasterType,
+        interpolation: Interpolation,
+        time_from_zero: bool,
+    ) -> Result<Self, &'static str> {
+        let mut mdf = Self::from_file(self.file.resample(raster, interpolation, time_from_zero)?);
+        mdf.warnings = self.skipped();
+        Ok(mdf)
+    }
+    fn select(
+        &self,
+        channels: &[MdfChannel],
+        record_offset: usize,
+        record_count: Option<usize>,
+        raw: bool,
+    ) -> Result<Vec<Signal>, &'static str> {
+        self.file.select(channels, record_offset, record_count, raw)
+    }
+
+    fn read_range(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channel: usize,
+        t_start: f64,
+        t_end: f64,
+    ) -> Result<Signal, &'static str> {
+        self.file
+            .read_range(datagroup, channel_grp, channel, t_start, t_end)
+    }
+
+    fn chunks(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channels: &[MdfChannel],
+        chunk_records: usize,
+    ) -> Result<RecordChunks<'_>, &'static str> {
+        self.file
+            .chunks(datagroup, channel_grp, channels, chunk_records)
     }
-    fn resample(&self, raster: RasterType, version: &str, time_from_zero: bool) -> Self {
-        Self {
-            filepath: self.filepath.clone(),
-            file: self.file.resample(raster, version, time_from_zero),
-            channels: Vec::new(),
-        }
-    }
-    // fn select(
-    //     &self,
-    //     channels: ChannelsType,
-    //     record_offset: isize,
-    //     raw: bool,
-    //     copy_master: bool,
-    //     ignore_value2text_conversions: bool,
-    //     record_count: isize,
-    //     validate: bool,
-    // ) -> Vec<Signal> {
-    //     self.file.select(
-    //         channels,
-    //         record_offset,
-    //         raw,
-    //         copy_master,
-    //         ignore_value2text_conversions,
-    //         record_count,
-    //         validate,
-    //     )
-    // }
 }
 
 pub trait MDFFile {
@@ -290,9 +965,37 @@ pub trait MDFFile {
 
     fn read_channel(&self, datagroup: usize, channel_grp: usize, channel: usize) -> Vec<Record>;
 
+    /// The records of the channel group, borrowed from the file where it
+    /// holds them uncompressed in one block.
+    fn records(&self, datagroup: usize, channel_grp: usize) -> RecordView<'_>;
+
     #[must_use]
     fn new(filepath: &str) -> Self;
 
+    fn comment(&self) -> String;
+
+    fn start_time(&self) -> DateTime<Utc>;
+
+    fn events(&self) -> Vec<Event>;
+
+    fn attachments(&self) -> Vec<Attachment>;
+
+    fn group_comment(&self, datagroup: usize, channel_grp: usize) -> String;
+
+    /// The number of records of the channel group, from its metadata.
+    fn record_count(&self, datagroup: usize, channel_grp: usize) -> usize;
+
+    /// Metadata of the channel, read without decoding any samples.
+    fn channel_info(&self, datagroup: usize, channel_grp: usize, channel: usize) -> ChannelInfo;
+
+    /// True when the channel has a conversion that [`Signal`] cannot hold.
+    fn has_unsupported_conversion(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channel: usize,
+    ) -> bool;
+
     fn read_all(&mut self);
 
     fn list_data_groups(&mut self);
@@ -302,28 +1005,394 @@ pub trait MDFFile {
     #[must_use]
     fn read(&self, datagroup: usize, channel_grp: usize, channel: usize) -> Signal;
 
-    fn cut(&self, start: f64, end: f64, include_ends: bool, time_from_zero: bool);
+    /// Keeps the samples between `start` and `end` seconds in every channel
+    /// group, along with the events in that window and all attachments.
+    /// `include_ends` keeps samples lying exactly on the bounds, and
+    /// `time_from_zero` moves `start` to time zero and the start time with it.
+    #[must_use]
+    fn cut(&self, start: f64, end: f64, include_ends: bool, time_from_zero: bool) -> Self;
 
-    fn export(&self, format: &str, filename: &str);
-    fn filter(&self, channels: &str);
+    /// Writes the channels to `filename` as `format`, see
+    /// [`crate::export::export`].
+    fn export(&self, format: &str, filename: &str, options: &ExportOptions) -> io::Result<()>;
+    /// Keeps only the selected channels, each group written with a fresh
+    /// master and records packed to the kept channels. Groups without a
+    /// selected channel are dropped, selectors matching nothing are ignored.
     #[must_use]
-    fn resample(&self, raster: RasterType, version: &str, time_from_zero: bool) -> Self;
-    // #[must_use]
-    // fn select(
-    //     &self,
-    //     channels: ChannelsType,
-    //     record_offset: isize,
-    //     raw: bool,
-    //     copy_master: bool,
-    //     ignore_value2text_conversions: bool,
-    //     record_count: isize,
-    //     validate: bool,
-    // ) -> Vec<Signal>;
+    fn filter(&self, channels: &[ChannelSelector]) -> Self;
+    /// Puts every channel onto the `raster` timestamps, use
+    /// [`MDF::convert`] to change the version.
+    fn resample(
+        &self,
+        raster: &RasterType,
+        interpolation: Interpolation,
+        time_from_zero: bool,
+    ) -> Result<Self, &'static str>
+    where
+        Self: Sized;
+    /// Reads `channels` in one pass over the data of each channel group,
+    /// the signals in the order of `channels`. Takes `record_count` records
+    /// from `record_offset` on, all remaining ones if `None`, and reads only
+    /// the data blocks holding them. `raw` keeps the samples before the
+    /// conversion, which stays attached to the signal. Fails like
+    /// [`MDFFile::read_range`].
+    fn select(
+        &self,
+        channels: &[MdfChannel],
+        record_offset: usize,
+        record_count: Option<usize>,
+        raw: bool,
+    ) -> Result<Vec<Signal>, &'static str>;
+
+    /// The samples of the channel from `t_start` to `t_end` seconds, both
+    /// included, reading only the data blocks that overlap the window. An
+    /// error if the group has no master channel or, in MDF3, shares its
+    /// data block with other groups.
+    fn read_range(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channel: usize,
+        t_start: f64,
+        t_end: f64,
+    ) -> Result<Signal, &'static str>;
+
+    /// Reads `channels`, all of the given channel group, in chunks of up to
+    /// `chunk_records` records while holding a single data block. Fails like
+    /// [`MDFFile::read_range`].
+    fn chunks(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channels: &[MdfChannel],
+        chunk_records: usize,
+    ) -> Result<RecordChunks<'_>, &'static str>;
 }
 
-pub struct RasterType {}
+/// The parts of a file the writers can rebuild from.
+pub struct Measurement {
+    pub comment: String,
+    pub start_time: DateTime<Utc>,
+    pub events: Vec<Event>,
+    pub attachments: Vec<Attachment>,
+    /// Data group, channel group, group comment and the signals of each
+    /// channel group, masters left out.
+    pub groups: Vec<(usize, usize, String, Vec<Signal>)>,
+}
 
-pub struct ChannelsType {}
+impl Measurement {
+    pub fn read<F: MDFFile>(file: &F) -> Self {
+        Self::read_selected(file, |_| true)
+    }
+
+    /// The data and channel groups [`Measurement::read`] leaves out, those
+    /// holding only a master channel and those [`MDFFile::select`] cannot
+    /// read.
+    pub fn skipped<F: MDFFile>(file: &F) -> Vec<(usize, usize)> {
+        let channels = file.channels();
+        groups(&channels)
+            .into_iter()
+            .filter(|&(dg, cg)| {
+                let group: Vec<MdfChannel> = channels
+                    .iter()
+                    .filter(|x| x.data_group == dg && x.channel_group == cg)
+                    .cloned()
+                    .collect();
+                group.len() == 1 || file.select(&group[..1], 0, Some(0), true).is_err()
+            })
+            .collect()
+    }
+
+    /// Reads the channels picked by any of `selectors`.
+    pub fn read_matching<F: MDFFile>(file: &F, selectors: &[ChannelSelector]) -> Self {
+        let channels = file.channels();
+        let selected: Vec<MdfChannel> = selectors
+            .iter()
+            .flat_map(|x| x.select(file, &channels))
+            .collect();
+        Self::read_selected(file, |channel| selected.contains(channel))
+    }
+
+    /// Reads only the channels for which `keep` is true.
+    pub fn read_selected<F: MDFFile>(file: &F, keep: impl Fn(&MdfChannel) -> bool) -> Self {
+        let channels: Vec<MdfChannel> = file
+            .channels()
+            .into_iter()
+            .filter(|x| {
+                keep(x) && file.find_time_channel(x.data_group, x.channel_group) != Ok(x.channel)
+            })
+            .collect();
+
+        // Groups without a master have no timestamps to write and are left out
+        let mut groups = Vec::new();
+        for (dg, cg) in self::groups(&channels) {
+            let selected: Vec<MdfChannel> = channels
+                .iter()
+                .filter(|x| x.data_group == dg && x.channel_group == cg)
+                .cloned()
+                .collect();
+            if let Ok(signals) = file.select(&selected, 0, None, true) {
+                groups.push((dg, cg, file.group_comment(dg, cg), signals));
+            }
+        }
+
+        Self {
+            comment: file.comment(),
+            start_time: file.start_time(),
+            events: file.events(),
+            attachments: file.attachments(),
+            groups,
+        }
+    }
+
+    /// See [`MDFFile::cut`].
+    pub fn cut(&mut self, start: f64, end: f64, include_ends: bool, time_from_zero: bool) {
+        let shift = if time_from_zero { start } else { 0.0 };
+
+        for (_, _, _, signals) in &mut self.groups {
+            for signal in signals.iter_mut() {
+                *signal = signal.cut(start, end, include_ends, time_from_zero);
+            }
+        }
+
+        self.events.retain(|x| match include_ends {
+            true => start <= x.time && x.time <= end,
+            false => start < x.time && x.time < end,
+        });
+        for event in &mut self.events {
+            event.time -= shift;
+        }
+        self.start_time += Duration::nanoseconds((shift * 1e9) as i64);
+    }
+
+    fn shift(&mut self, seconds: f64) {
+        for (_, _, _, signals) in &mut self.groups {
+            for signal in signals.iter_mut() {
+                signal.timestamps.iter_mut().for_each(|x| *x += seconds);
+            }
+        }
+        for event in &mut self.events {
+            event.time += seconds;
+        }
+    }
+
+    fn merge_extras(&mut self, events: Vec<Event>, attachments: Vec<Attachment>) {
+        self.events.extend(events);
+        self.events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        for attachment in attachments {
+            if !self.attachments.contains(&attachment) {
+                self.attachments.push(attachment);
+            }
+        }
+    }
+
+    /// See [`MDFFile::resample`].
+    pub fn resample(
+        &mut self,
+        raster: &RasterType,
+        interpolation: Interpolation,
+        time_from_zero: bool,
+    ) -> Result<(), &'static str> {
+        let mut timestamps = raster.timestamps(self)?;
+        let shift = match timestamps.first() {
+            Some(first) if time_from_zero => *first,
+            _ => 0.0,
+        };
+
+        for (_, _, _, signals) in &mut self.groups {
+            for signal in signals.iter_mut() {
+                *signal = signal.interp(timestamps.clone(), interpolation);
+                signal.timestamps.iter_mut().for_each(|x| *x -= shift);
+            }
+        }
+
+        timestamps.iter_mut().for_each(|x| *x -= shift);
+        for event in &mut self.events {
+            event.time -= shift;
+        }
+        self.start_time += Duration::nanoseconds((shift * 1e9) as i64);
+
+        Ok(())
+    }
+
+    pub fn mdf3(&self, little_endian: bool) -> Vec<u8> {
+        let mut writer = Mdf3Writer::new();
+        writer.set_little_endian(little_endian);
+        writer.set_comment(&self.comment);
+        writer.set_start_time(self.start_time);
+        for event in &self.events {
+            writer.add_event(event.clone());
+        }
+        for (_, _, comment, signals) in &self.groups {
+            writer
+                .add_channel_group(comment, signals.clone())
+                .expect("Signals of a channel group share their timestamps");
+        }
+        writer.to_bytes()
+    }
+
+    /// An error for versions the writer does not support and start times
+    /// MDF 4 cannot store.
+    pub fn mdf4(&self, version: u16) -> Result<Vec<u8>, &'static str> {
+        let mut writer = Mdf4Writer::new();
+        writer.set_version(version)?;
+        writer.set_comment(&self.comment);
+        writer.set_start_time(self.start_time)?;
+        for event in &self.events {
+            writer.add_event(event.clone());
+        }
+        for attachment in &self.attachments {
+            writer.add_attachment(attachment.clone());
+        }
+        for (_, _, comment, signals) in &self.groups {
+            writer
+                .add_channel_group(comment, signals.clone())
+                .expect("Signals of a channel group share their timestamps");
+        }
+        Ok(writer.to_bytes())
+    }
+}
+
+/// The timestamps [`MDFFile::resample`] puts the channels on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RasterType {
+    /// A fixed step in seconds from the first to the last timestamp in the file.
+    Step(f64),
+    /// The timestamps of the named channel.
+    Channel(String),
+    Timestamps(Vec<f64>),
+}
+
+impl RasterType {
+    fn timestamps(&self, measurement: &Measurement) -> Result<Vec<f64>, &'static str> {
+        match self {
+            Self::Step(step) if *step <= 0.0 || !step.is_finite() => {
+                Err("The raster step must be positive")
+            }
+            Self::Step(step) => {
+                let signals = measurement.groups.iter().map(|x| &x.3[0]);
+                let start = signals
+                    .clone()
+                    .filter_map(|x| x.timestamps.first())
+                    .fold(f64::INFINITY, |a, &b| a.min(b));
+                let end = signals
+                    .filter_map(|x| x.timestamps.last())
+                    .fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+                if start > end {
+                    return Ok(Vec::new());
+                }
+
+                // Allow for rounding so the last timestamp stays in the raster
+                let count = ((end - start) / step + 1e-9).floor() as usize + 1;
+                Ok((0..count).map(|i| start + i as f64 * step).collect())
+            }
+            Self::Channel(name) => measurement
+                .groups
+                .iter()
+                .flat_map(|x| &x.3)
+                .find(|x| x.name == *name)
+                .map(|x| x.timestamps.clone())
+                .ok_or("Raster channel not found"),
+            Self::Timestamps(timestamps) => Ok(timestamps.clone()),
+        }
+    }
+}
+
+/// Picks channels by exact name, by position, by a glob pattern with `*`
+/// and `?` wildcards, or by an address.
+///
+/// An address is a channel name, optionally qualified as
+/// `"name@group_comment"`, `"source::name"` with the source name or path, or
+/// `"data_group/channel_group/name"`. Addresses whose qualifier matches
+/// nothing are taken as a plain name, so names may hold `@`, `::` and `/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelSelector {
+    Name(String),
+    Channel(MdfChannel),
+    Pattern(String),
+    Address(String),
+}
+
+impl ChannelSelector {
+    /// The channels of `file` picked by the selector, `channels` being the
+    /// channels of the file.
+    pub fn select<F: MDFFile>(&self, file: &F, channels: &[MdfChannel]) -> Vec<MdfChannel> {
+        let keep = |keep: &dyn Fn(&MdfChannel) -> bool| {
+            channels.iter().filter(|x| keep(x)).cloned().collect()
+        };
+        match self {
+            Self::Name(name) => keep(&|x| x.name == *name),
+            Self::Channel(selected) => keep(&|x| x == selected),
+            Self::Pattern(pattern) => keep(&|x| utils::wildcard_match(pattern, &x.name)),
+            Self::Address(address) => {
+                let found: Vec<MdfChannel> = match qualified(address) {
+                    Some(Qualifier::Group(dg, cg, name)) => {
+                        keep(&|x| x.name == name && x.data_group == dg && x.channel_group == cg)
+                    }
+                    Some(Qualifier::Source(source, name)) => keep(&|x| {
+                        x.name == name
+                            && file
+                                .channel_info(x.data_group, x.channel_group, x.channel)
+                                .source
+                                .is_some_and(|x| x.name == source || x.path == source)
+                    }),
+                    Some(Qualifier::GroupComment(name, comment)) => keep(&|x| {
+                        x.name == name
+                            && file.group_comment(x.data_group, x.channel_group) == comment
+                    }),
+                    None => Vec::new(),
+                };
+                match found.is_empty() {
+                    true => keep(&|x| x.name == *address),
+                    false => found,
+                }
+            }
+        }
+    }
+}
+
+/// The qualifier of a channel address, see [`ChannelSelector`].
+enum Qualifier<'a> {
+    Group(usize, usize, &'a str),
+    Source(&'a str, &'a str),
+    GroupComment(&'a str, &'a str),
+}
+
+fn qualified(address: &str) -> Option<Qualifier<'_>> {
+    let mut parts = address.splitn(3, '/');
+    if let (Some(Ok(dg)), Some(Ok(cg)), Some(name)) = (
+        parts.next().map(str::parse),
+        parts.next().map(str::parse),
+        parts.next(),
+    ) {
+        return Some(Qualifier::Group(dg, cg, name));
+    }
+
+    if let Some((source, name)) = address.split_once("::") {
+        return Some(Qualifier::Source(source, name));
+    }
+    address
+        .rsplit_once('@')
+        .map(|(name, comment)| Qualifier::GroupComment(name, comment))
+}
+
+impl From<&str> for ChannelSelector {
+    fn from(address: &str) -> Self {
+        Self::Address(address.to_string())
+    }
+}
+
+impl From<MdfChannel> for ChannelSelector {
+    fn from(channel: MdfChannel) -> Self {
+        Self::Channel(channel)
+    }
+}
+
+impl From<&MdfChannel> for ChannelSelector {
+    fn from(channel: &MdfChannel) -> Self {
+        Self::Channel(channel.clone())
+    }
+}
 
 pub struct TimeChannel {
     pub time: Vec<f64>,
@@ -347,7 +1416,7 @@ impl TimeChannel {
     }
 
     pub fn max_time(&self) -> f64 {
-        return *self.time.last().expect("Error reading time");
+        *self.time.last().expect("Error reading time")
     }
 }
 
@@ -358,3 +1427,1184 @@ pub struct MdfChannel {
     pub channel: usize,
     pub channel_group: usize,
 }
+
+/// The data and channel group pairs `channels` belong to, sorted.
+pub fn groups(channels: &[MdfChannel]) -> Vec<(usize, usize)> {
+    let mut groups: Vec<(usize, usize)> = channels
+        .iter()
+        .map(|x| (x.data_group, x.channel_group))
+        .collect();
+    groups.sort_unstable();
+    groups.dedup();
+    groups
+}
+
+/// The records [`MDFFile::select`] takes from a group of `record_number`.
+pub fn record_window(
+    record_number: usize,
+    record_offset: usize,
+    record_count: Option<usize>,
+) -> Range<usize> {
+    let end = record_count.map_or(record_number, |x| record_offset.saturating_add(x));
+    let end = end.min(record_number);
+    record_offset.min(end)..end
+}
+
+/// Metadata of a channel, see [`MDFFile::channel_info`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelInfo {
+    /// Empty when the file gives none.
+    pub display_name: String,
+    pub unit: String,
+    pub comment: String,
+    pub source: Option<Source>,
+    pub data_type: DataType,
+    /// What the master channel of the group counts.
+    pub master: MasterType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MasterType {
+    /// The group has no master channel.
+    None,
+    Time,
+    Angle,
+    Distance,
+    Index,
+}
+
+/// How [`ChannelSearch::name`] is compared to channel names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NamePattern {
+    Exact(String),
+    /// A pattern with `*` and `?` wildcards.
+    Glob(String),
+    Regex(String),
+}
+
+/// Filters for [`MDF::find_channels`], unset filters match every channel.
+/// Units, sources and group comments are matched as glob patterns, a source
+/// pattern against both the source name and path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelSearch {
+    pub name: Option<NamePattern>,
+    /// Also match the name pattern against display names.
+    pub display_name: bool,
+    pub unit: Option<String>,
+    pub source: Option<String>,
+    pub group_comment: Option<String>,
+    pub data_type: Option<DataType>,
+    pub master: Option<MasterType>,
+    pub ignore_case: bool,
+}
+
+type NameMatcher<'a> = Box<dyn Fn(&str) -> bool + 'a>;
+
+impl ChannelSearch {
+    pub fn glob(pattern: &str) -> Self {
+        Self {
+            name: Some(NamePattern::Glob(pattern.to_string())),
+            ..Self::default()
+        }
+    }
+
+    pub fn regex(pattern: &str) -> Self {
+        Self {
+            name: Some(NamePattern::Regex(pattern.to_string())),
+            ..Self::default()
+        }
+    }
+
+    fn name_matcher(&self) -> Result<NameMatcher<'_>, &'static str> {
+        Ok(match &self.name {
+            None => Box::new(|_| true),
+            Some(NamePattern::Exact(name)) if self.ignore_case => {
+                let name = name.to_lowercase();
+                Box::new(move |x| x.to_lowercase() == name)
+            }
+            Some(NamePattern::Exact(name)) => Box::new(move |x| x == name),
+            Some(NamePattern::Glob(pattern)) => Box::new(move |x| self.glob_match(pattern, x)),
+            Some(NamePattern::Regex(pattern)) => {
+                let regex = RegexBuilder::new(pattern)
+                    .case_insensitive(self.ignore_case)
+                    .build()
+                    .map_err(|_| "Invalid regular expression")?;
+                Box::new(move |x| regex.is_match(x))
+            }
+        })
+    }
+
+    fn glob_match(&self, pattern: &str, text: &str) -> bool {
+        match self.ignore_case {
+            true => utils::wildcard_match(&pattern.to_lowercase(), &text.to_lowercase()),
+            false => utils::wildcard_match(pattern, text),
+        }
+    }
+
+    /// True when `pattern` is unset or matches one of `texts`.
+    fn filter(&self, pattern: &Option<String>, texts: &[&str]) -> bool {
+        pattern
+            .as_ref()
+            .is_none_or(|pattern| texts.iter().any(|x| self.glob_match(pattern, x)))
+    }
+}
+
+/// A channel found by [`MDF::find_channels`], with the context to tell it
+/// apart from channels of the same name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelMatch {
+    pub channel: MdfChannel,
+    pub group_comment: String,
+    pub info: ChannelInfo,
+}
+
+/// Physical samples of the channels of one channel group over a run of
+/// records, see [`MDF::iter_chunks`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub data_group: usize,
+    pub channel_group: usize,
+    /// Index of the first record of the chunk in the channel group.
+    pub record_offset: usize,
+    pub timestamps: Vec<f64>,
+    pub channels: Vec<MdfChannel>,
+    /// The samples of each of `channels`.
+    pub columns: Vec<Vec<Record>>,
+}
+
+/// Decodes a record into its master value and the raw samples of the
+/// selected channels.
+pub type RecordDecoder<'a> = Box<dyn Fn(&[u8]) -> (f64, Vec<Record>) + 'a>;
+
+/// Splits the records of a channel group, read one data block at a time,
+/// into [`Chunk`]s.
+pub struct RecordChunks<'a> {
+    blocks: Box<dyn Iterator<Item = Vec<u8>> + 'a>,
+    decode: RecordDecoder<'a>,
+    conversions: Vec<Option<Conversion>>,
+    record_size: usize,
+    /// Records not read yet.
+    remaining: usize,
+    chunk_records: usize,
+    /// The bytes of the current block, after the part of a record carried
+    /// over from the previous one.
+    block: Vec<u8>,
+    position: usize,
+    chunk: Chunk,
+}
+
+impl<'a> RecordChunks<'a> {
+    /// `conversions` are applied to the decoded samples of each channel.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(
+        data_group: usize,
+        channel_group: usize,
+        channels: &[MdfChannel],
+        conversions: Vec<Option<Conversion>>,
+        record_size: usize,
+        record_number: usize,
+        chunk_records: usize,
+        blocks: Box<dyn Iterator<Item = Vec<u8>> + 'a>,
+        decode: RecordDecoder<'a>,
+    ) -> Self {
+        Self {
+            blocks,
+            decode,
+            conversions,
+            record_size,
+            remaining: if record_size == 0 { 0 } else { record_number },
+            chunk_records: chunk_records.max(1),
+            block: Vec::new(),
+            position: 0,
+            chunk: Chunk {
+                data_group,
+                channel_group,
+                record_offset: 0,
+                timestamps: Vec::new(),
+                channels: channels.to_vec(),
+                columns: Vec::new(),
+            },
+        }
+    }
+}
+
+impl Iterator for RecordChunks<'_> {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Chunk> {
+        let size = self.chunk_records.min(self.remaining);
+        let mut timestamps = Vec::with_capacity(size);
+        let mut columns = vec![Vec::with_capacity(size); self.chunk.channels.len()];
+
+        while timestamps.len() < size {
+            if self.block.len() - self.position < self.record_size {
+                match self.blocks.next() {
+                    Some(block) => {
+                        self.block.drain(..self.position);
+                        self.block.extend(block);
+                        self.position = 0;
+                    }
+                    None => break,
+                }
+                continue;
+            }
+
+            let record = &self.block[self.position..self.position + self.record_size];
+            let (time, samples) = (self.decode)(record);
+            timestamps.push(time);
+            for (column, sample) in columns.iter_mut().zip(samples) {
+                column.push(sample);
+            }
+            self.position += self.record_size;
+        }
+
+        if timestamps.is_empty() {
+            self.remaining = 0;
+            return None;
+        }
+
+        self.remaining -= timestamps.len();
+        let columns = columns
+            .into_iter()
+            .zip(&self.conversions)
+            .map(|(column, conversion)| match conversion {
+                Some(conversion) => conversion.convert_all(&column),
+                None => column,
+            })
+            .collect();
+        let chunk = Chunk {
+            timestamps,
+            columns,
+            ..self.chunk.clone()
+        };
+        self.chunk.record_offset += chunk.timestamps.len();
+
+        Some(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{
+        ChannelSearch, ChannelSelector, ConversionWarning, LayoutMismatch, MDFFile, MDFType,
+        MasterType, RasterType, ValidationIssue, MDF,
+    };
+    use crate::attachment::Attachment;
+    use crate::conversion::Conversion;
+    use crate::event::Event;
+    use crate::export::ExportOptions;
+    use crate::import::ImportOptions;
+    use crate::mdf4::mdf4_writer::Mdf4Writer;
+    use crate::record::{DataType, Record};
+    use crate::signal::{Interpolation, Signal, Source};
+    use crate::storage::{Storage, Stream};
+    use crate::utils::TempFile;
+
+    #[test]
+    fn convert_mdf3() {
+        let mdf = MDF::new("example_files/Single_Channel.dat");
+
+        let mdf4 = mdf.convert("4.10").unwrap();
+        assert!(mdf4.warnings.is_empty());
+        assert_eq!(mdf4.channels.len(), mdf.channels.len());
+        assert_eq!(mdf4.start_time(), mdf.start_time());
+        assert!(mdf.comment().starts_with("Date: 22.11.2018"));
+        assert_eq!(mdf4.comment(), mdf.comment());
+
+        let channel = &mdf.channels[1];
+        let original = mdf.read_channel(channel);
+        let converted = mdf4.read_channel(&mdf4.search_channels(&channel.name).unwrap());
+        assert_eq!(converted.samples, original.samples);
+        assert_eq!(converted.timestamps, original.timestamps);
+        assert_eq!(converted.unit, original.unit);
+        assert_eq!(converted.comment, original.comment);
+
+        let mdf3 = mdf4.convert("3.30").unwrap();
+        assert!(mdf3.warnings.is_empty());
+        assert_eq!(mdf3.read_channel(&mdf3.channels[1]), original);
+    }
+
+    #[test]
+    fn convert_mdf4() {
+        let speed = Signal::new(
+            vec![0.0, 0.1, 0.2],
+            vec![Record::Int(-300), Record::Int(0), Record::Int(400)],
+            "km/h".to_string(),
+            "speed".to_string(),
+            "vehicle speed".to_string(),
+            true,
+        )
+        .with_conversion(Conversion::Linear {
+            offset: 1.0,
+            factor: 0.5,
+        })
+        .with_source(Source {
+            name: "ECU".to_string(),
+            path: "CAN1".to_string(),
+            comment: String::new(),
+        });
+        let status = Signal::new(
+            vec![0.0, 0.1, 0.2],
+            vec![Record::Uint(0), Record::Uint(1), Record::Uint(2)],
+            "".to_string(),
+            "status".to_string(),
+            "".to_string(),
+            false,
+        )
+        .with_source(Source {
+            name: "Gateway".to_string(),
+            path: "CAN2".to_string(),
+            comment: "routed".to_string(),
+        });
+
+        let mut writer = Mdf4Writer::new();
+        writer.set_comment("drive");
+        writer.add_event(Event::new(0.1, "", "trigger"));
+        writer.add_event(Event::new(0.2, "stop", "trigger"));
+        writer.add_attachment(Attachment::embedded(
+            "notes.txt",
+            "text/plain",
+            b"cold start".to_vec(),
+        ));
+        writer
+            .add_channel_group("engine", vec![speed.clone(), status])
+            .unwrap();
+        let mdf = MDF::from_bytes(writer.to_bytes());
+        assert_eq!(mdf.events().len(), 2);
+
+        assert!(mdf.convert("5.00").is_err());
+
+        let mdf42 = mdf.convert("4.20").unwrap();
+        assert!(mdf42.warnings.is_empty());
+        assert_eq!(mdf42.attachments(), mdf.attachments());
+        assert_eq!(mdf42.events(), mdf.events());
+
+        let mdf3 = mdf.convert("3.30").unwrap();
+        assert_eq!(
+            mdf3.warnings,
+            vec![
+                ConversionWarning::Source {
+                    channel: "status".to_string()
+                },
+                ConversionWarning::Attachments { count: 1 },
+                ConversionWarning::EventText { time: 0.2 }
+            ]
+        );
+        assert_eq!(mdf3.comment(), "drive");
+        assert_eq!(mdf3.start_time(), mdf.start_time());
+        assert_eq!(mdf3.group_comment(0, 0), "engine");
+        assert_eq!(
+            mdf3.events(),
+            vec![
+                Event::new(0.1, "", "trigger"),
+                Event::new(0.2, "", "trigger")
+            ]
+        );
+        assert_eq!(
+            mdf3.read_channel(&mdf3.search_channels("speed").unwrap()),
+            speed
+        );
+        let status = mdf3.read_channel(&mdf3.search_channels("status").unwrap());
+        assert_eq!(status.source.unwrap().comment, "");
+
+        let mut event = Event::new(0.5, "", "");
+        event.pre_trigger = 0.25;
+        let mut writer = crate::mdf3::mdf3_writer::Mdf3Writer::new();
+        writer.add_event(event.clone());
+        writer
+            .add_channel_group("engine", vec![speed.clone()])
+            .unwrap();
+        let mdf3 = MDF::from_bytes(writer.to_bytes());
+        assert_eq!(mdf3.events(), vec![event]);
+
+        let mdf4 = mdf3.convert("4.20").unwrap();
+        assert_eq!(*mdf4.file.storage().bytes(8, 8), *b"4.20    ");
+        assert_eq!(
+            mdf4.warnings,
+            vec![ConversionWarning::EventWindow { time: 0.5 }]
+        );
+        assert_eq!(mdf4.events(), vec![Event::new(0.5, "", "")]);
+        assert_eq!(
+            mdf4.read_channel(&mdf4.search_channels("speed").unwrap()),
+            speed
+        );
+    }
+
+    #[test]
+    fn cut() {
+        let timestamps: Vec<f64> = (0..10).map(|x| x as f64 * 0.5).collect();
+        let signal = |name: &str| {
+            Signal::new(
+                timestamps.clone(),
+                (0..10).map(Record::Uint).collect(),
+                "".to_string(),
+                name.to_string(),
+                "".to_string(),
+                false,
+            )
+        };
+        let start_time = Utc.timestamp_nanos(1_600_000_000_000_000_000);
+
+        let mut writer = Mdf4Writer::new();
+        writer.set_start_time(start_time).unwrap();
+        writer.add_event(Event::new(0.5, "early", ""));
+        writer.add_event(Event::new(2.0, "inside", ""));
+        writer.add_attachment(Attachment::embedded("a.bin", "", vec![1, 2, 3]));
+        writer
+            .add_channel_group("first", vec![signal("a"), signal("b")])
+            .unwrap();
+        writer
+            .add_channel_group("second", vec![signal("c")])
+            .unwrap();
+        let mdf = MDF::from_bytes(writer.to_bytes());
+
+        let cut = mdf.cut(1.0, 3.0, true, false);
+        let a = cut.read_channel(&cut.search_channels("a").unwrap());
+        assert_eq!(a.timestamps, vec![1.0, 1.5, 2.0, 2.5, 3.0]);
+        assert_eq!(a.samples, (2..7).map(Record::Uint).collect::<Vec<_>>());
+        let c = cut.read_channel(&cut.search_channels("c").unwrap());
+        assert_eq!(c.timestamps, a.timestamps);
+        assert_eq!(cut.events(), vec![Event::new(2.0, "inside", "")]);
+        assert_eq!(cut.attachments(), mdf.attachments());
+        assert_eq!(cut.start_time(), start_time);
+
+        let cut = mdf.cut(1.0, 3.0, false, true);
+        let b = cut.read_channel(&cut.search_channels("b").unwrap());
+        assert_eq!(b.timestamps, vec![0.5, 1.0, 1.5]);
+        assert_eq!(b.samples, (3..6).map(Record::Uint).collect::<Vec<_>>());
+        assert_eq!(cut.events(), vec![Event::new(1.0, "inside", "")]);
+        assert_eq!(cut.start_time(), start_time + Duration::seconds(1));
+
+        let events = |include_ends| mdf.cut(0.5, 2.0, include_ends, false).events();
+        assert_eq!(events(true).len(), 2);
+        assert!(events(false).is_empty());
+
+        let cut = mdf.cut_at(
+            start_time + Duration::milliseconds(4000),
+            start_time + Duration::seconds(10),
+            true,
+            false,
+        );
+        let a = cut.read_channel(&cut.search_channels("a").unwrap());
+        assert_eq!(a.timestamps, vec![4.0, 4.5]);
+
+        let cut = mdf.cut(20.0, 30.0, true, false);
+        assert_eq!(cut.channels.len(), mdf.channels.len());
+        assert!(cut
+            .read_channel(&cut.search_channels("a").unwrap())
+            .is_empty());
+        assert!(cut.warnings.is_empty());
+
+        // An MDF3 group left with only its master channel
+        let mut writer = crate::mdf3::mdf3_writer::Mdf3Writer::new();
+        writer
+            .add_channel_group("first", vec![signal("a")])
+            .unwrap();
+        writer
+            .add_channel_group("second", vec![signal("c")])
+            .unwrap();
+        let mut bytes = writer.to_bytes();
+        let read = |bytes: &[u8], at: usize| {
+            u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize
+        };
+        let dg = read(&bytes, read(&bytes, 68) + 4);
+        let cn = read(&bytes, read(&bytes, dg + 8) + 8);
+        bytes[cn + 4..cn + 8].copy_from_slice(&[0; 4]);
+        let mdf = MDF::from_bytes(bytes);
+        assert_eq!(mdf.channels.len(), 3);
+        assert_eq!(mdf.find_time_channel(1, 0), Ok(0));
+
+        let skipped = vec![ConversionWarning::Group {
+            data_group: 1,
+            channel_group: 0,
+        }];
+        let cut = mdf.cut(1.0, 3.0, true, false);
+        assert_eq!(cut.warnings, skipped);
+        assert_eq!(cut.channels.len(), 2);
+        assert_eq!(mdf.convert("4.10").unwrap().warnings, skipped);
+    }
+
+    #[test]
+    fn resample() {
+        let signal = |name: &str, timestamps: Vec<f64>| {
+            let samples = timestamps
+                .iter()
+                .map(|&x| Record::Float64(x * 2.0))
+                .collect();
+            Signal::new(
+                timestamps,
+                samples,
+                "".to_string(),
+                name.to_string(),
+                "".to_string(),
+                false,
+            )
+        };
+        let start_time = Utc.timestamp_nanos(1_600_000_000_000_000_000);
+
+        let mut writer = Mdf4Writer::new();
+        writer.set_start_time(start_time).unwrap();
+        writer.add_event(Event::new(2.0, "event", ""));
+        writer
+            .add_channel_group("first", vec![signal("a", vec![1.0, 2.0, 3.0])])
+            .unwrap();
+        writer
+            .add_channel_group("second", vec![signal("b", vec![0.5, 1.5, 2.5, 3.5])])
+            .unwrap();
+        let mdf = MDF::from_bytes(writer.to_bytes());
+
+        let resampled = mdf
+            .resample(
+                &RasterType::Step(1.0),
+                Interpolation::RepeatPreviousSample,
+                false,
+            )
+            .unwrap();
+        let a = resampled.read_channel(&resampled.search_channels("a").unwrap());
+        assert_eq!(a.timestamps, vec![0.5, 1.5, 2.5, 3.5]);
+        let samples = [2.0, 2.0, 4.0, 6.0].map(Record::Float64);
+        assert_eq!(a.samples, samples);
+        assert_eq!(resampled.channels.len(), mdf.channels.len());
+
+        let resampled = mdf
+            .resample(
+                &RasterType::Channel("a".to_string()),
+                Interpolation::LinearInterpolation,
+                true,
+            )
+            .unwrap();
+        let b = resampled.read_channel(&resampled.search_channels("b").unwrap());
+        assert_eq!(b.timestamps, vec![0.0, 1.0, 2.0]);
+        assert_eq!(b.samples, [2.0, 4.0, 6.0].map(Record::Float64));
+        assert_eq!(resampled.events(), vec![Event::new(1.0, "event", "")]);
+        assert_eq!(resampled.start_time(), start_time + Duration::seconds(1));
+
+        let resampled = mdf
+            .resample(
+                &RasterType::Timestamps(vec![0.0, 3.25]),
+                Interpolation::LinearInterpolation,
+                false,
+            )
+            .unwrap();
+        let b = resampled.read_channel(&resampled.search_channels("b").unwrap());
+        assert_eq!(b.samples, [1.0, 6.5].map(Record::Float64));
+
+        assert!(mdf
+            .resample(
+                &RasterType::Step(0.0),
+                Interpolation::LinearInterpolation,
+                false
+            )
+            .is_err());
+        assert!(mdf
+            .resample(
+                &RasterType::Channel("missing".to_string()),
+                Interpolation::LinearInterpolation,
+                false
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn concatenate() {
+        let start_time = Utc.timestamp_nanos(1_600_000_000_000_000_000);
+        let file = |offset: i64, names: &[&str], event: f64| {
+            let mut writer = Mdf4Writer::new();
+            writer
+                .set_start_time(start_time + Duration::seconds(offset))
+                .unwrap();
+            writer.add_event(Event::new(event, "", ""));
+            let signals = names
+                .iter()
+                .map(|name| {
+                    Signal::new(
+                        vec![0.0, 1.0, 2.0],
+                        (0..3).map(|x| Record::Uint(x + offset as u64)).collect(),
+                        "".to_string(),
+                        name.to_string(),
+                        "".to_string(),
+                        false,
+                    )
+                })
+                .collect();
+            writer.add_channel_group("group", signals).unwrap();
+            MDF::from_bytes(writer.to_bytes())
+        };
+
+        let files = [file(10, &["a", "b"], 1.0), file(0, &["a", "b"], 0.5)];
+        let merged = MDF::concatenate(&files).unwrap();
+        assert_eq!(merged.start_time(), start_time);
+        let a = merged.read_channel(&merged.search_channels("a").unwrap());
+        assert_eq!(a.timestamps, vec![0.0, 1.0, 2.0, 10.0, 11.0, 12.0]);
+        let samples = [0, 1, 2, 10, 11, 12].map(Record::Uint);
+        assert_eq!(a.samples, samples);
+        assert_eq!(
+            merged.events(),
+            vec![Event::new(0.5, "", ""), Event::new(11.0, "", "")]
+        );
+
+        let files = [file(0, &["a", "b"], 0.0), file(10, &["a", "c"], 0.0)];
+        assert_eq!(
+            MDF::concatenate(&files).err(),
+            Some(LayoutMismatch::Channels { file: 1, group: 0 })
+        );
+        let files = [file(0, &["a"], 0.0), file(1, &["a"], 0.0)];
+        assert_eq!(
+            MDF::concatenate(&files).err(),
+            Some(LayoutMismatch::Overlap { file: 1 })
+        );
+        let empty = {
+            let mut writer = Mdf4Writer::new();
+            writer.set_start_time(start_time).unwrap();
+            let signal = Signal::new(
+                vec![],
+                vec![],
+                "".to_string(),
+                "a".to_string(),
+                "".to_string(),
+                false,
+            );
+            writer.add_channel_group("group", vec![signal]).unwrap();
+            MDF::from_bytes(writer.to_bytes())
+        };
+        let files = [empty, file(10, &["a"], 0.0)];
+        let merged = MDF::concatenate(&files).unwrap();
+        let a = merged.read_channel(&merged.search_channels("a").unwrap());
+        assert_eq!(a.timestamps, vec![10.0, 11.0, 12.0]);
+        assert_eq!(MDF::concatenate(&[]).err(), Some(LayoutMismatch::NoFiles));
+
+        let files = [file(5, &["c"], 0.0), file(0, &["a", "b"], 0.0)];
+        let stacked = MDF::stack(&files).unwrap();
+        assert_eq!(stacked.start_time(), start_time);
+        let c = stacked.read_channel(&stacked.search_channels("c").unwrap());
+        assert_eq!(c.timestamps, vec![5.0, 6.0, 7.0]);
+        let b = stacked.read_channel(&stacked.search_channels("b").unwrap());
+        assert_eq!(b.timestamps, vec![0.0, 1.0, 2.0]);
+        let files = [
+            file(0, &["a"], 0.0),
+            file(5, &["c"], 0.0),
+            file(1, &["b", "c"], 0.0),
+        ];
+        assert_eq!(
+            MDF::stack(&files).err(),
+            Some(LayoutMismatch::Duplicate { file: 1, group: 0 })
+        );
+        assert_eq!(MDF::stack(&[]).err(), Some(LayoutMismatch::NoFiles));
+    }
+
+    #[test]
+    fn from_csv() {
+        let file = TempFile::create("import.csv");
+        std::fs::write(
+            file.path(),
+            "time,speed [km/h],gear\n0,1.5,low\n0.5,2,high\n",
+        )
+        .unwrap();
+        let mdf = MDF::from_csv(file.path(), &ImportOptions::default()).unwrap();
+
+        let speed = mdf.read_channel(&mdf.search_channels("speed").unwrap());
+        assert_eq!(speed.unit, "km/h");
+        assert_eq!(speed.timestamps, vec![0.0, 0.5]);
+        assert_eq!(speed.samples, [1.5, 2.0].map(Record::Float64));
+        let gear = mdf.read_channel(&mdf.search_channels("gear").unwrap());
+        assert_eq!(
+            gear.samples,
+            ["low", "high"].map(|x| Record::StringNullTerm(x.to_string()))
+        );
+        assert_eq!(
+            mdf.group_comment(0, 0),
+            std::path::Path::new(file.path())
+                .file_stem()
+                .unwrap()
+                .to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let mdf = MDF::new("example_files/Single_Channel.dat");
+        assert!(mdf.version().starts_with("3."));
+        assert_eq!(mdf.validate(), Vec::new());
+
+        let mut writer = Mdf4Writer::new();
+        let signal = Signal::new(
+            vec![0.0, 1.0, 0.5],
+            (0..3).map(Record::Uint).collect(),
+            "".to_string(),
+            "a".to_string(),
+            "".to_string(),
+            false,
+        );
+        writer.add_channel_group("", vec![signal]).unwrap();
+        let mdf = MDF::from_bytes(writer.to_bytes());
+        assert_eq!(mdf.version(), "4.10");
+        let issues = mdf.validate();
+        assert_eq!(
+            issues,
+            vec![ValidationIssue::TimeOrder {
+                data_group: 0,
+                channel_group: 0,
+                index: 2
+            }]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "group 0/0: time decreases at sample 2"
+        );
+    }
+
+    #[test]
+    fn cut_mdf3() {
+        let mdf = MDF::new("example_files/Single_Channel.dat");
+        let original = mdf.read_channel(&mdf.channels[1]);
+        let (start, end) = (original.timestamps[10], original.timestamps[20]);
+
+        let cut = mdf.cut(start, end, true, false);
+        let signal = cut.read_channel(&cut.channels[1]);
+        assert_eq!(signal.timestamps, original.timestamps[10..21].to_vec());
+        assert_eq!(signal.samples, original.samples[10..21].to_vec());
+        assert_eq!(signal.unit, original.unit);
+    }
+
+    #[test]
+    fn find_channels() {
+        let signal = |name: &str, unit: &str, samples: Vec<Record>| {
+            Signal::new(
+                vec![0.0, 1.0],
+                samples,
+                unit.to_string(),
+                name.to_string(),
+                "".to_string(),
+                false,
+            )
+        };
+        let mut speed = signal("Speed", "km/h", vec![Record::Float64(1.0); 2]);
+        speed.source = Some(Source {
+            name: "ECU".to_string(),
+            path: "CAN1".to_string(),
+            comment: "".to_string(),
+        });
+
+        let mut writer = Mdf4Writer::new();
+        writer
+            .add_channel_group(
+                "engine",
+                vec![speed, signal("rpm", "1/min", vec![Record::Uint(1); 2])],
+            )
+            .unwrap();
+        writer
+            .add_channel_group(
+                "wheels",
+                vec![signal("speed", "m/s", vec![Record::Float64(2.0); 2])],
+            )
+            .unwrap();
+        let mdf = MDF::from_bytes(writer.to_bytes());
+        let names = |search: &ChannelSearch| {
+            mdf.find_channels(search)
+                .unwrap()
+                .into_iter()
+                .map(|x| (x.channel.name, x.group_comment))
+                .collect::<Vec<_>>()
+        };
+        let found = |name: &str, group: &str| (name.to_string(), group.to_string());
+
+        assert_eq!(
+            names(&ChannelSearch::glob("speed")),
+            vec![found("speed", "wheels")]
+        );
+        let mut search = ChannelSearch::glob("SPEED");
+        search.ignore_case = true;
+        assert_eq!(
+            names(&search),
+            vec![found("Speed", "engine"), found("speed", "wheels")]
+        );
+        search.source = Some("can*".to_string());
+        assert_eq!(names(&search), vec![found("Speed", "engine")]);
+
+        assert_eq!(
+            names(&ChannelSearch {
+                unit: Some("*/*".to_string()),
+                data_type: Some(DataType::Float64),
+                ..ChannelSearch::default()
+            }),
+            vec![found("Speed", "engine"), found("speed", "wheels")]
+        );
+        assert_eq!(
+            names(&ChannelSearch {
+                group_comment: Some("eng*".to_string()),
+                master: Some(MasterType::Time),
+                ..ChannelSearch::regex("^(rpm|time)$")
+            }),
+            vec![found("time", "engine"), found("rpm", "engine")]
+        );
+        assert!(mdf.find_channels(&ChannelSearch::regex("(")).is_err());
+
+        let matches = mdf.find_channels(&ChannelSearch::glob("rpm")).unwrap();
+        assert_eq!(matches[0].info.unit, "1/min");
+        assert_eq!(matches[0].info.data_type, DataType::UnsignedInt);
+        assert_eq!(matches[0].info.source, None);
+
+        let mdf3 = mdf.convert("3.30").unwrap();
+        let matches = mdf3.find_channels(&ChannelSearch::glob("*peed")).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[1].info.unit, "m/s");
+        assert_eq!(matches[1].info.master, MasterType::Time);
+    }
+
+    #[test]
+    fn address() {
+        let signal = |name: &str, value: u64| {
+            Signal::new(
+                vec![0.0, 1.0],
+                vec![Record::Uint(value); 2],
+                "".to_string(),
+                name.to_string(),
+                "".to_string(),
+                false,
+            )
+        };
+        let mut speed = signal("speed", 1);
+        speed.source = Some(Source {
+            name: "ECU".to_string(),
+            path: "CAN1".to_string(),
+            comment: "".to_string(),
+        });
+
+        let mut writer = Mdf4Writer::new();
+        writer
+            .add_channel_group("engine", vec![speed, signal("mail@home", 3)])
+            .unwrap();
+        writer
+            .add_channel_group("wheels", vec![signal("speed", 2)])
+            .unwrap();
+        let mdf = MDF::from_bytes(writer.to_bytes());
+        let value = |address: &str| mdf.get(address).map(|x| x.samples[0].clone());
+
+        assert_eq!(value("speed"), Err("Multiple matches found"));
+        assert_eq!(value("speed@engine"), Ok(Record::Uint(1)));
+        assert_eq!(value("speed@wheels"), Ok(Record::Uint(2)));
+        assert_eq!(value("ECU::speed"), Ok(Record::Uint(1)));
+        assert_eq!(value("CAN1::speed"), Ok(Record::Uint(1)));
+        assert_eq!(value("1/0/speed"), Ok(Record::Uint(2)));
+        assert_eq!(value("mail@home"), Ok(Record::Uint(3)));
+        assert_eq!(value("speed@brakes"), Err("Channel not found"));
+        assert_eq!(value("2/0/speed"), Err("Channel not found"));
+
+        let filtered = mdf.filter(&["speed@wheels".into()]);
+        assert_eq!(filtered.get("speed"), Ok(signal("speed", 2)));
+
+        let file = TempFile::create("address.json");
+        let options = ExportOptions {
+            channels: vec!["ECU::speed".into()],
+            ..ExportOptions::default()
+        };
+        mdf.export("json", file.path(), &options).unwrap();
+        let json = std::fs::read_to_string(file.path()).unwrap();
+        assert!(json.contains("\"engine\""));
+        assert!(!json.contains("wheels") && !json.contains("mail"));
+    }
+
+    #[test]
+    fn select() {
+        for path in [
+            "example_files/ASAP2_Demo_V171.mf4",
+            "example_files/Single_Channel.dat",
+        ] {
+            let mdf = MDF::new(path);
+            let channels: Vec<_> = mdf.channels.iter().rev().step_by(3).cloned().collect();
+
+            let signals = mdf.select(&channels, 0, None, true).unwrap();
+            assert_eq!(signals.len(), channels.len());
+            for (channel, signal) in channels.iter().zip(&signals) {
+                assert_eq!(*signal, mdf.read_channel(channel));
+            }
+
+            let physical = mdf.select(&channels, 2, Some(3), false).unwrap();
+            for (signal, full) in physical.iter().zip(&signals) {
+                let full = full.physical();
+                let end = full.len().min(5);
+                assert_eq!(signal.timestamps, full.timestamps[2.min(end)..end]);
+                assert_eq!(signal.samples, full.samples[2.min(end)..end]);
+            }
+        }
+
+        let mdf = MDF::new("example_files/ASAP2_Demo_V171.mf4");
+        let channel = mdf
+            .search_channels("ASAM.M.SCALAR.FLOAT32.IDENTICAL")
+            .unwrap();
+        let address = format!(
+            "{}/{}/{}",
+            channel.data_group, channel.channel_group, channel.name
+        );
+        let signals = mdf
+            .select(
+                &[
+                    ChannelSelector::from(address.as_str()),
+                    ChannelSelector::Pattern("ASAM.M.SCALAR.FLOAT32.*".to_string()),
+                    ChannelSelector::from("missing"),
+                ],
+                0,
+                None,
+                true,
+            )
+            .unwrap();
+        assert_eq!(signals[0], mdf.read_channel(&channel));
+        assert_eq!(signals.iter().filter(|x| x.name == channel.name).count(), 1);
+        assert!(signals
+            .iter()
+            .all(|x| x.name.starts_with("ASAM.M.SCALAR.FLOAT32.")));
+        assert!(signals.len() > 1);
+        assert!(mdf.select(&[channel], 1 << 40, Some(3), true).unwrap()[0].is_empty());
+
+        // An MDF3 channel group without a master channel
+        let mut bytes = std::fs::read("example_files/Single_Channel.dat").unwrap();
+        let read = |bytes: &[u8], at: usize| {
+            u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize
+        };
+        let mut cn = read(&bytes, read(&bytes, read(&bytes, 68) + 8) + 8);
+        while cn != 0 {
+            bytes[cn + 24..cn + 26].copy_from_slice(&[0, 0]);
+            cn = read(&bytes, cn + 4);
+        }
+        let mdf = MDF::from_bytes(bytes);
+        let error = "No time series found for the channel selected";
+        assert_eq!(mdf.select(&mdf.channels, 0, None, true), Err(error));
+        assert_eq!(mdf.read_range(&mdf.channels[0], 0.0, 1.0), Err(error));
+        assert!(mdf.iter_chunks(&mdf.channels, 7).is_err());
+    }
+
+    #[test]
+    fn iter_chunks() {
+        for path in [
+            "example_files/ASAP2_Demo_V171_deflate.mf4",
+            "example_files/Single_Channel.dat",
+        ] {
+            let mdf = MDF::new(path);
+            let channels: Vec<_> = mdf.channels.iter().rev().step_by(2).cloned().collect();
+            let signals = mdf.select(&channels, 0, None, false).unwrap();
+
+            let mut read: Vec<(Vec<f64>, Vec<Record>)> =
+                vec![(Vec::new(), Vec::new()); channels.len()];
+            for chunk in mdf.iter_chunks(&channels, 7).unwrap() {
+                assert!(chunk.timestamps.len() <= 7);
+                assert_eq!(chunk.columns.len(), chunk.channels.len());
+                for (channel, column) in chunk.channels.iter().zip(chunk.columns) {
+                    assert_eq!(column.len(), chunk.timestamps.len());
+                    let index = channels.iter().position(|x| x == channel).unwrap();
+                    let (timestamps, samples) = &mut read[index];
+                    assert_eq!(timestamps.len(), chunk.record_offset);
+                    timestamps.extend(&chunk.timestamps);
+                    samples.extend(column);
+                }
+            }
+
+            for ((timestamps, samples), signal) in read.iter().zip(&signals) {
+                assert_eq!(*timestamps, signal.timestamps);
+                assert_eq!(*samples, signal.samples);
+            }
+        }
+
+        let mdf = MDF::new("example_files/ASAP2_Demo_V171.mf4");
+        let literal = mdf.resolve(&["ASAM.M.SCALAR.UBYTE.*"]);
+        let mut names: Vec<String> = mdf
+            .iter_chunks(
+                &[ChannelSelector::Pattern(
+                    "ASAM.M.SCALAR.UBYTE.*".to_string(),
+                )],
+                7,
+            )
+            .unwrap()
+            .flat_map(|x| x.channels)
+            .map(|x| x.name)
+            .collect();
+        names.dedup();
+        assert!(!names.is_empty());
+        assert!(names.iter().all(|x| x.starts_with("ASAM.M.SCALAR.UBYTE.")));
+        assert!(literal.is_empty());
+    }
+
+    #[test]
+    fn read_range() {
+        for path in [
+            "example_files/ASAP2_Demo_V171.mf4",
+            "example_files/ASAP2_Demo_V171_deflate.mf4",
+            "example_files/Single_Channel.dat",
+        ] {
+            let mdf = MDF::new(path);
+            for channel in mdf.channels.iter().step_by(5) {
+                let full = mdf.read_channel(channel);
+                let (start, end) = (full.timestamps[0], full.max_time());
+                for (t_start, t_end) in [
+                    (start, end),
+                    (start - 1.0, start),
+                    (start + (end - start) / 3.0, end - (end - start) / 4.0),
+                    (end, end + 1.0),
+                    (end + 1.0, end + 2.0),
+                ] {
+                    let window: Vec<usize> = (0..full.len())
+                        .filter(|&i| (t_start..=t_end).contains(&full.timestamps[i]))
+                        .collect();
+                    let signal = mdf.read_range(channel, t_start, t_end).unwrap();
+                    assert_eq!(signal.name, full.name);
+                    assert_eq!(
+                        signal.timestamps,
+                        window
+                            .iter()
+                            .map(|&i| full.timestamps[i])
+                            .collect::<Vec<_>>()
+                    );
+                    assert_eq!(
+                        signal.samples,
+                        window
+                            .iter()
+                            .map(|&i| full.samples[i].clone())
+                            .collect::<Vec<_>>()
+                    );
+                }
+            }
+        }
+
+        let mdf = MDF::new("example_files/ASAP2_Demo_V171.mf4");
+        let name = "ASAM.M.SCALAR.FLOAT32.IDENTICAL";
+        let full = mdf.get(name).unwrap();
+        let signal = mdf.read_range(name, 0.0, full.max_time()).unwrap();
+        assert_eq!(signal.samples, full.samples);
+        assert_eq!(
+            mdf.read_range("missing", 0.0, 1.0),
+            Err("Channel not found")
+        );
+        assert_eq!(
+            mdf.read_range(ChannelSelector::Pattern("ASAM.M.*".to_string()), 0.0, 1.0),
+            Err("Multiple matches found")
+        );
+
+        // An MDF3 channel group with zero sized records
+        let mut bytes = std::fs::read("example_files/Single_Channel.dat").unwrap();
+        let read = |bytes: &[u8], at: usize| {
+            u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize
+        };
+        let dg = read(&bytes, 68);
+        let cg = read(&bytes, dg + 8);
+        let mut zero_sized = bytes.clone();
+        zero_sized[cg + 20..cg + 22].copy_from_slice(&[0, 0]);
+        let mdf = MDF::from_bytes(zero_sized);
+        let channel = mdf.channels.last().unwrap();
+        assert!(mdf.read_range(channel, 0.0, 10.0).unwrap().is_empty());
+
+        // Records with IDs cannot be found by their position
+        bytes[dg + 22..dg + 24].copy_from_slice(&[1, 0]);
+        let mdf = MDF::from_bytes(bytes);
+        let channel = mdf.channels.last().unwrap();
+        let unsorted = "Unsorted data groups are not supported";
+        assert_eq!(mdf.read_range(channel, 0.0, 10.0), Err(unsorted));
+        assert_eq!(mdf.select(&[channel], 0, None, true), Err(unsorted));
+        assert!(mdf.iter_chunks(&[channel], 7).is_err());
+    }
+
+    #[test]
+    fn filter() {
+        let mdf = MDF::new("example_files/ASAP2_Demo_V171.mf4");
+
+        let form = mdf
+            .search_channels("ASAM.M.SCALAR.UBYTE.FORM_X_PLUS_4")
+            .unwrap();
+        let filtered = mdf.filter(&[
+            ChannelSelector::Pattern("ASAM.M.SCALAR.*LONG.IDENTICAL".to_string()),
+            ChannelSelector::from(form.clone()),
+            ChannelSelector::from("ASAM.M.SCALAR.FLOAT32.IDENTICAL"),
+            ChannelSelector::from("missing"),
+        ]);
+        assert!(filtered.file.storage().len() < mdf.file.storage().len());
+
+        let mut names: Vec<&str> = filtered.channels.iter().map(|x| x.name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            vec![
+                "ASAM.M.SCALAR.FLOAT32.IDENTICAL",
+                "ASAM.M.SCALAR.SLONG.IDENTICAL",
+                "ASAM.M.SCALAR.UBYTE.FORM_X_PLUS_4",
+                "ASAM.M.SCALAR.ULONG.IDENTICAL",
+                "time",
+                "time"
+            ]
+        );
+
+        for channel in filtered.channels.iter().filter(|x| x.name != "time") {
+            let original = mdf.read_channel(&mdf.search_channels(&channel.name).unwrap());
+            assert_eq!(filtered.read_channel(channel), original);
+        }
+    }
+
+    #[test]
+    fn open_corrupt() {
+        let link = |bytes: &[u8], position: usize| {
+            u64::from_le_bytes(bytes[position..position + 8].try_into().unwrap()) as usize
+        };
+        let open = |bytes: Vec<u8>| MDFType::from_storage(Storage::Memory(bytes)).err();
+
+        let path = "example_files/ASAP2_Demo_V171.mf4";
+        assert!(MDF::open(path).is_ok());
+        assert_eq!(MDF::open("missing.mf4").err(), Some("Could not read file"));
+        let bytes = std::fs::read(path).unwrap();
+        let dg = link(&bytes, 88);
+
+        // The first data group linking back to itself
+        let mut cyclic = bytes.clone();
+        cyclic[dg + 24..dg + 32].copy_from_slice(&(dg as u64).to_le_bytes());
+        assert_eq!(open(cyclic), Some("Linked blocks form a cycle"));
+
+        // The first channel of the first channel group past the end
+        let mut outside = bytes.clone();
+        let cg = link(&bytes, dg + 32);
+        outside[cg + 32..cg + 40].copy_from_slice(&(bytes.len() as u64 + 8).to_le_bytes());
+        assert_eq!(open(outside), Some("Link past the end of the file"));
+
+        // A data list holding itself
+        let mut nested = bytes.clone();
+        let mut next = dg;
+        let list = loop {
+            let data = link(&bytes, next + 40);
+            if &bytes[data..data + 4] == b"##DL" {
+                break data;
+            }
+            next = link(&bytes, next + 24);
+        };
+        nested[list + 32..list + 40].copy_from_slice(&(list as u64).to_le_bytes());
+        assert_eq!(open(nested), Some("Linked blocks form a cycle"));
+
+        // The first data group linking into the middle of the header block
+        let mut inside = bytes.clone();
+        inside[88..96].copy_from_slice(&72_u64.to_le_bytes());
+        assert_eq!(open(inside), Some("Link to a block of the wrong type"));
+
+        // The first data group running past the end of the file
+        let mut long = bytes.clone();
+        long[dg + 8..dg + 16].copy_from_slice(&(bytes.len() as u64).to_le_bytes());
+        assert_eq!(open(long), Some("Block past the end of the file"));
+
+        assert_eq!(open(b"MDF     4.10    ".to_vec()), Some("Not an MDF file"));
+        assert_eq!(
+            open(bytes[..80].to_vec()),
+            Some("Link past the end of the file")
+        );
+        let readme = std::fs::read("README.md").unwrap();
+        assert_eq!(open(readme), Some("Not an MDF file"));
+        let mut future = bytes.clone();
+        future[8..12].copy_from_slice(b"5.00");
+        assert_eq!(open(future), Some("Unsupported MDF version"));
+
+        let mdf3 = std::fs::read("example_files/Single_Channel.dat").unwrap();
+        let dg = u32::from_le_bytes(mdf3[68..72].try_into().unwrap());
+        let mut cyclic = mdf3.clone();
+        let at = dg as usize + 4;
+        cyclic[at..at + 4].copy_from_slice(&dg.to_le_bytes());
+        assert_eq!(open(cyclic), Some("Linked blocks form a cycle"));
+        let mut inside = mdf3.clone();
+        inside[68..72].copy_from_slice(&70_u32.to_le_bytes());
+        assert_eq!(open(inside), Some("Link to a block of the wrong type"));
+        assert_eq!(open(mdf3[..200].to_vec()), Some("No header block found"));
+    }
+}
@@ -1,5 +1,8 @@
-use super::conversion_data::ConversionData;
-use super::mdf3_block::Mdf3Block;
+use super::conversion_data::{self, ConversionData};
+use super::mdf3_block::{Mdf3Block, WriteBlock};
+use super::tx_block::Txblock;
+use crate::conversion::Conversion;
+use crate::storage::Stream;
 use crate::utils;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -16,7 +19,7 @@ pub struct Ccblock {
 }
 
 impl Mdf3Block for Ccblock {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let block_type: [u8; 2] = utils::read(stream, little_endian, &mut pos);
 
@@ -32,10 +35,13 @@ impl Mdf3Block for Ccblock {
         let conversion_type = utils::read(stream, little_endian, &mut pos);
         let size_info = utils::read(stream, little_endian, &mut pos);
 
-        let datatype = 1;
-
-        let (conversion_data, pos_conversion) =
-            ConversionData::read(stream, little_endian, datatype);
+        let end = (position + block_size as usize).max(pos);
+        let (conversion_data, pos_conversion) = ConversionData::read(
+            &stream.bytes(pos, end - pos),
+            little_endian,
+            conversion_type,
+            size_info,
+        );
         pos += pos_conversion;
 
         (
@@ -55,48 +61,154 @@ impl Mdf3Block for Ccblock {
     }
 }
 
+impl WriteBlock for Ccblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.block_type, little_endian);
+        utils::write(stream, self.block_size, little_endian);
+        utils::write(stream, self.physical_range_valid, little_endian);
+        utils::write(stream, self.physical_min, little_endian);
+        utils::write(stream, self.physical_max, little_endian);
+        utils::write(stream, self.unit, little_endian);
+        utils::write(stream, self.conversion_type, little_endian);
+        utils::write(stream, self.size_info, little_endian);
+        self.conversion_data.write(stream, little_endian);
+    }
+}
+
 impl Ccblock {
-    #[allow(dead_code)]
-    pub fn write() {}
+    pub fn create(unit: &str, conversion_type: u16, conversion_data: ConversionData) -> Self {
+        let mut unit_bytes = [0_u8; 20];
+        for (byte, c) in unit_bytes.iter_mut().take(19).zip(unit.bytes()) {
+            *byte = c;
+        }
+
+        let size_info = match &conversion_data {
+            ConversionData::Parameters(parameters) => parameters.len(),
+            ConversionData::Table(table) => table.len(),
+            ConversionData::Text(table) => table.len(),
+            ConversionData::TextRange(table) => table.len(),
+            ConversionData::Other(_) => 0,
+        };
+
+        let mut data = Vec::new();
+        conversion_data.write(&mut data, true);
+
+        Self {
+            block_type: *b"CC",
+            block_size: (46 + data.len()) as u16,
+            physical_range_valid: 0,
+            physical_min: 0.0,
+            physical_max: 0.0,
+            unit: unit_bytes,
+            conversion_type,
+            size_info: size_info as u16,
+            conversion_data,
+        }
+    }
+
+    pub fn unit(&self) -> String {
+        let end = self.unit.iter().position(|x| *x == 0).unwrap_or(20);
+        String::from_utf8_lossy(&self.unit[..end]).into_owned()
+    }
+
+    /// True for conversions other than one to one that [`Ccblock::conversion`]
+    /// cannot express.
+    pub fn is_unsupported(&self, stream: &dyn Stream, little_endian: bool) -> bool {
+        self.conversion_type != conversion_data::ONE_TO_ONE
+            && self.conversion(stream, little_endian).is_none()
+    }
+
+    pub fn conversion(&self, stream: &dyn Stream, little_endian: bool) -> Option<Conversion> {
+        let text = |address: u32| {
+            if address == 0 {
+                String::new()
+            } else {
+                Txblock::read(stream, address as usize, little_endian)
+                    .1
+                    .text()
+            }
+        };
+
+        match (self.conversion_type, &self.conversion_data) {
+            (conversion_data::LINEAR, ConversionData::Parameters(parameters)) => {
+                Some(Conversion::Linear {
+                    offset: parameters[0],
+                    factor: parameters[1],
+                })
+            }
+            (conversion_data::TABULAR_INTERPOLATION, ConversionData::Table(table)) => {
+                Some(Conversion::TabularInterpolation {
+                    raw: table.iter().map(|x| x.internal).collect(),
+                    phys: table.iter().map(|x| x.physical).collect(),
+                })
+            }
+            (conversion_data::TABULAR, ConversionData::Table(table)) => Some(Conversion::Tabular {
+                raw: table.iter().map(|x| x.internal).collect(),
+                phys: table.iter().map(|x| x.physical).collect(),
+            }),
+            (_, ConversionData::Text(table)) => Some(Conversion::ValueToText {
+                values: table.iter().map(|x| x.internal).collect(),
+                texts: table
+                    .iter()
+                    .map(|x| {
+                        let end = x.text.iter().position(|c| *c == 0).unwrap_or(32);
+                        String::from_utf8_lossy(&x.text[..end]).into_owned()
+                    })
+                    .collect(),
+                default: String::new(),
+            }),
+            // Only ranges covering a single value map onto a value to text table
+            (_, ConversionData::TextRange(table))
+                if table.iter().skip(1).all(|x| x.lower == x.upper) =>
+            {
+                Some(Conversion::ValueToText {
+                    values: table.iter().skip(1).map(|x| x.lower).collect(),
+                    texts: table.iter().skip(1).map(|x| text(x.txblock)).collect(),
+                    default: table.first().map_or(String::new(), |x| text(x.txblock)),
+                })
+            }
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::mdf3::conversion::TableEntry;
     use crate::utils;
 
     use super::*;
 
+    static RAW: [u8; 233] = [
+        0x43, 0x43, 0x2E, 0x00, 0x01, 0x00, 0x04, 0x19, 0x60, 0x9C, 0xAE, 0xDD, 0xBC, 0x3F, 0x52,
+        0xE8, 0x62, 0xFA, 0x56, 0xD3, 0x28, 0x40, 0x73, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00,
+        0x00, 0x43, 0x45, 0x80, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x43, 0x68,
+        0x61, 0x6E, 0x6E, 0x65, 0x6C, 0x20, 0x69, 0x6E, 0x73, 0x65, 0x72, 0x74, 0x65, 0x64, 0x20,
+        0x62, 0x79, 0x20, 0x50, 0x79, 0x74, 0x68, 0x6F, 0x6E, 0x20, 0x53, 0x63, 0x72, 0x69, 0x70,
+        0x74, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x43, 0x4E, 0xE4, 0x00, 0xA6, 0xE3,
+        0x10, 0x00, 0x80, 0xE0, 0x10, 0x00, 0xAE, 0xE0, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x01, 0x00, 0x74, 0x69, 0x6D, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
     #[test]
     fn read() {
-        let cc_data = [
-            0x43, 0x43, 0x2E, 0x00, 0x01, 0x00, 0x04, 0x19, 0x60, 0x9C, 0xAE, 0xDD, 0xBC, 0x3F,
-            0x52, 0xE8, 0x62, 0xFA, 0x56, 0xD3, 0x28, 0x40, 0x73, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0xFF, 0xFF, 0x00, 0x00, 0x43, 0x45, 0x80, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x43, 0x68, 0x61, 0x6E, 0x6E, 0x65, 0x6C, 0x20, 0x69, 0x6E, 0x73, 0x65,
-            0x72, 0x74, 0x65, 0x64, 0x20, 0x62, 0x79, 0x20, 0x50, 0x79, 0x74, 0x68, 0x6F, 0x6E,
-            0x20, 0x53, 0x63, 0x72, 0x69, 0x70, 0x74, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x43, 0x4E, 0xE4, 0x00, 0xA6, 0xE3, 0x10, 0x00,
-            0x80, 0xE0, 0x10, 0x00, 0xAE, 0xE0, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x01, 0x00, 0x74, 0x69, 0x6D, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-        ];
-
-        let (position, cc_block) = Ccblock::read(&cc_data, 0, true);
-
-        assert_eq!(position, 47); // should match the block size
+        let (position, cc_block) = Ccblock::read(&RAW, 0, true);
+
+        assert_eq!(position, 46); // should match the block size
         assert_eq!(cc_block.block_size, 46);
         assert_eq!(cc_block.physical_range_valid, 1);
 
         assert!(
             (cc_block.physical_min
-                - utils::read::<f64>(
+                - utils::read::<f64, _>(
                     &[0x04, 0x19, 0x60, 0x9C, 0xAE, 0xDD, 0xBC, 0x3F],
                     true,
                     &mut 0_usize
@@ -106,7 +218,7 @@ mod tests {
         );
         assert!(
             (cc_block.physical_max
-                - utils::read::<f64>(
+                - utils::read::<f64, _>(
                     &[0x52, 0xE8, 0x62, 0xFA, 0x56, 0xD3, 0x28, 0x40],
                     true,
                     &mut 0_usize
@@ -128,5 +240,46 @@ mod tests {
     }
 
     #[test]
-    fn write() {}
+    fn write() {
+        let (position, cc_block) = Ccblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        cc_block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..position], &stream[..]);
+    }
+
+    #[test]
+    fn conversion() {
+        let cc_block = Ccblock::create(
+            "rpm",
+            conversion_data::TABULAR_INTERPOLATION,
+            ConversionData::Table(vec![
+                TableEntry {
+                    internal: 0.0,
+                    physical: 10.0,
+                },
+                TableEntry {
+                    internal: 10.0,
+                    physical: 30.0,
+                },
+            ]),
+        );
+
+        let mut stream = Vec::new();
+        cc_block.write(&mut stream, false);
+
+        let (position, read) = Ccblock::read(&stream, 0, false);
+
+        assert_eq!(position, stream.len());
+        assert_eq!(read.block_size as usize, stream.len());
+        assert_eq!(read.unit(), "rpm");
+        assert_eq!(
+            read.conversion(&stream, false),
+            Some(Conversion::TabularInterpolation {
+                raw: vec![0.0, 10.0],
+                phys: vec![10.0, 30.0]
+            })
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use crate::storage::Stream;
 use crate::utils;
 use chrono::{NaiveDateTime, TimeZone};
 use itertools::izip;
@@ -22,7 +23,7 @@ pub enum ConversionTypes {
 
 impl ConversionTypes {
     #[allow(dead_code)]
-    fn new(stream: &[u8], position: usize, little_endian: bool, conversion: u16) -> Self {
+    fn new(stream: &dyn Stream, position: usize, little_endian: bool, conversion: u16) -> Self {
         match conversion {
             0 => {
                 let (_pos, con) = Linear::read(stream, position, little_endian);
@@ -100,14 +101,14 @@ impl ConversionTypes {
     }
 }
 
+#[allow(dead_code)]
 pub enum Physical {
     Text(Vec<String>),
     Value(Vec<f64>),
-    
 }
 
 trait Conversion {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self);
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self);
     fn convert(&self, data: &[f64]) -> Physical;
 }
 
@@ -116,7 +117,7 @@ pub struct Linear {
     p2: f64,
 }
 impl Conversion for Linear {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let p1 = utils::read(stream, little_endian, &mut pos);
         let p2 = utils::read(stream, little_endian, &mut pos);
@@ -135,7 +136,7 @@ impl Conversion for Linear {
 
 pub struct TabularInterpolation {}
 impl Conversion for TabularInterpolation {
-    fn read(_stream: &[u8], _position: usize, _little_endian: bool) -> (usize, Self) {
+    fn read(_stream: &dyn Stream, _position: usize, _little_endian: bool) -> (usize, Self) {
         todo!()
     }
 
@@ -146,7 +147,7 @@ impl Conversion for TabularInterpolation {
 
 pub struct Tabular {}
 impl Conversion for Tabular {
-    fn read(_stream: &[u8], _position: usize, _little_endian: bool) -> (usize, Self) {
+    fn read(_stream: &dyn Stream, _position: usize, _little_endian: bool) -> (usize, Self) {
         todo!()
     }
 
@@ -165,7 +166,7 @@ pub struct Polynomial {
 }
 
 impl Conversion for Polynomial {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let p1 = utils::read(stream, little_endian, &mut pos);
         let p2 = utils::read(stream, little_endian, &mut pos);
@@ -209,7 +210,7 @@ pub struct Exponential {
 }
 
 impl Conversion for Exponential {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let p1 = utils::read(stream, little_endian, &mut pos);
         let p2 = utils::read(stream, little_endian, &mut pos);
@@ -265,7 +266,7 @@ pub struct Logarithmic {
     p7: f64,
 }
 impl Conversion for Logarithmic {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let p1 = utils::read(stream, little_endian, &mut pos);
         let p2 = utils::read(stream, little_endian, &mut pos);
@@ -320,7 +321,7 @@ pub struct Rational {
 }
 
 impl Conversion for Rational {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let p1: f64 = utils::read(stream, little_endian, &mut pos);
         let p2: f64 = utils::read(stream, little_endian, &mut pos);
@@ -360,7 +361,7 @@ pub struct TextFormula {
 }
 
 impl Conversion for TextFormula {
-    fn read(_stream: &[u8], _position: usize, _little_endian: bool) -> (usize, Self) {
+    fn read(_stream: &dyn Stream, _position: usize, _little_endian: bool) -> (usize, Self) {
         todo!()
     }
 
@@ -369,6 +370,7 @@ impl Conversion for TextFormula {
     }
 }
 
+#[allow(dead_code)]
 trait LookupTable {
     fn lookup(&self, search: &[f64]) -> Physical;
 }
@@ -391,7 +393,7 @@ impl TextTable {
 }
 
 impl Conversion for TextTable {
-    fn read(_stream: &[u8], _position: usize, _little_endian: bool) -> (usize, Self) {
+    fn read(_stream: &dyn Stream, _position: usize, _little_endian: bool) -> (usize, Self) {
         todo!()
     }
 
@@ -434,7 +436,7 @@ impl TextRangeTable {
 }
 
 impl Conversion for TextRangeTable {
-    fn read(_stream: &[u8], _position: usize, _little_endian: bool) -> (usize, Self) {
+    fn read(_stream: &dyn Stream, _position: usize, _little_endian: bool) -> (usize, Self) {
         todo!()
     }
 
@@ -459,7 +461,7 @@ use crate::mdf3::mdf3_block::Mdf3Block;
 
 impl TextRangeEntry {
     #[allow(dead_code)]
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let lower = utils::read(stream, little_endian, &mut pos);
         let upper = utils::read(stream, little_endian, &mut pos);
@@ -489,20 +491,20 @@ pub struct Date {
 impl Date {
     #[allow(dead_code)]
     fn to_datetime(&self) -> NaiveDateTime {
-        let _date_time = chrono::Local
-            .ymd(self.year as i32, self.month as u32, self.day as u32)
-            .and_hms_milli(
-                self.hour as u32,
-                self.min as u32,
-                (self.ms / 1000) as u32,
-                (self.ms % 1000) as u32,
-            );
+        let _date_time = chrono::Local.with_ymd_and_hms(
+            self.year as i32,
+            self.month as u32,
+            self.day as u32,
+            self.hour as u32,
+            self.min as u32,
+            (self.ms / 1000) as u32,
+        );
         todo!()
     }
 }
 
 impl Conversion for Date {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let ms = utils::read(stream, little_endian, &mut pos);
         let min = utils::read(stream, little_endian, &mut pos);
@@ -537,12 +539,12 @@ pub struct Time {
 }
 
 impl Conversion for Time {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let ms = utils::read(stream, little_endian, &mut pos);
         let days = utils::read(stream, little_endian, &mut pos);
 
-        (pos, Self { ms, days }, )
+        (pos, Self { ms, days })
     }
 
     fn convert(&self, _data: &[f64]) -> Physical {
@@ -552,7 +554,7 @@ impl Conversion for Time {
 
 pub struct Direct {}
 impl Conversion for Direct {
-    fn read(_stream: &[u8], _position: usize, _little_endian: bool) -> (usize, Self) {
+    fn read(_stream: &dyn Stream, _position: usize, _little_endian: bool) -> (usize, Self) {
         todo!()
     }
 
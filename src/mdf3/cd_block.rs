@@ -9,7 +9,7 @@ pub struct Cdblock {
 }
 
 impl Mdf3Block for Cdblock {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let block_type: [u8; 2] = utils::read(stream, little_endian, &mut pos);
 
@@ -84,6 +84,7 @@ impl Cdblock {
 //     fn write() {}
 // }
 
+use crate::storage::Stream;
 use crate::{mdf3::signals::Signals, utils};
 
 use super::mdf3_block::Mdf3Block;
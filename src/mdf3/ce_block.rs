@@ -1,7 +1,16 @@
+use crate::signal::Source;
+use crate::storage::Stream;
 use crate::utils;
 
-use super::mdf3_block::Mdf3Block;
+use super::cn_block::fixed_text;
+use super::mdf3_block::{Mdf3Block, WriteBlock};
 
+const DIM: u16 = 2;
+const VECTOR_CAN: u16 = 19;
+const DIM_DESCRIPTION_LENGTH: usize = 80;
+const DIM_ECU_LENGTH: usize = 32;
+
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Ceblock {
     block_type: [u8; 2],
@@ -11,16 +20,18 @@ pub struct Ceblock {
 }
 
 impl Mdf3Block for Ceblock {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let block_type: [u8; 2] = utils::read(stream, little_endian, &mut pos);
         let block_size = utils::read(stream, little_endian, &mut pos);
         let extension_type = utils::read(stream, little_endian, &mut pos);
 
-        let additional = stream[pos..block_size as usize].to_vec();
+        let additional = stream
+            .bytes(pos, position + block_size as usize - pos)
+            .to_vec();
 
         (
-            pos,
+            position + block_size as usize,
             Ceblock {
                 block_type,
                 block_size,
@@ -31,42 +42,127 @@ impl Mdf3Block for Ceblock {
     }
 }
 
+impl WriteBlock for Ceblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.block_type, little_endian);
+        utils::write(stream, self.block_size, little_endian);
+        utils::write(stream, self.extension_type, little_endian);
+        stream.extend_from_slice(&self.additional);
+    }
+}
+
 impl Ceblock {
-    #[allow(dead_code)]
-    pub fn write() {}
+    /// A DIM extension, the only one carrying free text for both a name and a
+    /// path. Longer texts are truncated.
+    pub fn dim(description: &str, ecu_id: &str) -> Self {
+        // Module number and address stay zero
+        let mut additional = vec![0_u8; 6];
+        additional.extend(fixed_field(description, DIM_DESCRIPTION_LENGTH));
+        additional.extend(fixed_field(ecu_id, DIM_ECU_LENGTH));
+        additional.resize(122, 0);
+
+        Self {
+            block_type: *b"CE",
+            block_size: 6 + additional.len() as u16,
+            extension_type: DIM,
+            additional,
+        }
+    }
+
+    /// The DIM description or CAN message becomes the source name, the ECU
+    /// or sending node becomes its path.
+    pub fn source(&self) -> Option<Source> {
+        match self.extension_type {
+            DIM if self.additional.len() >= 118 => Some(Source {
+                name: fixed_text(&self.additional[6..86]),
+                path: fixed_text(&self.additional[86..118]),
+                comment: String::new(),
+            }),
+            VECTOR_CAN if self.additional.len() >= 80 => Some(Source {
+                name: fixed_text(&self.additional[8..44]),
+                path: fixed_text(&self.additional[44..80]),
+                comment: String::new(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether `source` fits a DIM extension without losing anything.
+    pub fn fits(source: &Source) -> bool {
+        source.name.len() < DIM_DESCRIPTION_LENGTH
+            && source.path.len() < DIM_ECU_LENGTH
+            && source.comment.is_empty()
+    }
+}
+
+fn fixed_field(text: &str, length: usize) -> Vec<u8> {
+    let mut field = vec![0_u8; length];
+    for (byte, c) in field.iter_mut().take(length - 1).zip(text.bytes()) {
+        *byte = c;
+    }
+    field
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    static RAW: [u8; 204] = [
+        0x43, 0x45, 0x80, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x43, 0x68, 0x61,
+        0x6E, 0x6E, 0x65, 0x6C, 0x20, 0x69, 0x6E, 0x73, 0x65, 0x72, 0x74, 0x65, 0x64, 0x20, 0x62,
+        0x79, 0x20, 0x50, 0x79, 0x74, 0x68, 0x6F, 0x6E, 0x20, 0x53, 0x63, 0x72, 0x69, 0x70, 0x74,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x43, 0x4E, 0xE4, 0x00, 0xA6, 0xE3, 0x10,
+        0x00, 0x80, 0xE0, 0x10, 0x00, 0xAE, 0xE0, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x01, 0x00, 0x74, 0x69, 0x6D, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
     #[test]
     fn read() {
-        let ce_data = [
-            0x43, 0x45, 0x80, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x43, 0x68,
-            0x61, 0x6E, 0x6E, 0x65, 0x6C, 0x20, 0x69, 0x6E, 0x73, 0x65, 0x72, 0x74, 0x65, 0x64,
-            0x20, 0x62, 0x79, 0x20, 0x50, 0x79, 0x74, 0x68, 0x6F, 0x6E, 0x20, 0x53, 0x63, 0x72,
-            0x69, 0x70, 0x74, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x43, 0x4E, 0xE4, 0x00, 0xA6, 0xE3, 0x10, 0x00, 0x80, 0xE0, 0x10, 0x00,
-            0xAE, 0xE0, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
-            0x74, 0x69, 0x6D, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-        ];
-
-        let (_position, ce_block) = Ceblock::read(&ce_data, 0, true);
-
-        // assert_eq!(position, 0);
+        let (position, ce_block) = Ceblock::read(&RAW, 0, true);
+
+        assert_eq!(position, 128);
         assert_eq!(ce_block.block_size, 128);
         assert_eq!(ce_block.extension_type, 2);
+        assert_eq!(
+            ce_block.source(),
+            Some(Source {
+                name: "Channel inserted by Python Script".to_string(),
+                path: String::new(),
+                comment: String::new(),
+            })
+        );
     }
 
     #[test]
-    fn write() {}
+    fn dim() {
+        let source = Source {
+            name: "Channel inserted by Python Script".to_string(),
+            path: String::new(),
+            comment: String::new(),
+        };
+        assert!(Ceblock::fits(&source));
+
+        let mut stream = Vec::new();
+        Ceblock::dim(&source.name, &source.path).write(&mut stream, true);
+
+        assert_eq!(&RAW[..128], &stream[..]);
+    }
+
+    #[test]
+    fn write() {
+        let (_position, ce_block) = Ceblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        ce_block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..stream.len()], &stream[..]);
+    }
 }
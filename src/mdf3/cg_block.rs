@@ -1,8 +1,9 @@
+use crate::storage::Stream;
 use crate::utils;
 
 use super::{
     cn_block::Cnblock,
-    mdf3_block::{LinkedBlock, Mdf3Block},
+    mdf3_block::{LinkedBlock, Mdf3Block, WriteBlock},
     tx_block::Txblock,
 };
 
@@ -31,38 +32,16 @@ pub struct Cgblock {
 }
 
 impl LinkedBlock for Cgblock {
-    fn next(&self, stream: &[u8], little_endian: bool) -> Option<Self>
-    where
-        Self: std::marker::Sized,
-    {
-        if self.next == 0 {
-            None
-        } else {
-            let (_pos, block) = Self::read(stream, self.next as usize, little_endian);
-            Some(block)
-        }
-    }
-
-    fn list(&self, stream: &[u8], little_endian: bool) -> Vec<Self>
-    where
-        Self: std::marker::Sized,
-    {
-        let mut all = Vec::new();
-
-        let next = self.next(stream, little_endian);
-
-        all.push(*self);
-        match next {
-            None => {}
-            Some(block) => all.append(&mut block.list(stream, little_endian)),
-        }
+    const ID: &'static [u8; 2] = b"CG";
+    const SIZE: usize = 30;
 
-        all
+    fn link(&self) -> u64 {
+        self.next.into()
     }
 }
 
 impl Mdf3Block for Cgblock {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let block_type: [u8; 2] = utils::read(stream, little_endian, &mut pos);
         // stream[pos..pos + 2].try_into().expect("msg");
@@ -102,7 +81,44 @@ impl Mdf3Block for Cgblock {
     }
 }
 
+impl WriteBlock for Cgblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.block_type, little_endian);
+        utils::write(stream, self.block_size, little_endian);
+        utils::write(stream, self.next, little_endian);
+        utils::write(stream, self.first, little_endian);
+        utils::write(stream, self.comment, little_endian);
+        utils::write(stream, self.record_id, little_endian);
+        utils::write(stream, self.number_of_channels, little_endian);
+        utils::write(stream, self.record_size, little_endian);
+        utils::write(stream, self.record_number, little_endian);
+        utils::write(stream, self.first_sample_reduction_block, little_endian);
+    }
+}
+
 impl Cgblock {
+    pub fn create(
+        next: u32,
+        first: u32,
+        comment: u32,
+        number_of_channels: u16,
+        record_size: u16,
+        record_number: u32,
+    ) -> Self {
+        Self {
+            block_type: *b"CG",
+            block_size: 30,
+            next,
+            first,
+            comment,
+            record_id: 0,
+            number_of_channels,
+            record_size,
+            record_number,
+            first_sample_reduction_block: 0,
+        }
+    }
+
     #[allow(dead_code)]
     pub fn data_length(&self) -> usize {
         self.record_number as usize * self.record_size as usize
@@ -116,7 +132,7 @@ impl Cgblock {
         self.record_size as usize
     }
 
-    pub fn first_channel(&self, stream: &[u8], little_endian: bool) -> Cnblock {
+    pub fn first_channel(&self, stream: &dyn Stream, little_endian: bool) -> Cnblock {
         if self.first == 0 {
             panic!("Error");
         }
@@ -125,15 +141,21 @@ impl Cgblock {
         cn
     }
 
-    pub fn comment(&self, stream: &[u8], little_endian: bool) -> String {
+    pub fn comment(&self, stream: &dyn Stream, little_endian: bool) -> String {
+        if self.comment == 0 {
+            return String::new();
+        }
+
         let (_pos, tx) = Txblock::read(stream, self.comment as usize, little_endian);
         tx.name()
     }
-    #[allow(dead_code)]
-    pub fn write() {}
-    pub fn channels(self, stream: &[u8], little_endian: bool) -> Vec<Cnblock> {
-        let first_channel = self.first_channel(stream, little_endian);
-        first_channel.list(stream, little_endian)
+
+    pub fn channels(
+        &self,
+        stream: &dyn Stream,
+        little_endian: bool,
+    ) -> Result<Vec<Cnblock>, &'static str> {
+        Cnblock::list(stream, self.first as usize, little_endian)
     }
 }
 
@@ -141,15 +163,14 @@ impl Cgblock {
 mod tests {
     use super::*;
 
+    static RAW: [u8; 30] = [
+        0x43, 0x47, 0x1E, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2E, 0xE1, 0x10, 0x00, 0x8A, 0xE4, 0x10,
+        0x00, 0x01, 0x00, 0x02, 0x00, 0x09, 0x00, 0x7C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
     #[test]
     fn read() {
-        let cg_data = [
-            0x43, 0x47, 0x1E, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2E, 0xE1, 0x10, 0x00, 0x8A, 0xE4,
-            0x10, 0x00, 0x01, 0x00, 0x02, 0x00, 0x09, 0x00, 0x7C, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00,
-        ];
-
-        let (position, cg_block) = Cgblock::read(&cg_data, 0, true);
+        let (position, cg_block) = Cgblock::read(&RAW, 0, true);
 
         assert_eq!(position, 30);
 
@@ -164,5 +185,12 @@ mod tests {
     }
 
     #[test]
-    fn write() {}
+    fn write() {
+        let (position, cg_block) = Cgblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        cg_block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..position], &stream[..]);
+    }
 }
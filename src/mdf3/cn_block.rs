@@ -1,8 +1,16 @@
-use crate::{record::DataTypeRead, utils};
+use crate::storage::Stream;
+use crate::{
+    conversion::Conversion,
+    record::{DataType, DataTypeRead, Record},
+    signal::Source,
+    utils,
+};
 
 use super::{
+    cc_block::Ccblock,
+    ce_block::Ceblock,
     channel_type::ChannelType,
-    mdf3_block::{LinkedBlock, Mdf3Block},
+    mdf3_block::{LinkedBlock, Mdf3Block, WriteBlock},
     tx_block::Txblock,
 };
 
@@ -51,38 +59,16 @@ pub struct Cnblock {
 }
 
 impl LinkedBlock for Cnblock {
-    fn next(&self, stream: &[u8], little_endian: bool) -> Option<Self>
-    where
-        Self: std::marker::Sized,
-    {
-        if self.next == 0 {
-            None
-        } else {
-            let (_pos, block) = Self::read(stream, self.next as usize, little_endian);
-            Some(block)
-        }
-    }
-
-    fn list(&self, stream: &[u8], little_endian: bool) -> Vec<Self>
-    where
-        Self: std::marker::Sized,
-    {
-        let mut all = Vec::new();
-
-        let next = self.next(stream, little_endian);
+    const ID: &'static [u8; 2] = b"CN";
+    const SIZE: usize = 228;
 
-        all.push(*self);
-        match next {
-            None => {}
-            Some(block) => all.append(&mut block.list(stream, little_endian)),
-        }
-
-        all
+    fn link(&self) -> u64 {
+        self.next.into()
     }
 }
 
 impl Mdf3Block for Cnblock {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let block_type: [u8; 2] = utils::read(stream, little_endian, &mut pos);
         if !utils::eq(&block_type, "CN".as_bytes()) {
@@ -143,74 +129,222 @@ impl Mdf3Block for Cnblock {
     }
 }
 
+impl WriteBlock for Cnblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.block_type, little_endian);
+        utils::write(stream, self.block_size, little_endian);
+        utils::write(stream, self.next, little_endian);
+        utils::write(stream, self.conversion_formula, little_endian);
+        utils::write(stream, self.source_ext, little_endian);
+        utils::write(stream, self.dependency, little_endian);
+        utils::write(stream, self.comment, little_endian);
+        utils::write(stream, self.channel_type, little_endian);
+        utils::write(stream, self.short_name, little_endian);
+        utils::write(stream, self.desc, little_endian);
+        utils::write(stream, self.start_offset, little_endian);
+        utils::write(stream, self.bit_number, little_endian);
+        utils::write(stream, self.data_type.code(little_endian), little_endian);
+        utils::write(stream, self.value_range_valid, little_endian);
+        utils::write(stream, self.signal_min, little_endian);
+        utils::write(stream, self.signal_max, little_endian);
+        utils::write(stream, self.sample_rate, little_endian);
+        utils::write(stream, self.long_name, little_endian);
+        utils::write(stream, self.display_name, little_endian);
+        utils::write(stream, self.addition_byte_offset, little_endian);
+    }
+}
+
 impl Cnblock {
+    /// `name` and `description` are truncated to fit their fixed length
+    /// fields, the full name can be linked through `long_name`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(
+        next: u32,
+        conversion_formula: u32,
+        source_ext: u32,
+        comment: u32,
+        channel_type: ChannelType,
+        name: &str,
+        description: &str,
+        long_name: u32,
+        start_offset: u16,
+        bit_number: u16,
+        data_type: DataTypeRead,
+    ) -> Self {
+        let mut short_name = [0_u8; 32];
+        for (byte, c) in short_name.iter_mut().take(31).zip(name.bytes()) {
+            *byte = c;
+        }
+        let mut desc = [0_u8; 128];
+        for (byte, c) in desc.iter_mut().take(127).zip(description.bytes()) {
+            *byte = c;
+        }
+
+        Self {
+            block_type: *b"CN",
+            block_size: 228,
+            next,
+            conversion_formula,
+            source_ext,
+            dependency: 0,
+            comment,
+            channel_type: if channel_type.is_time() { 1 } else { 0 },
+            short_name,
+            desc,
+            start_offset,
+            bit_number,
+            data_type,
+            value_range_valid: 0,
+            signal_min: 0.0,
+            signal_max: 0.0,
+            sample_rate: 0.0,
+            long_name,
+            display_name: 0,
+            addition_byte_offset: 0,
+        }
+    }
+
     pub fn channel_type(&self) -> ChannelType {
         ChannelType::new(self.channel_type)
     }
     pub fn byte_offset(&self) -> usize {
-        self.start_offset as usize / 8
+        self.start_offset as usize / 8 + self.addition_byte_offset as usize
     }
 
     pub fn data_type_len(&self) -> usize {
-        self.data_type.len()
+        match self.data_type.data_type {
+            DataType::UnsignedInt | DataType::SignedInt => {
+                (self.start_offset as usize % 8 + self.bit_number as usize).div_ceil(8)
+            }
+            _ => self.bit_number as usize / 8,
+        }
     }
 
     pub fn data_type(&self) -> DataTypeRead {
         self.data_type
     }
-    #[allow(dead_code)]
-    pub fn write() {}
 
-    pub fn name(self, stream: &[u8], little_endian: bool) -> String {
-        let mut name = "".to_string();
+    /// Decodes this channel's value from a single record.
+    pub fn decode(&self, record: &[u8]) -> Record {
+        let start = self.byte_offset();
+        let raw = &record[start..start + self.data_type_len()];
 
+        match self.data_type.data_type {
+            DataType::UnsignedInt | DataType::SignedInt => Record::new(raw, self.data_type)
+                .mask(self.start_offset as u32 % 8, self.bit_number as u32),
+            _ => Record::new(raw, self.data_type),
+        }
+    }
+
+    pub fn name(&self, stream: &dyn Stream, little_endian: bool) -> String {
         if self.channel_type == 1 {
-            name = "time".to_string();
+            "time".to_string()
         } else if self.long_name != 0 {
             let (_pos, tx) = Txblock::read(stream, self.long_name as usize, little_endian);
-            tx.name();
-            // name = match std::str::from_utf8(&tx.text) {
-            //     Ok(v) => v.to_string(),
-            //     Err(e) => panic!("Invalid UTF-8 sequence: {}", e),
-            // };
+            tx.text()
+        } else {
+            fixed_text(&self.short_name)
+        }
+    }
+
+    /// The name linked as display name, empty when there is none.
+    pub fn display_name(&self, stream: &dyn Stream, little_endian: bool) -> String {
+        if self.display_name == 0 {
+            return String::new();
         }
 
-        name
+        let (_pos, tx) = Txblock::read(stream, self.display_name as usize, little_endian);
+        tx.text()
+    }
+
+    pub fn comment(&self, stream: &dyn Stream, little_endian: bool) -> String {
+        if self.comment != 0 {
+            let (_pos, tx) = Txblock::read(stream, self.comment as usize, little_endian);
+            let text = tx.text();
+            if text.starts_with('<') {
+                utils::extract_tx(text.as_bytes())
+            } else {
+                text
+            }
+        } else {
+            fixed_text(&self.desc)
+        }
+    }
+
+    pub fn unit(&self, stream: &dyn Stream, little_endian: bool) -> String {
+        match self.conversion_block(stream, little_endian) {
+            Some(cc) => cc.unit(),
+            None => String::new(),
+        }
+    }
+
+    pub fn conversion(&self, stream: &dyn Stream, little_endian: bool) -> Option<Conversion> {
+        self.conversion_block(stream, little_endian)?
+            .conversion(stream, little_endian)
+    }
+
+    pub fn has_unsupported_conversion(&self, stream: &dyn Stream, little_endian: bool) -> bool {
+        match self.conversion_block(stream, little_endian) {
+            Some(cc) => cc.is_unsupported(stream, little_endian),
+            None => false,
+        }
+    }
+
+    pub fn source(&self, stream: &dyn Stream, little_endian: bool) -> Option<Source> {
+        if self.source_ext == 0 {
+            return None;
+        }
+
+        let (_pos, ce) = Ceblock::read(stream, self.source_ext as usize, little_endian);
+        ce.source()
+    }
+
+    fn conversion_block(&self, stream: &dyn Stream, little_endian: bool) -> Option<Ccblock> {
+        if self.conversion_formula == 0 {
+            None
+        } else {
+            let (_pos, cc) = Ccblock::read(stream, self.conversion_formula as usize, little_endian);
+            Some(cc)
+        }
     }
 }
 
+pub fn fixed_text(text: &[u8]) -> String {
+    let end = text.iter().position(|x| *x == 0).unwrap_or(text.len());
+    String::from_utf8_lossy(&text[..end]).into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::utils;
 
     use super::*;
 
+    static RAW: [u8; 274] = [
+        0x43, 0x4E, 0xE4, 0x00, 0xA6, 0xE3, 0x10, 0x00, 0x80, 0xE0, 0x10, 0x00, 0xAE, 0xE0, 0x10,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x74, 0x69, 0x6D, 0x65,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x03, 0x00, 0x01, 0x00, 0x04,
+        0x19, 0x60, 0x9C, 0xAE, 0xDD, 0xBC, 0x3F, 0x52, 0xE8, 0x62, 0xFA, 0x56, 0xD3, 0x28, 0x40,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x54, 0x58, 0x2B, 0x00, 0x41, 0x53, 0x41, 0x4D, 0x2E, 0x4D, 0x2E, 0x53,
+        0x43, 0x41, 0x4C, 0x41, 0x52, 0x2E, 0x53, 0x42, 0x59, 0x54, 0x45, 0x2E, 0x49, 0x44, 0x45,
+        0x4E, 0x54, 0x49, 0x43, 0x41, 0x4C, 0x2E, 0x44, 0x49, 0x53, 0x43, 0x52, 0x45, 0x54, 0x45,
+        0x00, 0x54, 0x58, 0xBB,
+    ];
+
     #[test]
     fn read() {
-        let cn_data = [
-            0x43, 0x4E, 0xE4, 0x00, 0xA6, 0xE3, 0x10, 0x00, 0x80, 0xE0, 0x10, 0x00, 0xAE, 0xE0,
-            0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x74, 0x69,
-            0x6D, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x03, 0x00, 0x01, 0x00, 0x04, 0x19,
-            0x60, 0x9C, 0xAE, 0xDD, 0xBC, 0x3F, 0x52, 0xE8, 0x62, 0xFA, 0x56, 0xD3, 0x28, 0x40,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x54, 0x58, 0x2B, 0x00, 0x41, 0x53, 0x41, 0x4D, 0x2E, 0x4D,
-            0x2E, 0x53, 0x43, 0x41, 0x4C, 0x41, 0x52, 0x2E, 0x53, 0x42, 0x59, 0x54, 0x45, 0x2E,
-            0x49, 0x44, 0x45, 0x4E, 0x54, 0x49, 0x43, 0x41, 0x4C, 0x2E, 0x44, 0x49, 0x53, 0x43,
-            0x52, 0x45, 0x54, 0x45, 0x00, 0x54, 0x58, 0xBB,
-        ];
-
-        let (_pos, cn_block) = Cnblock::read(&cn_data, 0, true);
+        let (_pos, cn_block) = Cnblock::read(&RAW, 0, true);
 
         //assert_eq!(position, 228);
         assert_eq!(cn_block.block_size, 228);
@@ -251,7 +385,7 @@ mod tests {
 
         assert!(
             (cn_block.signal_min
-                - utils::read::<f64>(
+                - utils::read::<f64, _>(
                     &[0x04, 0x19, 0x60, 0x9C, 0xAE, 0xDD, 0xBC, 0x3F,],
                     true,
                     &mut 0_usize
@@ -261,7 +395,7 @@ mod tests {
         );
         assert!(
             (cn_block.signal_max
-                - utils::read::<f64>(
+                - utils::read::<f64, _>(
                     &[0x52, 0xE8, 0x62, 0xFA, 0x56, 0xD3, 0x28, 0x40,],
                     true,
                     &mut 0_usize
@@ -276,5 +410,12 @@ mod tests {
     }
 
     #[test]
-    fn write() {}
+    fn write() {
+        let (position, cn_block) = Cnblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        cn_block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..position], &stream[..]);
+    }
 }
@@ -1,5 +1,6 @@
 use crate::utils;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct ConversionLinear {
     pub p1: f64,
@@ -19,6 +20,7 @@ impl ConversionLinear {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct ConversionPoly {
     pub p1: f64,
@@ -56,6 +58,7 @@ impl ConversionPoly {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct ConversionExponetial {
     pub p1: f64,
@@ -96,6 +99,7 @@ impl ConversionExponetial {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct ConversionLog {
     pub p1: f64,
@@ -136,6 +140,7 @@ impl ConversionLog {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct ConversionRational {
     pub p1: f64,
@@ -173,12 +178,14 @@ impl ConversionRational {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Table {
     #[allow(dead_code)]
     ConversionTabular,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct ConversionTabular {
     pub value: Vec<TableEntry>,
@@ -201,15 +208,18 @@ impl ConversionTabular {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TableEntry {
     pub internal: f64,
     pub physical: f64,
 }
 
 impl TableEntry {
-    #[allow(dead_code)]
-    pub fn write() {}
+    pub fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.internal, little_endian);
+        utils::write(stream, self.physical, little_endian);
+    }
     pub fn read(stream: &[u8], little_endian: bool) -> (TableEntry, usize) {
         let mut position = 0;
         let internal = utils::read(stream, little_endian, &mut position);
@@ -219,6 +229,7 @@ impl TableEntry {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Text {
     #[allow(dead_code)]
@@ -227,6 +238,7 @@ pub enum Text {
     ConversionTextRangeTable,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct ConversionTextFormula {
     pub formula: [u8; 256],
@@ -245,6 +257,7 @@ impl ConversionTextFormula {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct ConversionTextTable {
     pub table: Vec<TextTableEntry>,
@@ -267,24 +280,28 @@ impl ConversionTextTable {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TextTableEntry {
     pub internal: f64,
     pub text: [u8; 32],
 }
 
 impl TextTableEntry {
-    #[allow(dead_code)]
-    pub fn write() {}
+    pub fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.internal, little_endian);
+        utils::write(stream, self.text, little_endian);
+    }
     pub fn read(stream: &[u8], little_endian: bool) -> (TextTableEntry, usize) {
         let mut position = 0;
         let internal = utils::read(stream, little_endian, &mut position);
-        let text: [u8; 32] = stream.try_into().expect("msg");
+        let text: [u8; 32] = utils::read(stream, little_endian, &mut position);
 
         (TextTableEntry { internal, text }, position)
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct ConversionTextRangeTable {
     pub undef1: f64,
@@ -316,7 +333,8 @@ impl ConversionTextRangeTable {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TextRange {
     pub lower: f64,
     pub upper: f64,
@@ -324,9 +342,11 @@ pub struct TextRange {
 }
 
 impl TextRange {
-    #[allow(dead_code)]
-    pub fn write() {}
-    #[allow(dead_code)]
+    pub fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.lower, little_endian);
+        utils::write(stream, self.upper, little_endian);
+        utils::write(stream, self.txblock, little_endian);
+    }
     pub fn read(stream: &[u8], little_endian: bool) -> (TextRange, usize) {
         let mut position = 0;
         let lower = utils::read(stream, little_endian, &mut position);
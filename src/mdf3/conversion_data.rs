@@ -1,19 +1,91 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use crate::utils;
+
+use super::conversion::{TableEntry, TextRange, TextTableEntry};
+
+pub const LINEAR: u16 = 0;
+pub const TABULAR_INTERPOLATION: u16 = 1;
+pub const TABULAR: u16 = 2;
+pub const POLYNOMIAL: u16 = 6;
+pub const EXPONENTIAL: u16 = 7;
+pub const LOGARITHMIC: u16 = 8;
+pub const RATIONAL: u16 = 9;
+pub const TEXT_TABLE: u16 = 11;
+pub const TEXT_RANGE_TABLE: u16 = 12;
+pub const ONE_TO_ONE: u16 = 65535;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ConversionData {
-    Parameters,
-    Table,
-    #[allow(dead_code)]
-    Text,
+    Parameters(Vec<f64>),
+    Table(Vec<TableEntry>),
+    Text(Vec<TextTableEntry>),
+    TextRange(Vec<TextRange>),
+    /// Formula, date and time conversions are kept as their raw bytes.
+    Other(Vec<u8>),
 }
 
 impl ConversionData {
-    #[allow(dead_code)]
-    pub fn write() {}
-    pub fn read(_data: &[u8], _little_endian: bool, datatype: u8) -> (ConversionData, usize) {
-        if datatype == 1 {
-            (ConversionData::Parameters, 1)
-        } else {
-            (ConversionData::Table, 1)
+    pub fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        match self {
+            Self::Parameters(parameters) => {
+                for parameter in parameters {
+                    utils::write(stream, *parameter, little_endian);
+                }
+            }
+            Self::Table(table) => table.iter().for_each(|x| x.write(stream, little_endian)),
+            Self::Text(table) => table.iter().for_each(|x| x.write(stream, little_endian)),
+            Self::TextRange(table) => table.iter().for_each(|x| x.write(stream, little_endian)),
+            Self::Other(data) => stream.extend_from_slice(data),
         }
     }
+
+    /// Reads `size_info` entries for `conversion_type` from the start of
+    /// `data`, which runs to the end of the CC block.
+    pub fn read(
+        data: &[u8],
+        little_endian: bool,
+        conversion_type: u16,
+        size_info: u16,
+    ) -> (ConversionData, usize) {
+        let mut position = 0;
+        let conversion_data = match conversion_type {
+            LINEAR | POLYNOMIAL | EXPONENTIAL | LOGARITHMIC | RATIONAL => Self::Parameters(
+                (0..size_info)
+                    .map(|_| utils::read(data, little_endian, &mut position))
+                    .collect(),
+            ),
+            TABULAR_INTERPOLATION | TABULAR => Self::Table(
+                (0..size_info)
+                    .map(|_| {
+                        let (entry, pos) = TableEntry::read(&data[position..], little_endian);
+                        position += pos;
+                        entry
+                    })
+                    .collect(),
+            ),
+            TEXT_TABLE => Self::Text(
+                (0..size_info)
+                    .map(|_| {
+                        let (entry, pos) = TextTableEntry::read(&data[position..], little_endian);
+                        position += pos;
+                        entry
+                    })
+                    .collect(),
+            ),
+            TEXT_RANGE_TABLE => Self::TextRange(
+                (0..size_info)
+                    .map(|_| {
+                        let (entry, pos) = TextRange::read(&data[position..], little_endian);
+                        position += pos;
+                        entry
+                    })
+                    .collect(),
+            ),
+            _ => {
+                position = data.len();
+                Self::Other(data.to_vec())
+            }
+        };
+
+        (conversion_data, position)
+    }
 }
@@ -1,5 +1,7 @@
+use crate::storage::Stream;
 use crate::utils;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct DateStruct {
     pub ms: u16,
@@ -14,7 +16,7 @@ impl DateStruct {
     #[allow(dead_code)]
     pub fn write() {}
     #[allow(dead_code)]
-    pub fn read(stream: &[u8], little_endian: bool) -> (DateStruct, usize) {
+    pub fn read(stream: &dyn Stream, little_endian: bool) -> (DateStruct, usize) {
         let mut position = 0;
         let ms = utils::read(stream, little_endian, &mut position);
         let min = utils::read(stream, little_endian, &mut position);
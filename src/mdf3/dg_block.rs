@@ -1,8 +1,13 @@
+use std::borrow::Cow;
+
+use crate::event::Event;
+use crate::storage::Stream;
 use crate::utils;
 
 use super::{
     cg_block::Cgblock,
-    mdf3_block::{LinkedBlock, Mdf3Block},
+    mdf3_block::{LinkedBlock, Mdf3Block, WriteBlock},
+    tr_block::Trblock,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -19,47 +24,23 @@ pub struct Dgblock {
     trigger_block: u32,
     #[allow(dead_code)]
     data_block: u32,
-    #[allow(dead_code)]
     group_number: u16,
-    #[allow(dead_code)]
     id_number: u16,
     #[allow(dead_code)]
     reserved: u32,
 }
 
 impl LinkedBlock for Dgblock {
-    fn next(&self, stream: &[u8], little_endian: bool) -> Option<Self>
-    where
-        Self: std::marker::Sized,
-    {
-        if self.next == 0 {
-            None
-        } else {
-            let (_pos, block) = Self::read(stream, self.next as usize, little_endian);
-            Some(block)
-        }
-    }
-
-    fn list(&self, stream: &[u8], little_endian: bool) -> Vec<Self>
-    where
-        Self: std::marker::Sized,
-    {
-        let mut all = Vec::new();
-
-        let next = self.next(stream, little_endian);
-
-        all.push(*self);
-        match next {
-            None => {}
-            Some(block) => all.append(&mut block.list(stream, little_endian)),
-        }
+    const ID: &'static [u8; 2] = b"DG";
+    const SIZE: usize = 28;
 
-        all
+    fn link(&self) -> u64 {
+        self.next.into()
     }
 }
 
 impl Mdf3Block for Dgblock {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
 
         // Read block type to confirm
@@ -97,13 +78,72 @@ impl Mdf3Block for Dgblock {
     }
 }
 
+impl WriteBlock for Dgblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.block_type, little_endian);
+        utils::write(stream, self.block_size, little_endian);
+        utils::write(stream, self.next, little_endian);
+        utils::write(stream, self.first, little_endian);
+        utils::write(stream, self.trigger_block, little_endian);
+        utils::write(stream, self.data_block, little_endian);
+        utils::write(stream, self.group_number, little_endian);
+        utils::write(stream, self.id_number, little_endian);
+        utils::write(stream, self.reserved, little_endian);
+    }
+}
+
 impl Dgblock {
+    pub fn create(
+        next: u32,
+        first: u32,
+        trigger_block: u32,
+        data_block: u32,
+        group_number: u16,
+    ) -> Self {
+        Self {
+            block_type: *b"DG",
+            block_size: 28,
+            next,
+            first,
+            trigger_block,
+            data_block,
+            group_number,
+            id_number: 0,
+            reserved: 0,
+        }
+    }
+
+    /// The trigger events of this group, all sharing the trigger block comment.
+    pub fn events(&self, stream: &dyn Stream, little_endian: bool) -> Vec<Event> {
+        if self.trigger_block == 0 {
+            return Vec::new();
+        }
+
+        let (_pos, tr) = Trblock::read(stream, self.trigger_block as usize, little_endian);
+        let comment = tr.comment(stream, little_endian);
+        tr.events()
+            .iter()
+            .map(|x| {
+                let mut event = Event::new(x.trigger_time, "", &comment);
+                event.pre_trigger = x.pre_trigger_time;
+                event.post_trigger = x.post_trigger_time;
+                event
+            })
+            .collect()
+    }
+
     #[allow(dead_code)]
+    /// Whether the data block holds the records of a single channel group
+    /// without record IDs, the only layout the records are read from.
+    pub fn sorted(&self) -> bool {
+        self.group_number <= 1 && self.id_number == 0
+    }
+
     pub fn data_location(&self) -> usize {
         self.data_block as usize
     }
 
-    pub fn first_channel_group(&self, stream: &[u8], little_endian: bool) -> Cgblock {
+    pub fn first_channel_group(&self, stream: &dyn Stream, little_endian: bool) -> Cgblock {
         if self.first == 0 {
             panic!("Error");
         }
@@ -113,37 +153,37 @@ impl Dgblock {
         cg
     }
 
-    pub fn read_data(&self, stream: &[u8], _little_endian: bool, channel: &Cgblock) -> Vec<u8> {
+    /// The records of `channel`, borrowed from `stream` where it keeps its
+    /// bytes.
+    pub fn read_data<'a>(
+        &self,
+        stream: &'a dyn Stream,
+        _little_endian: bool,
+        channel: &Cgblock,
+    ) -> Cow<'a, [u8]> {
         let data_length = self.data_length(channel);
         let data_block = self.data_block as usize;
 
-        stream[data_block..data_block+data_length].to_vec()
+        stream.data(data_block, data_length)
     }
 
-    #[allow(dead_code)]
-    pub fn write() {
-        todo!()
-    }
-
-    pub fn read_all(stream: &[u8], little_endian: bool, position: usize) -> Vec<Self> {
-        let mut all = Vec::new();
-        let mut next_dg = position;
-
-        while next_dg != 0 {
-            let (_pos, dg_block) = Dgblock::read(stream, next_dg, little_endian);
-            next_dg = dg_block.next as usize;
-            all.push(dg_block);
-        }
-
-        all
+    pub fn read_all(
+        stream: &dyn Stream,
+        little_endian: bool,
+        position: usize,
+    ) -> Result<Vec<Self>, &'static str> {
+        Self::list(stream, position, little_endian)
     }
 
-    pub fn read_channel_groups(self, stream: &[u8], little_endian: bool) -> Vec<Cgblock> {
-        let first_group = self.first_channel_group(stream, little_endian);
-        first_group.list(stream, little_endian)
+    pub fn read_channel_groups(
+        self,
+        stream: &dyn Stream,
+        little_endian: bool,
+    ) -> Result<Vec<Cgblock>, &'static str> {
+        Cgblock::list(stream, self.first as usize, little_endian)
     }
 
-    fn data_length(self, channel: &Cgblock) -> usize{
+    fn data_length(self, channel: &Cgblock) -> usize {
         let record_number = channel.record_number();
         let record_size = channel.record_size();
         record_number * record_size
@@ -154,15 +194,15 @@ impl Dgblock {
 mod tests {
     use super::*;
 
+    static RAW: [u8; 28] = [
+        0x44, 0x47, 0x1C, 0x00, 0xF4, 0xDF, 0x10, 0x00, 0x99, 0xE4, 0x10, 0x00, 0x2B, 0xE5, 0x10,
+        0x00, 0xDC, 0x03, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
     #[test]
     fn read() {
-        let dg_data = [
-            0x44, 0x47, 0x1C, 0x00, 0xF4, 0xDF, 0x10, 0x00, 0x99, 0xE4, 0x10, 0x00, 0x2B, 0xE5,
-            0x10, 0x00, 0xDC, 0x03, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-        ];
-
         let position = 0;
-        let (position, dg_block) = Dgblock::read(&dg_data, position, true);
+        let (position, dg_block) = Dgblock::read(&RAW, position, true);
 
         assert_eq!(position, 28);
         assert_eq!(dg_block.next, 1105908);
@@ -175,5 +215,32 @@ mod tests {
     }
 
     #[test]
-    fn write() {}
+    fn write() {
+        let (position, dg_block) = Dgblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        dg_block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..position], &stream[..]);
+    }
+
+    #[test]
+    fn list() {
+        assert_eq!(
+            Dgblock::list(&[RAW, RAW].concat(), 28, true).err(),
+            Some("Link past the end of the file")
+        );
+
+        let mut stream = [RAW, RAW, RAW].concat();
+        stream[32..36].copy_from_slice(&56_u32.to_le_bytes());
+        stream[60..64].copy_from_slice(&0_u32.to_le_bytes());
+        assert_eq!(Dgblock::list(&stream, 28, true).unwrap().len(), 2);
+
+        // Back to the first block
+        stream[60..64].copy_from_slice(&28_u32.to_le_bytes());
+        assert_eq!(
+            Dgblock::list(&stream, 28, true).err(),
+            Some("Linked blocks form a cycle")
+        );
+    }
 }
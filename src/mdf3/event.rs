@@ -1,6 +1,7 @@
+use crate::storage::Stream;
 use crate::utils;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Event {
     pub trigger_time: f64,
     pub pre_trigger_time: f64,
@@ -8,9 +9,12 @@ pub struct Event {
 }
 
 impl Event {
-    #[allow(dead_code)]
-    pub fn write() {}
-    pub fn read(stream: &[u8], position: usize, little_endian: bool) -> (Event, usize) {
+    pub fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.trigger_time, little_endian);
+        utils::write(stream, self.pre_trigger_time, little_endian);
+        utils::write(stream, self.post_trigger_time, little_endian);
+    }
+    pub fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (Event, usize) {
         let mut pos = position;
         let trigger_time = utils::read(stream, little_endian, &mut pos);
         let pre_trigger_time = utils::read(stream, little_endian, &mut pos);
@@ -21,17 +25,37 @@ impl Event {
                 pre_trigger_time,
                 post_trigger_time,
             },
-            position,
+            pos,
         )
     }
 }
 
 #[cfg(test)]
 mod event_test {
+    use super::Event;
+
+    static RAW: [u8; 24] = [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xF8, 0x3F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xE0,
+        0x3F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+    ];
 
     #[test]
-    fn read() {}
+    fn read() {
+        let (event, pos) = Event::read(&RAW, 0, true);
+
+        assert_eq!(pos, 24);
+        assert_eq!(event.trigger_time, 1.5);
+        assert_eq!(event.pre_trigger_time, 0.5);
+        assert_eq!(event.post_trigger_time, 2.0);
+    }
 
     #[test]
-    fn write() {}
+    fn write() {
+        let (event, _pos) = Event::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        event.write(&mut stream, true);
+
+        assert_eq!(&RAW[..], &stream[..]);
+    }
 }
@@ -1,6 +1,14 @@
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+
+use crate::storage::Stream;
 use crate::utils;
 
-use super::{dg_block::Dgblock, mdf3_block::Mdf3Block, tx_block::Txblock};
+use super::{
+    cn_block::fixed_text,
+    dg_block::Dgblock,
+    mdf3_block::{Mdf3Block, WriteBlock},
+    tx_block::Txblock,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Hdblock {
@@ -39,7 +47,7 @@ pub struct Hdblock {
 }
 
 impl Mdf3Block for Hdblock {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let block_type: [u8; 2] = utils::read(stream, little_endian, &mut pos);
 
@@ -88,17 +96,99 @@ impl Mdf3Block for Hdblock {
     }
 }
 
+impl WriteBlock for Hdblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.block_type, little_endian);
+        utils::write(stream, self.block_size, little_endian);
+        utils::write(stream, self.data_group_block, little_endian);
+        utils::write(stream, self.file_comment, little_endian);
+        utils::write(stream, self.program_block, little_endian);
+        utils::write(stream, self.data_group_number, little_endian);
+        utils::write(stream, self.date, little_endian);
+        utils::write(stream, self.time, little_endian);
+        utils::write(stream, self.author, little_endian);
+        utils::write(stream, self.department, little_endian);
+        utils::write(stream, self.project, little_endian);
+        utils::write(stream, self.subject, little_endian);
+        utils::write(stream, self.timestamp, little_endian);
+        utils::write(stream, self.utc_time_offset, little_endian);
+        utils::write(stream, self.time_quality, little_endian);
+        utils::write(stream, self.timer_id, little_endian);
+    }
+}
+
 impl Hdblock {
+    pub const BYTE_LEN: usize = 208;
+
+    pub fn create(
+        data_group_block: u32,
+        file_comment: u32,
+        data_group_number: u16,
+        start_time: DateTime<Utc>,
+    ) -> Self {
+        let mut date = [0_u8; 10];
+        date.copy_from_slice(start_time.format("%d:%m:%Y").to_string().as_bytes());
+        let mut time = [0_u8; 8];
+        time.copy_from_slice(start_time.format("%H:%M:%S").to_string().as_bytes());
+        let mut timer_id = [0_u8; 32];
+        timer_id[..23].copy_from_slice(b"Local PC Reference Time");
+
+        Self {
+            position: 0,
+            block_type: *b"HD",
+            block_size: Self::BYTE_LEN as u16,
+            data_group_block,
+            file_comment,
+            program_block: 0,
+            data_group_number,
+            date,
+            time,
+            author: [0_u8; 32],
+            department: [0_u8; 32],
+            project: [0_u8; 32],
+            subject: [0_u8; 32],
+            timestamp: start_time.timestamp() as u64 * 1_000_000_000
+                + start_time.timestamp_subsec_nanos() as u64,
+            utc_time_offset: 0,
+            time_quality: 0,
+            timer_id,
+        }
+    }
+
+    /// The recording start from the timestamp, or from the date and time
+    /// fields for files older than 3.20. Both are local time shifted by the
+    /// UTC offset.
+    pub fn start_time(&self) -> DateTime<Utc> {
+        let local = if self.timestamp != 0 {
+            Utc.timestamp_nanos(self.timestamp as i64)
+        } else {
+            let text = format!("{} {}", fixed_text(&self.date), fixed_text(&self.time));
+            match NaiveDateTime::parse_from_str(&text, "%d:%m:%Y %H:%M:%S") {
+                Ok(time) => Utc.from_utc_datetime(&time),
+                Err(_) => Utc.timestamp_nanos(0),
+            }
+        };
+
+        local - Duration::hours(self.utc_time_offset as i64)
+    }
+
     pub fn data_group(&self) -> usize {
         self.data_group_block as usize
     }
-    pub fn comment(&self, stream: &[u8], little_endian: bool) -> String {
+    pub fn comment(&self, stream: &dyn Stream, little_endian: bool) -> String {
+        if self.file_comment == 0 {
+            return String::new();
+        }
+
         let (_pos, tx) = Txblock::read(stream, self.file_comment as usize, little_endian);
-        tx.name()
+        let text = tx.text();
+        if text.starts_with('<') {
+            utils::extract_tx(text.as_bytes())
+        } else {
+            text
+        }
     }
-    #[allow(dead_code)]
-    pub fn write() {}
-    pub fn first_data_group(&self, stream: &[u8], little_endian: bool) -> Dgblock {
+    pub fn first_data_group(&self, stream: &dyn Stream, little_endian: bool) -> Dgblock {
         if self.data_group_block == 0 {
             panic!("No data group found!");
         }
@@ -116,44 +206,42 @@ mod tests {
 
     use super::*;
 
+    static RAW: [u8; 444] = [
+        0x48, 0x44, 0xD0, 0x00, 0xD8, 0xDF, 0x10, 0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x06, 0x00, 0x32, 0x32, 0x3A, 0x31, 0x31, 0x3A, 0x32, 0x30, 0x31, 0x38, 0x31, 0x34,
+        0x3A, 0x32, 0x36, 0x3A, 0x33, 0x35, 0x4A, 0x61, 0x63, 0x6B, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x29, 0x46, 0xF9, 0x75, 0x78, 0x69, 0x15, 0x00, 0x00, 0x00, 0x00, 0x4C, 0x6F, 0x63, 0x61,
+        0x6C, 0x20, 0x50, 0x43, 0x20, 0x52, 0x65, 0x66, 0x65, 0x72, 0x65, 0x6E, 0x63, 0x65, 0x20,
+        0x54, 0x69, 0x6D, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x54, 0x58,
+        0xCC, 0x02, 0x3C, 0x48, 0x44, 0x63, 0x6F, 0x6D, 0x6D, 0x65, 0x6E, 0x74, 0x20, 0x78, 0x6D,
+        0x6C, 0x6E, 0x73, 0x3D, 0x22, 0x68, 0x74, 0x74, 0x70, 0x3A, 0x2F, 0x2F, 0x77, 0x77, 0x77,
+        0x2E, 0x61, 0x73, 0x61, 0x6D, 0x2E, 0x6E, 0x65, 0x74, 0x2F, 0x6D, 0x64, 0x66, 0x2F, 0x76,
+        0x34, 0x22, 0x3E, 0x3C, 0x54, 0x58, 0x3E, 0x44, 0x61, 0x74, 0x65, 0x3A, 0x20, 0x32, 0x32,
+        0x2E, 0x31, 0x31, 0x2E, 0x32, 0x30, 0x31, 0x38, 0x0D, 0x0A, 0x54, 0x69, 0x6D, 0x65, 0x3A,
+        0x20, 0x31, 0x35, 0x3A, 0x32, 0x37, 0x0D, 0x0A, 0x52, 0x65, 0x63, 0x6F, 0x72, 0x64, 0x69,
+        0x6E, 0x67, 0x20, 0x44, 0x75, 0x72, 0x61, 0x74, 0x69, 0x6F, 0x6E, 0x3A, 0x20, 0x30, 0x30,
+        0x3A, 0x30, 0x30, 0x3A, 0x31, 0x32, 0x0D, 0x0A, 0xA7, 0x40, 0x0D, 0x0A, 0x44, 0x61, 0x74,
+        0x61, 0x62, 0x61, 0x73, 0x65, 0x3A, 0x20, 0x54, 0x65, 0x73, 0x74, 0x0D, 0x0A, 0x45, 0x78,
+        0x70, 0x65, 0x72, 0x69, 0x6D, 0x65, 0x6E, 0x74, 0x3A, 0x20, 0x45, 0x78, 0x70, 0x65, 0x72,
+        0x69, 0x6D, 0x65, 0x6E, 0x74, 0x0D, 0x0A, 0x57, 0x6F, 0x72, 0x6B, 0x73, 0x70, 0x61, 0x63,
+        0x65, 0x3A, 0x20, 0x57, 0x6F, 0x72, 0x6B, 0x73, 0x70, 0x61, 0x63, 0x65, 0x0D, 0x0A, 0x44,
+        0x65, 0x76, 0x69, 0x63, 0x65, 0x73, 0x3A, 0x20, 0x45, 0x54, 0x4B, 0x20, 0x74, 0x65, 0x73,
+        0x74, 0x20, 0x64, 0x65, 0x76, 0x69, 0x63, 0x65, 0x3A, 0x31, 0x0D, 0x0A, 0x50, 0x72, 0x6F,
+        0x67, 0x72, 0x61, 0x6D, 0x20, 0x44, 0x65, 0x73, 0x63, 0x72, 0x69, 0x70, 0x74, 0x69, 0x6F,
+        0x6E, 0x3A, 0x20, 0x41, 0x53, 0x41, 0x50, 0x32, 0x5F,
+    ];
+
     #[test]
     fn read() {
-        let hd_data = [
-            0x48, 0x44, 0xD0, 0x00, 0xD8, 0xDF, 0x10, 0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x06, 0x00, 0x32, 0x32, 0x3A, 0x31, 0x31, 0x3A, 0x32, 0x30, 0x31, 0x38,
-            0x31, 0x34, 0x3A, 0x32, 0x36, 0x3A, 0x33, 0x35, 0x4A, 0x61, 0x63, 0x6B, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x29, 0x46, 0xF9,
-            0x75, 0x78, 0x69, 0x15, 0x00, 0x00, 0x00, 0x00, 0x4C, 0x6F, 0x63, 0x61, 0x6C, 0x20,
-            0x50, 0x43, 0x20, 0x52, 0x65, 0x66, 0x65, 0x72, 0x65, 0x6E, 0x63, 0x65, 0x20, 0x54,
-            0x69, 0x6D, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x54, 0x58,
-            0xCC, 0x02, 0x3C, 0x48, 0x44, 0x63, 0x6F, 0x6D, 0x6D, 0x65, 0x6E, 0x74, 0x20, 0x78,
-            0x6D, 0x6C, 0x6E, 0x73, 0x3D, 0x22, 0x68, 0x74, 0x74, 0x70, 0x3A, 0x2F, 0x2F, 0x77,
-            0x77, 0x77, 0x2E, 0x61, 0x73, 0x61, 0x6D, 0x2E, 0x6E, 0x65, 0x74, 0x2F, 0x6D, 0x64,
-            0x66, 0x2F, 0x76, 0x34, 0x22, 0x3E, 0x3C, 0x54, 0x58, 0x3E, 0x44, 0x61, 0x74, 0x65,
-            0x3A, 0x20, 0x32, 0x32, 0x2E, 0x31, 0x31, 0x2E, 0x32, 0x30, 0x31, 0x38, 0x0D, 0x0A,
-            0x54, 0x69, 0x6D, 0x65, 0x3A, 0x20, 0x31, 0x35, 0x3A, 0x32, 0x37, 0x0D, 0x0A, 0x52,
-            0x65, 0x63, 0x6F, 0x72, 0x64, 0x69, 0x6E, 0x67, 0x20, 0x44, 0x75, 0x72, 0x61, 0x74,
-            0x69, 0x6F, 0x6E, 0x3A, 0x20, 0x30, 0x30, 0x3A, 0x30, 0x30, 0x3A, 0x31, 0x32, 0x0D,
-            0x0A, 0xA7, 0x40, 0x0D, 0x0A, 0x44, 0x61, 0x74, 0x61, 0x62, 0x61, 0x73, 0x65, 0x3A,
-            0x20, 0x54, 0x65, 0x73, 0x74, 0x0D, 0x0A, 0x45, 0x78, 0x70, 0x65, 0x72, 0x69, 0x6D,
-            0x65, 0x6E, 0x74, 0x3A, 0x20, 0x45, 0x78, 0x70, 0x65, 0x72, 0x69, 0x6D, 0x65, 0x6E,
-            0x74, 0x0D, 0x0A, 0x57, 0x6F, 0x72, 0x6B, 0x73, 0x70, 0x61, 0x63, 0x65, 0x3A, 0x20,
-            0x57, 0x6F, 0x72, 0x6B, 0x73, 0x70, 0x61, 0x63, 0x65, 0x0D, 0x0A, 0x44, 0x65, 0x76,
-            0x69, 0x63, 0x65, 0x73, 0x3A, 0x20, 0x45, 0x54, 0x4B, 0x20, 0x74, 0x65, 0x73, 0x74,
-            0x20, 0x64, 0x65, 0x76, 0x69, 0x63, 0x65, 0x3A, 0x31, 0x0D, 0x0A, 0x50, 0x72, 0x6F,
-            0x67, 0x72, 0x61, 0x6D, 0x20, 0x44, 0x65, 0x73, 0x63, 0x72, 0x69, 0x70, 0x74, 0x69,
-            0x6F, 0x6E, 0x3A, 0x20, 0x41, 0x53, 0x41, 0x50, 0x32, 0x5F,
-        ];
-
-        let (position, hd_block) = Hdblock::read(&hd_data, 0, true);
+        let (position, hd_block) = Hdblock::read(&RAW, 0, true);
 
         println!("Length {}", position);
         assert_eq!(position, 208);
@@ -217,5 +305,12 @@ mod tests {
     }
 
     #[test]
-    fn write() {}
+    fn write() {
+        let (position, hd_block) = Hdblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        hd_block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..position], &stream[..]);
+    }
 }
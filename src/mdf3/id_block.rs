@@ -1,5 +1,8 @@
+use crate::storage::Stream;
 use crate::utils;
 
+use super::mdf3_block::WriteBlock;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Idblock {
     file_id: [u8; 8],
@@ -14,8 +17,22 @@ pub struct Idblock {
 }
 
 impl Idblock {
-    #[allow(dead_code)]
-    pub fn write() {}
+    pub fn create(program: &str, little_endian: bool) -> Self {
+        let mut program_id = [0_u8; 8];
+        for (byte, c) in program_id.iter_mut().zip(program.bytes()) {
+            *byte = c;
+        }
+
+        Self::new(
+            *b"MDF     ",
+            *b"3.30    ",
+            program_id,
+            if little_endian { 0 } else { 1 },
+            0,
+            330,
+            0,
+        )
+    }
     #[allow(dead_code)]
     pub fn new(
         file_id: [u8; 8],
@@ -38,9 +55,9 @@ impl Idblock {
             reserved2: [0_u8; 30],
         }
     }
-    pub fn read(stream: &[u8]) -> (Idblock, usize, bool) {
+    pub fn read(stream: &dyn Stream) -> (Idblock, usize, bool) {
         let mut position = 0;
-        let file_id: [u8; 8] = stream[position..position + 8].try_into().expect("msg");
+        let file_id: [u8; 8] = stream.bytes(position, 8)[..].try_into().expect("msg");
 
         if !utils::eq(
             &file_id[..],
@@ -51,10 +68,10 @@ impl Idblock {
 
         position += file_id.len();
 
-        let format_id: [u8; 8] = stream[position..position + 8].try_into().expect("msg");
+        let format_id: [u8; 8] = stream.bytes(position, 8)[..].try_into().expect("msg");
         position += format_id.len();
 
-        let program_id: [u8; 8] = stream[position..position + 8].try_into().expect("msg");
+        let program_id: [u8; 8] = stream.bytes(position, 8)[..].try_into().expect("msg");
         position += program_id.len();
 
         let default_byte_order = utils::read(stream, true, &mut position);
@@ -67,9 +84,9 @@ impl Idblock {
 
         let code_page_number = utils::read(stream, little_endian, &mut position);
 
-        let reserved1: [u8; 2] = [stream[position], stream[position + 1]];
+        let reserved1: [u8; 2] = stream.bytes(position, 2)[..].try_into().expect("msg");
         position += reserved1.len();
-        let reserved2: [u8; 30] = stream[position..position + 30].try_into().expect("msg");
+        let reserved2: [u8; 30] = stream.bytes(position, 30)[..].try_into().expect("msg");
         position += reserved2.len();
 
         (
@@ -90,21 +107,35 @@ impl Idblock {
     }
 }
 
+impl WriteBlock for Idblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.file_id, little_endian);
+        utils::write(stream, self.format_id, little_endian);
+        utils::write(stream, self.program_id, little_endian);
+        utils::write(stream, self.default_byte_order, little_endian);
+        utils::write(stream, self.default_float_format, little_endian);
+        utils::write(stream, self.version_number, little_endian);
+        utils::write(stream, self.code_page_number, little_endian);
+        utils::write(stream, self.reserved1, little_endian);
+        utils::write(stream, self.reserved2, little_endian);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    static RAW: [u8; 64] = [
+        0x4D, 0x44, 0x46, 0x20, 0x20, 0x20, 0x20, 0x20, 0x33, 0x2E, 0x33, 0x30, 0x00, 0x00, 0x00,
+        0x00, 0x61, 0x6D, 0x64, 0x66, 0x36, 0x34, 0x34, 0x00, 0x00, 0x00, 0x00, 0x00, 0x4A, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+
     #[test]
     fn read() {
-        let id_data = [
-            0x4D, 0x44, 0x46, 0x20, 0x20, 0x20, 0x20, 0x20, 0x33, 0x2E, 0x33, 0x30, 0x00, 0x00,
-            0x00, 0x00, 0x61, 0x6D, 0x64, 0x66, 0x36, 0x34, 0x34, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x4A, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-        ];
-
-        let (id_block, position, endian) = Idblock::read(&id_data);
+        let (id_block, position, endian) = Idblock::read(&RAW);
 
         assert_eq!(position, 64);
         assert!(endian);
@@ -130,5 +161,25 @@ mod tests {
     }
 
     #[test]
-    fn write() {}
+    fn write() {
+        let (id_block, position, little_endian) = Idblock::read(&RAW);
+
+        let mut stream = Vec::new();
+        id_block.write(&mut stream, little_endian);
+
+        assert_eq!(&RAW[..position], &stream[..]);
+    }
+
+    #[test]
+    fn byte_order() {
+        let mut stream = Vec::new();
+        Idblock::create("rsmdf", false).write(&mut stream, false);
+
+        let (id_block, position, little_endian) = Idblock::read(&stream);
+
+        assert_eq!(position, 64);
+        assert!(!little_endian);
+        assert_eq!(id_block.version_number, 330);
+        assert_eq!(&id_block.program_id, b"rsmdf\0\0\0");
+    }
 }
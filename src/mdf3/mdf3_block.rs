@@ -1,12 +1,51 @@
+use crate::storage::Stream;
+use crate::utils;
+
 pub trait Mdf3Block {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self);
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self);
+}
+
+pub trait LinkedBlock: Mdf3Block + Sized {
+    const ID: &'static [u8; 2];
+    /// Bytes the reader takes, older versions store some blocks shorter.
+    const SIZE: usize;
+
+    /// Position of the next block, 0 for the last one.
+    fn link(&self) -> u64;
+
+    /// Reads the block at `position`, an error unless a whole block of this
+    /// type lies there.
+    fn checked(
+        stream: &dyn Stream,
+        position: usize,
+        little_endian: bool,
+    ) -> Result<Self, &'static str> {
+        if position.saturating_add(4) > stream.len() {
+            return Err("Link past the end of the file");
+        }
+        if *stream.bytes(position, 2) != *Self::ID {
+            return Err("Link to a block of the wrong type");
+        }
+        let size: u16 = utils::read(stream, little_endian, &mut (position + 2));
+        if position + Self::SIZE.max(size.into()) > stream.len() {
+            return Err("Block past the end of the file");
+        }
+        Ok(Self::read(stream, position, little_endian).1)
+    }
+
+    /// The block at `position` and all following it, an error if the links
+    /// or blocks are corrupt.
+    fn list(
+        stream: &dyn Stream,
+        position: usize,
+        little_endian: bool,
+    ) -> Result<Vec<Self>, &'static str> {
+        utils::linked_list(stream, position, Self::link, |position| {
+            Self::checked(stream, position, little_endian)
+        })
+    }
 }
 
-pub trait LinkedBlock {
-    fn next(&self, stream: &[u8], little_endian: bool) -> Option<Self>
-    where
-        Self: std::marker::Sized;
-    fn list(&self, stream: &[u8], little_endian: bool) -> Vec<Self>
-    where
-        Self: std::marker::Sized;
+pub trait WriteBlock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool);
 }
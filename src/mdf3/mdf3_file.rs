@@ -1,15 +1,25 @@
-use crate::mdf::{self, MdfChannel};
+use crate::attachment::Attachment;
+use crate::event::Event;
+use crate::export::{self, ExportOptions};
+use crate::mdf::{
+    self, ChannelInfo, ChannelSelector, MDFFile, MasterType, MdfChannel, Measurement, RecordChunks,
+};
 use crate::mdf3::cg_block::Cgblock;
 use crate::mdf3::cn_block::Cnblock;
-use crate::record::Record;
-use crate::signal;
-use std::fs::File;
-use std::io::prelude::*;
+use crate::record::{Record, RecordView};
+use crate::signal::{self, Interpolation};
+use crate::storage::{Storage, Stream};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::io;
+use std::ops::Range;
 
 use super::dg_block::Dgblock;
 use super::hd_block::Hdblock;
 use super::id_block::Idblock;
-use super::mdf3_block::{LinkedBlock, Mdf3Block};
+use super::mdf3_block::Mdf3Block;
 
 #[derive(Debug, Clone)]
 pub struct MDF3 {
@@ -23,31 +33,134 @@ pub struct MDF3 {
     pub channels: Vec<Cnblock>,
     pub channel_groups: Vec<Cgblock>,
     pub little_endian: bool,
-    pub file: Vec<u8>,
+    pub file: Storage,
+    /// The channel groups of each data group.
+    groups: Vec<Vec<ChannelGroup>>,
 }
 
-impl mdf::MDFFile for MDF3 {
-    fn channels(&self) -> Vec<MdfChannel> {
-        let mut mdf_channels = Vec::new();
+/// A channel group and its channels, read once when the file is opened.
+#[derive(Debug, Clone)]
+struct ChannelGroup {
+    block: Cgblock,
+    channels: Vec<Cnblock>,
+}
+
+impl MDF3 {
+    /// Writes `measurement` as a file of the same version.
+    pub fn rebuild(&self, measurement: &Measurement) -> Self {
+        Self::from_bytes(measurement.mdf3(self.little_endian))
+    }
+
+    pub fn from_bytes(stream: Vec<u8>) -> Self {
+        Self::from_storage(Storage::Memory(stream)).unwrap_or_else(|x| panic!("{x}"))
+    }
+
+    /// Reads the metadata blocks of the file, data blocks are read when the
+    /// samples are. An error if the blocks are linked in a corrupt way.
+    pub fn from_storage(stream: Storage) -> Result<Self, &'static str> {
+        if stream.len() < 64 + 208 || *stream.bytes(64, 2) != *b"HD" {
+            return Err("No header block found");
+        }
+        let (id, pos, little_endian) = Idblock::read(&stream);
+        let (_pos, header) = Hdblock::read(&stream, pos, little_endian);
+        // let (_pos, comment) = Txblock::read(&stream, header.file_comment as usize, little_endian);
+        let comment = header.comment(&stream, little_endian);
+        let data_groups = Dgblock::read_all(&stream, little_endian, header.data_group())?;
+
+        let mut groups = Vec::new();
+        for dg in &data_groups {
+            let mut channel_groups = Vec::new();
+            for block in dg.read_channel_groups(&stream, little_endian)? {
+                channel_groups.push(ChannelGroup {
+                    block,
+                    channels: block.channels(&stream, little_endian)?,
+                });
+            }
+            groups.push(channel_groups);
+        }
 
-        let (_id_block, position, little_endian) = Idblock::read(&self.file);
-        let (_pos, hd_block) = Hdblock::read(&self.file, position, little_endian);
+        let mut mdf = MDF3 {
+            id,
+            header,
+            comment,
+            data_groups,
+            channels: Vec::new(),
+            channel_groups: Vec::new(),
+            little_endian,
+            file: stream,
+            groups,
+        };
 
-        let first_data_group = hd_block.first_data_group(&self.file, little_endian);
-        let data_groups = first_data_group.list(&self.file, little_endian);
+        mdf.read_all();
+
+        Ok(mdf)
+    }
 
-        for (dg_no, dg) in data_groups.iter().enumerate() {
-            let first_cg = dg.first_channel_group(&self.file, little_endian);
-            let channel_groups = first_cg.list(&self.file, little_endian);
+    fn group(&self, datagroup: usize, channel_grp: usize) -> &ChannelGroup {
+        &self.groups[datagroup][channel_grp]
+    }
 
-            for (cg_no, cg) in channel_groups.iter().enumerate() {
-                let first_cn = cg.first_channel(&self.file, little_endian);
-                let channels = first_cn.list(&self.file, little_endian);
+    /// The bytes of `records` of a channel group, which follow each other
+    /// from the start of the data block.
+    fn record_range(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        records: Range<usize>,
+    ) -> Result<Cow<'_, [u8]>, &'static str> {
+        self.check_sorted(datagroup)?;
+        let record_size = self.group(datagroup, channel_grp).block.record_size();
+        Ok(self.file.data(
+            self.data_groups[datagroup].data_location() + records.start * record_size,
+            records.len() * record_size,
+        ))
+    }
 
-                for (cn_no, cn) in channels.iter().enumerate() {
-                    let name = cn.name(&self.file, little_endian);
+    /// An error unless the records of the data group can be found by their
+    /// position, see [`Dgblock::sorted`].
+    fn check_sorted(&self, datagroup: usize) -> Result<(), &'static str> {
+        match self.data_groups[datagroup].sorted() && self.groups[datagroup].len() <= 1 {
+            true => Ok(()),
+            false => Err("Unsorted data groups are not supported"),
+        }
+    }
+
+    pub fn storage(&self) -> &Storage {
+        &self.file
+    }
+
+    /// A signal of `cn` with its metadata and conversion.
+    fn signal(&self, cn: &Cnblock, timestamps: Vec<f64>, samples: Vec<Record>) -> signal::Signal {
+        let conversion = cn.conversion(&self.file, self.little_endian);
+        let mut signal = signal::Signal::new(
+            timestamps,
+            samples,
+            cn.unit(&self.file, self.little_endian),
+            cn.name(&self.file, self.little_endian),
+            cn.comment(&self.file, self.little_endian),
+            conversion.is_some(),
+        );
+
+        if let Some(source) = cn.source(&self.file, self.little_endian) {
+            signal = signal.with_source(source);
+        }
+
+        match conversion {
+            Some(conversion) => signal.with_conversion(conversion),
+            None => signal,
+        }
+    }
+}
+
+impl mdf::MDFFile for MDF3 {
+    fn channels(&self) -> Vec<MdfChannel> {
+        let mut mdf_channels = Vec::new();
+
+        for (dg_no, dg) in self.groups.iter().enumerate() {
+            for (cg_no, cg) in dg.iter().enumerate() {
+                for (cn_no, cn) in cg.channels.iter().enumerate() {
                     mdf_channels.push(mdf::MdfChannel {
-                        name,
+                        name: cn.name(&self.file, self.little_endian),
                         data_group: dg_no,
                         channel_group: cg_no,
                         channel: cn_no,
@@ -61,12 +174,15 @@ impl mdf::MDFFile for MDF3 {
 
     fn find_time_channel(
         &self,
-        _datagroup: usize,
+        datagroup: usize,
         channel_grp: usize,
     ) -> Result<usize, &'static str> {
-        let channel_group =
-            self.channel_groups[channel_grp].channels(&self.file, self.little_endian);
-        for (i, channel) in channel_group.iter().enumerate() {
+        for (i, channel) in self
+            .group(datagroup, channel_grp)
+            .channels
+            .iter()
+            .enumerate()
+        {
             if channel.channel_type().is_time() {
                 return Ok(i);
             }
@@ -75,50 +191,23 @@ impl mdf::MDFFile for MDF3 {
         Err("No time series found for the channel selected")
     }
     fn read_channel(&self, datagroup: usize, channel_grp: usize, channel: usize) -> Vec<Record> {
-        let channel_group = &self.channel_groups[channel_grp];
-        // let data_length = channel_group.data_length();
-        let channels = channel_group
-            .first_channel(&self.file, self.little_endian)
-            .list(&self.file, self.little_endian);
-        let dg = &self.data_groups[datagroup];
-        let cn = &channels[channel];
-
-        let data = dg.read_data(&self.file, self.little_endian, channel_group);
-        // &self.file[dg.data_location() as usize..(dg.data_location() as usize + data_length)];
+        let cn = &self.group(datagroup, channel_grp).channels[channel];
 
-        println!("Record Number: {}", channel_group.record_number());
-
-        let mut data_blocks: Vec<&[u8]> = vec![&[0_u8]; channel_group.record_number()];
-        // let mut data_blocks = Vec::new();
-
-        println!("Vec len: {}", data_blocks.len());
-
-        for (i, db) in data_blocks.iter_mut().enumerate() {
-            *db = &data[(i * channel_group.record_size())..((i + 1) * channel_group.record_size())];
-        }
-
-        let byte_offset = cn.byte_offset();
-
-        let mut records = Vec::new();
-        let mut pos = 0;
-        for _i in 0..channel_group.record_number() {
-            records.push(&data[pos..pos + channel_group.record_size()]);
-            pos += channel_group.record_size();
-        }
-
-        let mut raw_data = Vec::new();
-        let end = byte_offset + cn.data_type_len();
-
-        for rec in records {
-            raw_data.push(&rec[byte_offset..end]);
-        }
+        self.records(datagroup, channel_grp)
+            .iter()
+            .map(|record| cn.decode(record))
+            .collect()
+    }
 
-        let mut extracted_data = Vec::new();
-        for raw in raw_data {
-            extracted_data.push(Record::new(raw, cn.data_type()));
-        }
+    fn records(&self, datagroup: usize, channel_grp: usize) -> RecordView<'_> {
+        let dg = &self.data_groups[datagroup];
+        let channel_group = &self.group(datagroup, channel_grp).block;
 
-        extracted_data
+        RecordView::create(
+            dg.read_data(&self.file, self.little_endian, channel_group),
+            channel_group.record_size(),
+            channel_group.record_number(),
+        )
     }
 
     // fn read_channel(&self, datagroup: usize, channel_grp: usize, channel: usize) -> Vec<Record> {
@@ -181,100 +270,82 @@ impl mdf::MDFFile for MDF3 {
     // }
 
     fn new(filepath: &str) -> Self {
-        let mut file = File::open(filepath).expect("Could not read file");
-        let mut stream = Vec::new();
-        let _ = file.read_to_end(&mut stream);
-        let (id, pos, little_endian) = Idblock::read(&stream);
-        let (_pos, header) = Hdblock::read(&stream, pos, little_endian);
-        // let (_pos, comment) = Txblock::read(&stream, header.file_comment as usize, little_endian);
-        let comment = header.comment(&stream, little_endian);
-        let mut mdf = MDF3 {
-            id,
-            header,
-            comment,
-            data_groups: Dgblock::read_all(&stream, little_endian, header.data_group()),
-            channels: Vec::new(),
-            channel_groups: Vec::new(),
-            little_endian,
-            file: stream,
-        };
-
-        mdf.read_all();
-
-        mdf
+        Self::from_storage(Storage::new(filepath).expect("Could not read file"))
+            .unwrap_or_else(|x| panic!("{x}"))
     }
 
-    fn read_all(&mut self) {
-        let mut channel_groups = Vec::new();
-        for group in &self.data_groups {
-            channel_groups.append(&mut group.read_channel_groups(&self.file, self.little_endian));
-        }
-
-        let mut channels = Vec::new();
-        for grp in &channel_groups {
-            channels.append(&mut grp.channels(&self.file, self.little_endian));
-        }
-
-        self.channel_groups = channel_groups;
-        self.channels = channels;
+    fn comment(&self) -> String {
+        self.comment.clone()
     }
 
-    fn list_data_groups(&mut self) {
-        let (_id_block, position, little_endian) = Idblock::read(&self.file);
-        let (_pos, hd_block) = Hdblock::read(&self.file, position, little_endian);
-        //position += pos;
+    fn start_time(&self) -> DateTime<Utc> {
+        self.header.start_time()
+    }
 
-        let dg = Dgblock::read_all(&self.file, little_endian, hd_block.data_group());
-        self.data_groups = dg;
+    fn events(&self) -> Vec<Event> {
+        self.data_groups
+            .iter()
+            .flat_map(|dg| dg.events(&self.file, self.little_endian))
+            .collect()
     }
 
-    fn list_channels(&self) {
-        let (_id_block, position, little_endian) = Idblock::read(&self.file);
-        let (_pos, hd_block) = Hdblock::read(&self.file, position, little_endian);
+    fn attachments(&self) -> Vec<Attachment> {
+        Vec::new()
+    }
 
-        let first_data_group = hd_block.first_data_group(&self.file, little_endian);
-        let data_groups = first_data_group.list(&self.file, little_endian);
+    fn group_comment(&self, datagroup: usize, channel_grp: usize) -> String {
+        self.group(datagroup, channel_grp)
+            .block
+            .comment(&self.file, self.little_endian)
+    }
 
-        for dg in data_groups {
-            let first_channel_group = dg.first_channel_group(&self.file, little_endian);
-            let channel_groups = first_channel_group.list(&self.file, little_endian);
+    fn record_count(&self, datagroup: usize, channel_grp: usize) -> usize {
+        self.group(datagroup, channel_grp).block.record_number()
+    }
 
-            for cg in channel_groups {
-                // let first_channel = cg.first_channel(&self.file, little_endian);
-                // let channels = first_channel.list(&self.file, little_endian);
+    fn channel_info(&self, datagroup: usize, channel_grp: usize, channel: usize) -> ChannelInfo {
+        let channels = &self.group(datagroup, channel_grp).channels;
+        let cn = &channels[channel];
 
-                println!("Channel Group: {}", cg.comment(&self.file, little_endian));
-            }
+        ChannelInfo {
+            display_name: cn.display_name(&self.file, self.little_endian),
+            unit: cn.unit(&self.file, self.little_endian),
+            comment: cn.comment(&self.file, self.little_endian),
+            source: cn.source(&self.file, self.little_endian),
+            data_type: cn.data_type().data_type,
+            master: match channels.iter().any(|x| x.channel_type().is_time()) {
+                true => MasterType::Time,
+                false => MasterType::None,
+            },
         }
+    }
 
-        // let mut next_dg = hd_block.data_group_block;
-
-        // while next_dg != 0 {
-        //     let (_pos, dg_block) = Dgblock::read(&self.file, next_dg as usize, little_endian);
-        //     next_dg = dg_block.next;
-        //     let mut next_cg = dg_block.first;
-
-        //     dg.push(dg_block);
-
-        //     while next_cg != 0 {
-        //         let (_pos, cg_block) = Cgblock::read(&self.file, next_cg as usize, little_endian);
-        //         next_cg = cg_block.next;
-        //         let mut next_cn = cg_block.first;
-        //         cg.push(cg_block);
-
-        //         println!("Channel Group: {}", cg_block.comment);
+    fn has_unsupported_conversion(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channel: usize,
+    ) -> bool {
+        self.group(datagroup, channel_grp).channels[channel]
+            .has_unsupported_conversion(&self.file, self.little_endian)
+    }
 
-        //         while next_cn != 0 {
-        //             let (_pos, cn_block) =
-        //                 Cnblock::read(&self.file, next_cn as usize, little_endian);
-        //             next_cn = cn_block.next;
+    fn read_all(&mut self) {
+        let groups = self.groups.iter().flatten();
+        self.channel_groups = groups.clone().map(|x| x.block).collect();
+        self.channels = groups.flat_map(|x| x.channels.clone()).collect();
+    }
 
-        //             ch.push(cn_block);
-        //         }
-        //     }
-        // }
+    /// The data groups are read when the file is opened.
+    fn list_data_groups(&mut self) {}
 
-        // (ch, cg, dg);
+    fn list_channels(&self) {
+        for cg in self.groups.iter().flatten() {
+            println!(
+                "Channel Group: {}",
+                cg.block.comment(&self.file, self.little_endian)
+            );
+        }
     }
 
     fn read(&self, datagroup: usize, channel_grp: usize, channel: usize) -> signal::Signal {
@@ -283,41 +354,204 @@ impl mdf::MDFFile for MDF3 {
             Ok(x) => x,
             Err(e) => panic!("{}", e),
         };
-        println!("Time Channel: {}", time_channel);
         let time = self.read_channel(datagroup, channel_grp, time_channel);
         let some = self.read_channel(datagroup, channel_grp, channel);
 
-        signal::Signal::new(
-            time.iter().map(|x| x.extract()).collect(),
-            some,
-            "Unit".to_string(),
-            "Measurement".to_string(),
-            "This is some measurement".to_string(),
-            false,
-        )
+        let cn = &self.group(datagroup, channel_grp).channels[channel];
+        self.signal(cn, time.iter().map(|x| x.extract()).collect(), some)
     }
 
-    fn cut(&self, _start: f64, _end: f64, _include_ends: bool, _time_from_zero: bool) {
-        // let _delta = if time_from_zero { start } else { 0.0 };
+    fn cut(&self, start: f64, end: f64, include_ends: bool, time_from_zero: bool) -> Self {
+        let mut measurement = Measurement::read(self);
+        measurement.cut(start, end, include_ends, time_from_zero);
+        self.rebuild(&measurement)
     }
 
-    fn export(&self, _format: &str, _filename: &str) {}
-    fn filter(&self, _channels: &str) {}
-    fn resample(&self, _raster: mdf::RasterType, _version: &str, _time_from_zero: bool) -> Self {
-        self.clone()
+    fn export(&self, format: &str, filename: &str, options: &ExportOptions) -> io::Result<()> {
+        let measurement = match options.channels.is_empty() {
+            true => Measurement::read(self),
+            false => Measurement::read_matching(self, &options.channels),
+        };
+        export::export(measurement, format, filename, options)
     }
-    // fn select(
-    //     &self,
-    //     _channels: mdf::ChannelsType,
-    //     _record_offset: isize,
-    //     _raw: bool,
-    //     _copy_master: bool,
-    //     _ignore_value2text_conversions: bool,
-    //     _record_count: isize,
-    //     _validate: bool,
-    // ) -> Vec<Signal> {
-    //     Vec::new()
-    // }
+
+    fn filter(&self, channels: &[ChannelSelector]) -> Self {
+        self.rebuild(&Measurement::read_matching(self, channels))
+    }
+    fn resample(
+        &self,
+        raster: &mdf::RasterType,
+        interpolation: Interpolation,
+        time_from_zero: bool,
+    ) -> Result<Self, &'static str> {
+        let mut measurement = Measurement::read(self);
+        measurement.resample(raster, interpolation, time_from_zero)?;
+        Ok(self.rebuild(&measurement))
+    }
+    fn select(
+        &self,
+        channels: &[MdfChannel],
+        record_offset: usize,
+        record_count: Option<usize>,
+        raw: bool,
+    ) -> Result<Vec<signal::Signal>, &'static str> {
+        let mut signals = vec![None; channels.len()];
+        for (datagroup, channel_grp) in mdf::groups(channels) {
+            let ChannelGroup {
+                block: channel_group,
+                channels: cns,
+            } = self.group(datagroup, channel_grp);
+            let master = self.find_time_channel(datagroup, channel_grp)?;
+            let wanted: Vec<usize> = (0..channels.len())
+                .filter(|&i| {
+                    channels[i].data_group == datagroup && channels[i].channel_group == channel_grp
+                })
+                .collect();
+
+            let record_size = channel_group.record_size();
+            let range =
+                mdf::record_window(channel_group.record_number(), record_offset, record_count);
+            let data = match record_size {
+                0 => Cow::Borrowed(&[][..]),
+                _ => self.record_range(datagroup, channel_grp, range)?,
+            };
+            let records: Vec<&[u8]> = data.chunks_exact(record_size.max(1)).collect();
+            let timestamps: Vec<f64> = records
+                .iter()
+                .map(|record| cns[master].decode(record).extract())
+                .collect();
+
+            #[cfg(feature = "parallel")]
+            let wanted = wanted.par_iter();
+            #[cfg(not(feature = "parallel"))]
+            let wanted = wanted.iter();
+
+            let read: Vec<(usize, signal::Signal)> = wanted
+                .map(|&i| {
+                    let cn = &cns[channels[i].channel];
+                    let samples = records.iter().map(|x| cn.decode(x)).collect();
+                    let signal = self.signal(cn, timestamps.clone(), samples);
+                    match raw {
+                        true => (i, signal),
+                        false => (i, signal.physical()),
+                    }
+                })
+                .collect();
+            for (i, signal) in read {
+                signals[i] = Some(signal);
+            }
+        }
+
+        Ok(signals.into_iter().flatten().collect())
+    }
+
+    fn read_range(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channel: usize,
+        t_start: f64,
+        t_end: f64,
+    ) -> Result<signal::Signal, &'static str> {
+        let dg = &self.data_groups[datagroup];
+        let ChannelGroup {
+            block: channel_group,
+            channels: cns,
+        } = self.group(datagroup, channel_grp);
+        let master = &cns[self.find_time_channel(datagroup, channel_grp)?];
+        self.check_sorted(datagroup)?;
+
+        // The records follow each other in one data block, search them for
+        // the window reading one record at a time
+        let cn = &cns[channel];
+        let record_size = channel_group.record_size();
+        if record_size == 0 {
+            return Ok(self.signal(cn, Vec::new(), Vec::new()));
+        }
+        let start = dg.data_location();
+        let time = |record: usize| {
+            master
+                .decode(&self.file.data(start + record * record_size, record_size))
+                .extract()
+        };
+        let from = partition_point(channel_group.record_number(), |x| time(x) < t_start);
+        let to = partition_point(channel_group.record_number(), |x| time(x) <= t_end);
+
+        let data = self.file.data(
+            start + from * record_size,
+            to.saturating_sub(from) * record_size,
+        );
+        let (timestamps, samples) = data
+            .chunks_exact(record_size)
+            .map(|record| (master.decode(record).extract(), cn.decode(record)))
+            .unzip();
+
+        Ok(self.signal(cn, timestamps, samples))
+    }
+
+    fn chunks(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channels: &[MdfChannel],
+        chunk_records: usize,
+    ) -> Result<RecordChunks<'_>, &'static str> {
+        let dg = &self.data_groups[datagroup];
+        let ChannelGroup {
+            block: channel_group,
+            channels: cns,
+        } = self.group(datagroup, channel_grp);
+        let master = cns[self.find_time_channel(datagroup, channel_grp)?];
+        self.check_sorted(datagroup)?;
+        let wanted: Vec<Cnblock> = channels.iter().map(|x| cns[x.channel]).collect();
+
+        // The records follow each other in one data block, read a chunk of
+        // them at a time
+        let record_size = channel_group.record_size();
+        let length = record_size * channel_group.record_number();
+        let block_size = record_size * chunk_records.max(1);
+        let start = dg.data_location();
+        let blocks = (0..length).step_by(block_size.max(1)).map(move |offset| {
+            self.file
+                .data(start + offset, block_size.min(length - offset))
+                .into_owned()
+        });
+
+        Ok(RecordChunks::create(
+            datagroup,
+            channel_grp,
+            channels,
+            wanted
+                .iter()
+                .map(|cn| cn.conversion(&self.file, self.little_endian))
+                .collect(),
+            record_size,
+            channel_group.record_number(),
+            chunk_records,
+            Box::new(blocks),
+            Box::new(move |record| {
+                (
+                    master.decode(record).extract(),
+                    wanted.iter().map(|cn| cn.decode(record)).collect(),
+                )
+            }),
+        ))
+    }
+}
+
+/// The first index below `len` for which `pred` is false, `pred` holding for
+/// all indices before it.
+fn partition_point(len: usize, pred: impl Fn(usize) -> bool) -> usize {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if pred(middle) {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    low
 }
 
 #[allow(dead_code)]
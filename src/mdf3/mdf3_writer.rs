@@ -0,0 +1,609 @@
+use std::fs::File;
+use std::io::{self, Write};
+
+use chrono::{DateTime, Utc};
+
+use super::cc_block::Ccblock;
+use super::ce_block::Ceblock;
+use super::cg_block::Cgblock;
+use super::channel_type::ChannelType;
+use super::cn_block::Cnblock;
+use super::conversion::{TableEntry, TextRange, TextTableEntry};
+use super::conversion_data::{self, ConversionData};
+use super::dg_block::Dgblock;
+use super::event;
+use super::hd_block::Hdblock;
+use super::id_block::Idblock;
+use super::mdf3_block::WriteBlock;
+use super::tr_block::Trblock;
+use super::tx_block::Txblock;
+use crate::conversion::Conversion;
+use crate::event::Event;
+use crate::record::{DataType, DataTypeRead, Record};
+use crate::signal::{Signal, Source};
+use crate::utils;
+
+const ID_LENGTH: usize = 64;
+const SHORT_NAME_LENGTH: usize = 31;
+const DESCRIPTION_LENGTH: usize = 127;
+const TEXT_TABLE_LENGTH: usize = 31;
+
+struct ChannelGroup {
+    comment: String,
+    signals: Vec<Signal>,
+}
+
+/// Builds a sorted MDF 3.30 file, one data group per channel group, each with
+/// a float64 time channel followed by the signals.
+pub struct Mdf3Writer {
+    comment: String,
+    start_time: DateTime<Utc>,
+    little_endian: bool,
+    events: Vec<Event>,
+    groups: Vec<ChannelGroup>,
+}
+
+impl Default for Mdf3Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mdf3Writer {
+    pub fn new() -> Self {
+        Self {
+            comment: String::new(),
+            start_time: Utc::now(),
+            little_endian: true,
+            events: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    pub fn set_comment(&mut self, comment: &str) {
+        self.comment = comment.to_string();
+    }
+
+    pub fn set_start_time(&mut self, start_time: DateTime<Utc>) {
+        self.start_time = start_time;
+    }
+
+    /// Selects the byte order recorded in the ID block and used for every
+    /// block and record in the file.
+    pub fn set_little_endian(&mut self, little_endian: bool) {
+        self.little_endian = little_endian;
+    }
+
+    /// Adds a trigger event to the first data group. Events have no name and
+    /// share one comment, see [`Mdf3Writer::trigger_comment`].
+    pub fn add_event(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// Whether `source` is written without truncation or dropping its comment.
+    pub fn source_fits(source: &Source) -> bool {
+        Ceblock::fits(source)
+    }
+
+    /// The comment of the trigger block, the distinct event comments one per
+    /// line.
+    pub fn trigger_comment(&self) -> String {
+        Self::merge_comments(&self.events)
+    }
+
+    pub fn merge_comments(events: &[Event]) -> String {
+        let mut comments: Vec<&str> = Vec::new();
+        for event in events {
+            if !event.comment.is_empty() && !comments.contains(&event.comment.as_str()) {
+                comments.push(&event.comment);
+            }
+        }
+        comments.join("\n")
+    }
+
+    pub fn add_channel_group(
+        &mut self,
+        comment: &str,
+        signals: Vec<Signal>,
+    ) -> Result<(), &'static str> {
+        let first = match signals.first() {
+            Some(signal) => signal,
+            None => return Err("A channel group needs at least one signal"),
+        };
+
+        for signal in &signals {
+            if signal.samples.len() != signal.timestamps.len() {
+                return Err("Signal samples and timestamps differ in length");
+            }
+            if signal.timestamps != first.timestamps {
+                return Err("Signals in a channel group must share the same timestamps");
+            }
+        }
+
+        self.groups.push(ChannelGroup {
+            comment: comment.to_string(),
+            signals,
+        });
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let little_endian = self.little_endian;
+
+        let mut stream = Vec::new();
+        Idblock::create("rsmdf", little_endian).write(&mut stream, little_endian);
+        // Leave room for the header block, it is written last once its links are known
+        stream.resize(ID_LENGTH + Hdblock::BYTE_LEN, 0);
+
+        let comment = append_text(&mut stream, &self.comment, little_endian);
+
+        let trigger = if self.events.is_empty() {
+            0
+        } else {
+            let comment = append_text(&mut stream, &self.trigger_comment(), little_endian);
+            let events = self
+                .events
+                .iter()
+                .map(|x| event::Event {
+                    trigger_time: x.time,
+                    pre_trigger_time: x.pre_trigger,
+                    post_trigger_time: x.post_trigger,
+                })
+                .collect();
+            append(
+                &mut stream,
+                &Trblock::create(comment, events),
+                little_endian,
+            )
+        };
+
+        let mut dg_next = 0;
+        for (i, group) in self.groups.iter().enumerate().rev() {
+            let trigger = if i == 0 { trigger } else { 0 };
+            dg_next = write_group(&mut stream, dg_next, trigger, group, little_endian);
+        }
+
+        let mut header = Vec::new();
+        Hdblock::create(dg_next, comment, self.groups.len() as u16, self.start_time)
+            .write(&mut header, little_endian);
+        stream[ID_LENGTH..ID_LENGTH + header.len()].copy_from_slice(&header);
+
+        stream
+    }
+
+    pub fn save(&self, filepath: &str) -> io::Result<()> {
+        let mut file = File::create(filepath)?;
+        file.write_all(&self.to_bytes())
+    }
+}
+
+/// Writes the block at the end of the stream and returns its address.
+fn append<T: WriteBlock>(stream: &mut Vec<u8>, block: &T, little_endian: bool) -> u32 {
+    let address = stream.len() as u32;
+    block.write(stream, little_endian);
+    address
+}
+
+fn append_text(stream: &mut Vec<u8>, text: &str, little_endian: bool) -> u32 {
+    if text.is_empty() {
+        0
+    } else {
+        append(stream, &Txblock::create(text), little_endian)
+    }
+}
+
+fn append_conversion(
+    stream: &mut Vec<u8>,
+    unit: &str,
+    conversion: Option<&Conversion>,
+    little_endian: bool,
+) -> u32 {
+    let table = |raw: &[f64], phys: &[f64]| {
+        ConversionData::Table(
+            raw.iter()
+                .zip(phys)
+                .map(|(internal, physical)| TableEntry {
+                    internal: *internal,
+                    physical: *physical,
+                })
+                .collect(),
+        )
+    };
+
+    let (conversion_type, conversion_data) = match conversion {
+        None => (
+            conversion_data::ONE_TO_ONE,
+            ConversionData::Other(Vec::new()),
+        ),
+        Some(Conversion::Linear { offset, factor }) => (
+            conversion_data::LINEAR,
+            ConversionData::Parameters(vec![*offset, *factor]),
+        ),
+        Some(Conversion::TabularInterpolation { raw, phys }) => {
+            (conversion_data::TABULAR_INTERPOLATION, table(raw, phys))
+        }
+        Some(Conversion::Tabular { raw, phys }) => (conversion_data::TABULAR, table(raw, phys)),
+        // A text table has no default and holds short texts only, anything
+        // else goes into a text range table referencing TX blocks
+        Some(Conversion::ValueToText {
+            values,
+            texts,
+            default,
+        }) if default.is_empty() && texts.iter().all(|x| x.len() <= TEXT_TABLE_LENGTH) => (
+            conversion_data::TEXT_TABLE,
+            ConversionData::Text(
+                values
+                    .iter()
+                    .zip(texts)
+                    .map(|(value, text)| {
+                        let mut entry = TextTableEntry {
+                            internal: *value,
+                            text: [0_u8; 32],
+                        };
+                        entry.text[..text.len()].copy_from_slice(text.as_bytes());
+                        entry
+                    })
+                    .collect(),
+            ),
+        ),
+        Some(Conversion::ValueToText {
+            values,
+            texts,
+            default,
+        }) => {
+            let mut table = vec![TextRange {
+                lower: 0.0,
+                upper: 0.0,
+                txblock: append_text(stream, default, little_endian),
+            }];
+            for (value, text) in values.iter().zip(texts) {
+                table.push(TextRange {
+                    lower: *value,
+                    upper: *value,
+                    txblock: append_text(stream, text, little_endian),
+                });
+            }
+            (
+                conversion_data::TEXT_RANGE_TABLE,
+                ConversionData::TextRange(table),
+            )
+        }
+    };
+
+    append(
+        stream,
+        &Ccblock::create(unit, conversion_type, conversion_data),
+        little_endian,
+    )
+}
+
+fn write_group(
+    stream: &mut Vec<u8>,
+    dg_next: u32,
+    trigger: u32,
+    group: &ChannelGroup,
+    little_endian: bool,
+) -> u32 {
+    let timestamps = &group.signals[0].timestamps;
+
+    let master_len = 8;
+    let mut layout = Vec::with_capacity(group.signals.len());
+    let mut record_size = master_len;
+    for signal in &group.signals {
+        let (data_type, byte_len) = Record::storage(&signal.samples);
+        layout.push((data_type, record_size, byte_len));
+        record_size += byte_len;
+    }
+
+    let data_block = stream.len() as u32;
+    for (i, time) in timestamps.iter().enumerate() {
+        utils::write(stream, *time, little_endian);
+        for (signal, (_, _, byte_len)) in group.signals.iter().zip(&layout) {
+            stream.append(&mut signal.samples[i].to_bytes(*byte_len, little_endian));
+        }
+    }
+
+    let mut cn_next = 0;
+    for (signal, (data_type, byte_offset, byte_len)) in group.signals.iter().zip(&layout).rev() {
+        let long_name = if signal.name.len() > SHORT_NAME_LENGTH {
+            append_text(stream, &signal.name, little_endian)
+        } else {
+            0
+        };
+        let comment = if signal.comment.len() > DESCRIPTION_LENGTH {
+            append_text(stream, &signal.comment, little_endian)
+        } else {
+            0
+        };
+        let conversion = match (&signal.conversion, signal.raw) {
+            (Some(conversion), true) => Some(conversion),
+            _ => None,
+        };
+        let conversion = if signal.unit.is_empty() && conversion.is_none() {
+            0
+        } else {
+            append_conversion(stream, &signal.unit, conversion, little_endian)
+        };
+        let source = match &signal.source {
+            Some(source) => append(
+                stream,
+                &Ceblock::dim(&source.name, &source.path),
+                little_endian,
+            ),
+            None => 0,
+        };
+
+        cn_next = append(
+            stream,
+            &Cnblock::create(
+                cn_next,
+                conversion,
+                source,
+                comment,
+                ChannelType::Data,
+                &signal.name,
+                &signal.comment,
+                long_name,
+                (*byte_offset * 8) as u16,
+                (*byte_len * 8) as u16,
+                DataTypeRead {
+                    data_type: *data_type,
+                    little_endian,
+                },
+            ),
+            little_endian,
+        );
+    }
+
+    let conversion = append_conversion(stream, "s", None, little_endian);
+    let cn_first = append(
+        stream,
+        &Cnblock::create(
+            cn_next,
+            conversion,
+            0,
+            0,
+            ChannelType::Time,
+            "time",
+            "",
+            0,
+            0,
+            (master_len * 8) as u16,
+            DataTypeRead {
+                data_type: DataType::Float64,
+                little_endian,
+            },
+        ),
+        little_endian,
+    );
+
+    let comment = append_text(stream, &group.comment, little_endian);
+    let cg = append(
+        stream,
+        &Cgblock::create(
+            0,
+            cn_first,
+            comment,
+            group.signals.len() as u16 + 1,
+            record_size as u16,
+            timestamps.len() as u32,
+        ),
+        little_endian,
+    );
+
+    append(
+        stream,
+        &Dgblock::create(dg_next, cg, trigger, data_block, 1),
+        little_endian,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mdf3Writer;
+    use crate::conversion::Conversion;
+    use crate::mdf::{MDFFile, MDF};
+    use crate::record::Record;
+    use crate::signal::Signal;
+    use crate::utils::TempFile;
+
+    fn signal(name: &str, samples: Vec<Record>) -> Signal {
+        Signal::new(
+            (0..samples.len()).map(|x| x as f64 * 0.1).collect(),
+            samples,
+            "V".to_string(),
+            name.to_string(),
+            format!("{} comment", name),
+            false,
+        )
+    }
+
+    #[test]
+    fn add_channel_group() {
+        let mut writer = Mdf3Writer::new();
+
+        assert!(writer.add_channel_group("empty", Vec::new()).is_err());
+
+        let mut other = signal("b", vec![Record::Uint(1)]);
+        other.timestamps = vec![5.0];
+        assert!(writer
+            .add_channel_group("mismatch", vec![signal("a", vec![Record::Uint(1)]), other])
+            .is_err());
+    }
+
+    fn round_trip(little_endian: bool) {
+        let status = Signal::new(
+            vec![0.0, 0.1, 0.2],
+            vec![Record::Uint(0), Record::Uint(1), Record::Uint(2)],
+            "".to_string(),
+            "status".to_string(),
+            "".to_string(),
+            true,
+        )
+        .with_conversion(Conversion::ValueToText {
+            values: vec![0.0, 1.0],
+            texts: vec!["Off".to_string(), "On".to_string()],
+            default: "Unknown".to_string(),
+        });
+        let gear = Signal::new(
+            vec![0.0, 0.1, 0.2],
+            vec![Record::Uint(1), Record::Uint(2), Record::Uint(1)],
+            "".to_string(),
+            "gear".to_string(),
+            "".to_string(),
+            true,
+        )
+        .with_conversion(Conversion::ValueToText {
+            values: vec![1.0, 2.0],
+            texts: vec!["First".to_string(), "Second".to_string()],
+            default: "".to_string(),
+        });
+        let speed = Signal::new(
+            vec![0.0, 0.1, 0.2],
+            vec![Record::Int(-300), Record::Int(0), Record::Int(40000)],
+            "km/h".to_string(),
+            "speed".to_string(),
+            "vehicle speed".to_string(),
+            true,
+        )
+        .with_conversion(Conversion::Linear {
+            offset: 1.0,
+            factor: 0.5,
+        });
+        let pedal = Signal::new(
+            vec![0.0, 0.1, 0.2],
+            vec![Record::Uint(0), Record::Uint(5), Record::Uint(10)],
+            "%".to_string(),
+            "a pedal position with a name longer than the short name".to_string(),
+            "".to_string(),
+            true,
+        )
+        .with_conversion(Conversion::TabularInterpolation {
+            raw: vec![0.0, 10.0],
+            phys: vec![0.0, 100.0],
+        });
+
+        let mut writer = Mdf3Writer::new();
+        writer.set_little_endian(little_endian);
+        writer.set_comment("round trip");
+        writer
+            .add_channel_group("first", vec![status, gear, speed.clone(), pedal.clone()])
+            .unwrap();
+        writer
+            .add_channel_group(
+                "second",
+                vec![
+                    signal(
+                        "voltage",
+                        vec![Record::Float64(1.5), Record::Float64(-2.25)],
+                    ),
+                    signal("ratio", vec![Record::Float32(0.5), Record::Float32(0.25)]),
+                    signal(
+                        "label",
+                        vec![
+                            Record::StringNullTerm("idle".to_string()),
+                            Record::StringNullTerm("running".to_string()),
+                        ],
+                    ),
+                ],
+            )
+            .unwrap();
+
+        let file = TempFile::create(&format!("writer_{little_endian}.mdf"));
+        writer.save(file.path()).unwrap();
+        let mdf = MDF::new(file.path());
+
+        let names: Vec<String> = mdf.channels.iter().map(|x| x.name.clone()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "time",
+                "status",
+                "gear",
+                "speed",
+                "a pedal position with a name longer than the short name",
+                "time",
+                "voltage",
+                "ratio",
+                "label"
+            ]
+        );
+
+        let read = mdf.read_channel(&mdf.search_channels("speed").unwrap());
+        assert_eq!(read, speed);
+        assert_eq!(
+            read.physical().samples,
+            vec![
+                Record::Float64(-149.0),
+                Record::Float64(1.0),
+                Record::Float64(20001.0)
+            ]
+        );
+
+        let status = mdf.read_channel(&mdf.search_channels("status").unwrap());
+        assert_eq!(
+            status.physical().samples,
+            vec![
+                Record::StringNullTerm("Off".to_string()),
+                Record::StringNullTerm("On".to_string()),
+                Record::StringNullTerm("Unknown".to_string())
+            ]
+        );
+
+        let gear = mdf.read_channel(&mdf.search_channels("gear").unwrap());
+        assert_eq!(
+            gear.physical().samples,
+            vec![
+                Record::StringNullTerm("First".to_string()),
+                Record::StringNullTerm("Second".to_string()),
+                Record::StringNullTerm("First".to_string())
+            ]
+        );
+
+        let read = mdf.read_channel(&mdf.search_channels(&pedal.name).unwrap());
+        assert_eq!(read, pedal);
+        assert_eq!(
+            read.physical().samples,
+            vec![
+                Record::Float64(0.0),
+                Record::Float64(50.0),
+                Record::Float64(100.0)
+            ]
+        );
+
+        let voltage = mdf.read_channel(&mdf.search_channels("voltage").unwrap());
+        assert_eq!(voltage.timestamps, vec![0.0, 0.1]);
+        assert_eq!(
+            voltage.samples,
+            vec![Record::Float64(1.5), Record::Float64(-2.25)]
+        );
+        assert_eq!(voltage.unit, "V");
+        assert_eq!(voltage.comment, "voltage comment");
+
+        let ratio = mdf.read_channel(&mdf.search_channels("ratio").unwrap());
+        assert_eq!(
+            ratio.samples,
+            vec![Record::Float32(0.5), Record::Float32(0.25)]
+        );
+
+        let label = mdf.read_channel(&mdf.search_channels("label").unwrap());
+        assert_eq!(
+            label.samples,
+            vec![
+                Record::StringNullTerm("idle".to_string()),
+                Record::StringNullTerm("running".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trip_little_endian() {
+        round_trip(true);
+    }
+
+    #[test]
+    fn round_trip_big_endian() {
+        round_trip(false);
+    }
+}
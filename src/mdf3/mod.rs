@@ -1,4 +1,5 @@
 pub mod mdf3_file;
+pub mod mdf3_writer;
 
 mod cc_block;
 mod cd_block;
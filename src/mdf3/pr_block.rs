@@ -1,7 +1,9 @@
+use crate::storage::Stream;
 use crate::utils;
 
-use super::mdf3_block::Mdf3Block;
+use super::mdf3_block::{Mdf3Block, WriteBlock};
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Prblock {
     block_type: [u8; 2],
@@ -10,7 +12,7 @@ pub struct Prblock {
 }
 
 impl Mdf3Block for Prblock {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let block_type: [u8; 2] = utils::read(stream, little_endian, &mut pos);
         if !utils::eq(&block_type, "PR".as_bytes()) {
@@ -20,9 +22,7 @@ impl Mdf3Block for Prblock {
         let block_size = utils::read(stream, little_endian, &mut pos);
 
         //let mut program_data = vec![0; block_size as usize];
-        let mut program_data: Vec<u8> = stream[pos..block_size as usize - pos]
-            .try_into()
-            .expect("msg");
+        let mut program_data: Vec<u8> = stream.bytes(pos, block_size as usize - 2 * pos).into();
 
         pos += program_data.len();
 
@@ -44,9 +44,12 @@ impl Mdf3Block for Prblock {
     }
 }
 
-impl Prblock {
-    #[allow(dead_code)]
-    pub fn write() {}
+impl WriteBlock for Prblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.block_type, little_endian);
+        utils::write(stream, self.block_size, little_endian);
+        stream.extend_from_slice(&self.program_data);
+    }
 }
 
 #[cfg(test)]
@@ -381,5 +384,16 @@ mod tests {
     }
 
     #[test]
-    fn write() {}
+    fn write() {
+        let pr_block = Prblock {
+            block_type: *b"PR",
+            block_size: 9,
+            program_data: b"rsmdf".to_vec(),
+        };
+
+        let mut stream = Vec::new();
+        pr_block.write(&mut stream, true);
+
+        assert_eq!(stream, b"PR\x09\x00rsmdf");
+    }
 }
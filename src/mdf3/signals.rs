@@ -1,3 +1,4 @@
+use crate::storage::Stream;
 use crate::utils;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -11,7 +12,7 @@ impl Signals {
     #[allow(dead_code)]
     pub fn write() {}
     #[allow(dead_code)]
-    pub fn read(stream: &[u8], little_endian: bool) -> (Self, usize) {
+    pub fn read(stream: &dyn Stream, little_endian: bool) -> (Self, usize) {
         let mut position = 0;
         let data_group = utils::read(stream, little_endian, &mut position);
         let channel_group = utils::read(stream, little_endian, &mut position);
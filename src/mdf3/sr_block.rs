@@ -1,7 +1,9 @@
+use crate::storage::Stream;
 use crate::utils;
 
-use super::mdf3_block::{LinkedBlock, Mdf3Block};
+use super::mdf3_block::{LinkedBlock, Mdf3Block, WriteBlock};
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct Srblock {
     #[allow(dead_code)]
@@ -19,38 +21,16 @@ pub struct Srblock {
 }
 
 impl LinkedBlock for Srblock {
-    fn next(&self, stream: &[u8], little_endian: bool) -> Option<Self>
-    where
-        Self: std::marker::Sized,
-    {
-        if self.next == 0 {
-            None
-        } else {
-            let (_pos, block) = Self::read(stream, self.next as usize, little_endian);
-            Some(block)
-        }
-    }
-
-    fn list(&self, stream: &[u8], little_endian: bool) -> Vec<Self>
-    where
-        Self: std::marker::Sized,
-    {
-        let mut all = Vec::new();
-
-        let next = self.next(stream, little_endian);
+    const ID: &'static [u8; 2] = b"SR";
+    const SIZE: usize = 24;
 
-        all.push(*self);
-        match next {
-            None => {}
-            Some(block) => all.append(&mut block.list(stream, little_endian)),
-        }
-
-        all
+    fn link(&self) -> u64 {
+        self.next.into()
     }
 }
 
 impl Mdf3Block for Srblock {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let block_type: [u8; 2] = utils::read(stream, little_endian, &mut pos);
 
@@ -78,9 +58,15 @@ impl Mdf3Block for Srblock {
     }
 }
 
-impl Srblock {
-    #[allow(dead_code)]
-    pub fn write() {}
+impl WriteBlock for Srblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.block_type, little_endian);
+        utils::write(stream, self.block_size, little_endian);
+        utils::write(stream, self.next, little_endian);
+        utils::write(stream, self.data_block, little_endian);
+        utils::write(stream, self.samples_reduced_number, little_endian);
+        utils::write(stream, self.time_interval_length, little_endian);
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,7 @@
+use crate::storage::Stream;
 use crate::utils;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct TimeStruct {
     pub ms: u32,
@@ -10,7 +12,7 @@ impl TimeStruct {
     #[allow(dead_code)]
     pub fn write() {}
     #[allow(dead_code)]
-    pub fn read(stream: &[u8], little_endian: bool) -> (TimeStruct, usize) {
+    pub fn read(stream: &dyn Stream, little_endian: bool) -> (TimeStruct, usize) {
         let mut position = 0;
         let ms = utils::read(stream, little_endian, &mut position);
         let days = utils::read(stream, little_endian, &mut position);
@@ -1,3 +1,12 @@
+use crate::storage::Stream;
+use crate::utils;
+
+use super::{
+    event::Event,
+    mdf3_block::{Mdf3Block, WriteBlock},
+    tx_block::Txblock,
+};
+
 #[derive(Debug, Clone)]
 pub struct Trblock {
     #[allow(dead_code)]
@@ -13,10 +22,10 @@ pub struct Trblock {
 }
 
 impl Mdf3Block for Trblock {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
 
-        let block_type: [u8; 2] = utils::read(&stream[pos..], little_endian, &mut pos);
+        let block_type: [u8; 2] = utils::read(stream, little_endian, &mut pos);
         if !utils::eq(&block_type, "TR".as_bytes()) {
             panic!(
                 "TRBLOCK not found. Found: {}, {}",
@@ -24,10 +33,8 @@ impl Mdf3Block for Trblock {
             );
         }
 
-        pos += block_type.len();
-
-        let block_size = utils::read(&stream[pos..], little_endian, &mut pos);
-        let trigger_comment = utils::read(&stream[pos..], little_endian, &mut pos);
+        let block_size = utils::read(stream, little_endian, &mut pos);
+        let trigger_comment = utils::read(stream, little_endian, &mut pos);
         let trigger_events_number = utils::read(stream, little_endian, &mut pos);
         let (events, pos) = Trblock::read_events(stream, pos, little_endian, trigger_events_number);
 
@@ -44,74 +51,90 @@ impl Mdf3Block for Trblock {
     }
 }
 
+impl WriteBlock for Trblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.block_type, little_endian);
+        utils::write(stream, self.block_size, little_endian);
+        utils::write(stream, self.trigger_comment, little_endian);
+        utils::write(stream, self.trigger_events_number, little_endian);
+        for event in &self.events {
+            event.write(stream, little_endian);
+        }
+    }
+}
+
 impl Trblock {
-    #[allow(dead_code)]
-    pub fn write() {}
+    pub fn create(trigger_comment: u32, events: Vec<Event>) -> Self {
+        Self {
+            block_type: *b"TR",
+            block_size: 10 + 24 * events.len() as u16,
+            trigger_comment,
+            trigger_events_number: events.len() as u16,
+            events,
+        }
+    }
 
-    #[allow(dead_code)]
     pub fn read_events(
-        stream: &[u8],
+        stream: &dyn Stream,
         position: usize,
         little_endian: bool,
         no_events: u16,
     ) -> (Vec<Event>, usize) {
-        let mut events = Vec::with_capacity(no_events as usize + 1);
-        let mut pos1 = position;
+        let mut events = Vec::with_capacity(no_events as usize);
+        let mut pos = position;
         for _i in 0..no_events {
-            let (event, pos) = Event::read(stream, pos1, little_endian);
+            let (event, next) = Event::read(stream, pos, little_endian);
             events.push(event);
-            pos1 += pos;
+            pos = next;
         }
 
-        (events, position)
+        (events, pos)
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn comment(&self, stream: &dyn Stream, little_endian: bool) -> String {
+        if self.trigger_comment == 0 {
+            return String::new();
+        }
+
+        let (_pos, tx) = Txblock::read(stream, self.trigger_comment as usize, little_endian);
+        tx.text()
     }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-
-//     #[test]
-//     fn read() {
-//         let tr_data = [
-//             0x54, 0x52, 0x3A, 0x00, 0xB7, 0xE4, 0x10, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xCD, 0xF3, 0x8C, 0xEF, 0x1E, 0x6B, 0x0E, 0x40,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x43, 0x43, 0x2E, 0x00, 0x01, 0x00, 0x10, 0xA4, 0x20, 0x1C, 0x58, 0x33,
-//             0x97, 0x3F, 0x12, 0x5B, 0x1A, 0xC4, 0x49, 0xD3, 0x28, 0x40, 0x73, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x43, 0x4E, 0xE4, 0x00, 0x63, 0xE7, 0x10, 0x00,
-//             0x65, 0xE5, 0x10, 0x00, 0xAE, 0xE0, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x01, 0x00, 0x74, 0x69, 0x6D, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00,
-//             0x03, 0x00, 0x01, 0x00, 0x10, 0xA4, 0x20, 0x1C, 0x58, 0x33, 0x97, 0x3F, 0x12, 0x5B,
-//             0x1A, 0xC4, 0x49, 0xD3, 0x28, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-//             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x54, 0x58,
-//         ];
-
-//         let (tr_block, position) = TRBLOCK::read(&tr_data, true, 0);
-
-//         assert_eq!(position, 58);
-//         assert_eq!(tr_block.block_size, 58);
-//         assert_eq!(tr_block.trigger_comment, 1107127);
-//         assert_eq!(tr_block.trigger_events_number, 2);
-//     }
-
-//     #[test]
-//     fn write() {}
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-use crate::utils;
+    static RAW: [u8; 58] = [
+        0x54, 0x52, 0x3A, 0x00, 0xB7, 0xE4, 0x10, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0xCD, 0xF3, 0x8C, 0xEF, 0x1E, 0x6B, 0x0E, 0x40, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn read() {
+        let (position, tr_block) = Trblock::read(&RAW, 0, true);
 
-use super::{event::Event, mdf3_block::Mdf3Block};
+        assert_eq!(position, 58);
+        assert_eq!(tr_block.block_size, 58);
+        assert_eq!(tr_block.trigger_comment, 1107127);
+        assert_eq!(tr_block.trigger_events_number, 2);
+        assert_eq!(tr_block.events()[0].trigger_time, 0.0);
+        assert!((tr_block.events()[1].trigger_time - 3.802305099).abs() < 1e-9);
+    }
+
+    #[test]
+    fn write() {
+        let (_position, tr_block) = Trblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        Trblock::create(tr_block.trigger_comment, tr_block.events.clone()).write(&mut stream, true);
+
+        assert_eq!(&RAW[..], &stream[..]);
+    }
+}
@@ -1,6 +1,7 @@
+use crate::storage::Stream;
 use crate::utils;
 
-use super::mdf3_block::Mdf3Block;
+use super::mdf3_block::{Mdf3Block, WriteBlock};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Txblock {
@@ -10,7 +11,7 @@ pub struct Txblock {
 }
 
 impl Mdf3Block for Txblock {
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
 
         let block_type: [u8; 2] = utils::read(stream, little_endian, &mut pos);
@@ -22,9 +23,7 @@ impl Mdf3Block for Txblock {
         }
         let block_size = utils::read(stream, little_endian, &mut pos);
 
-        let mut text: Vec<u8> = stream[pos..pos + block_size as usize - 5]
-            .try_into()
-            .expect("msg");
+        let mut text: Vec<u8> = stream.bytes(pos, block_size as usize - 5).into();
 
         // make sure that the text is utf8
         for c in &mut text {
@@ -45,16 +44,42 @@ impl Mdf3Block for Txblock {
     }
 }
 
+impl WriteBlock for Txblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.block_type, little_endian);
+        utils::write(stream, self.block_size, little_endian);
+        stream.extend_from_slice(&self.text);
+        stream.push(0);
+    }
+}
+
 impl Txblock {
-    #[allow(dead_code)]
-    pub fn write() {}
+    pub fn create(text: &str) -> Self {
+        let text = text.as_bytes().to_vec();
+        Self {
+            block_type: *b"TX",
+            block_size: (text.len() + 5) as u16,
+            text,
+        }
+    }
 
-    pub fn name(self) -> String {
-        //let mut name = "".to_string();
+    pub fn text(&self) -> String {
+        let end = self
+            .text
+            .iter()
+            .position(|x| *x == 0)
+            .unwrap_or(self.text.len());
 
-        //let (tx, _pos) = Self::read(stream, little_endian);
+        String::from_utf8_lossy(&self.text[..end]).into_owned()
+    }
 
-        utils::extract_name(&self.text)
+    /// The display name for XML comments, otherwise the plain text.
+    pub fn name(self) -> String {
+        if self.text.first() == Some(&b'<') {
+            utils::extract_name(&self.text)
+        } else {
+            self.text()
+        }
     }
 }
 
@@ -150,5 +175,17 @@ mod tests {
     }
 
     #[test]
-    fn write() {}
+    fn write() {
+        let tx_block = Txblock::create("Engine speed");
+
+        let mut stream = Vec::new();
+        tx_block.write(&mut stream, true);
+
+        let (position, read) = Txblock::read(&stream, 0, true);
+
+        assert_eq!(position, stream.len() - 1);
+        assert_eq!(read.block_size as usize, stream.len());
+        assert_eq!(read.text(), "Engine speed");
+        assert_eq!(read.name(), "Engine speed");
+    }
 }
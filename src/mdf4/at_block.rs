@@ -1,11 +1,18 @@
 use std::mem;
 
+use crate::attachment::Attachment;
+use crate::storage::Stream;
 use crate::utils;
 
-use super::block::Block;
+use super::block::{Block, WriteBlock};
 use super::block_header::BlockHeader;
 use super::mdf4_file::link_extract;
+use super::utils as mdf4_utils;
 
+const EMBEDDED: u16 = 1;
+const COMPRESSED: u16 = 2;
+
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Atblock {
     header: BlockHeader,
@@ -55,7 +62,7 @@ impl Block for Atblock {
             embedded_data: Vec::new(),
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##AT".as_bytes()) {
@@ -75,7 +82,8 @@ impl Block for Atblock {
         let md5_sum = utils::read(stream, little_endian, &mut pos);
         let original_size = utils::read(stream, little_endian, &mut pos);
         let embedded_size = utils::read(stream, little_endian, &mut pos);
-        let embedded_data = stream[pos..pos + embedded_size as usize].to_vec();
+        let embedded_data = stream.data(pos, embedded_size as usize).into_owned();
+        pos += embedded_data.len();
 
         (
             pos,
@@ -115,3 +123,83 @@ impl Block for Atblock {
         length
     }
 }
+
+impl Atblock {
+    /// Embeds `attachment.data` unless it is empty, the MD5 checksum is left
+    /// unset.
+    pub fn create(
+        next_at_addr: u64,
+        file_name_addr: u64,
+        mime_addr: u64,
+        comment_addr: u64,
+        attachment: &Attachment,
+    ) -> Self {
+        let mut block = Self::new();
+        block.next_at_addr = next_at_addr;
+        block.file_name_addr = file_name_addr;
+        block.mime_addr = mime_addr;
+        block.comment_addr = comment_addr;
+        if !attachment.data.is_empty() {
+            block.flags |= EMBEDDED;
+        }
+        if attachment.compressed {
+            block.flags |= COMPRESSED;
+        }
+        block.original_size = attachment.original_size;
+        block.embedded_size = attachment.data.len() as u64;
+        block.embedded_data = attachment.data.clone();
+        block.header = BlockHeader::create("##AT", block.byte_len(), 4);
+        block
+    }
+
+    pub fn next(&self) -> u64 {
+        self.next_at_addr
+    }
+
+    pub fn attachment(&self, stream: &dyn Stream, little_endian: bool) -> Attachment {
+        Attachment {
+            file_name: mdf4_utils::read_text(stream, self.file_name_addr, little_endian),
+            mime: mdf4_utils::read_text(stream, self.mime_addr, little_endian),
+            comment: mdf4_utils::read_text(stream, self.comment_addr, little_endian),
+            data: self.embedded_data.clone(),
+            compressed: self.flags & COMPRESSED != 0,
+            original_size: self.original_size,
+        }
+    }
+}
+
+impl WriteBlock for Atblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        utils::write(stream, self.next_at_addr, little_endian);
+        utils::write(stream, self.file_name_addr, little_endian);
+        utils::write(stream, self.mime_addr, little_endian);
+        utils::write(stream, self.comment_addr, little_endian);
+        utils::write(stream, self.flags, little_endian);
+        utils::write(stream, self.creator_index, little_endian);
+        utils::write(stream, self.reserved1, little_endian);
+        utils::write(stream, self.md5_sum, little_endian);
+        utils::write(stream, self.original_size, little_endian);
+        utils::write(stream, self.embedded_size, little_endian);
+        stream.extend_from_slice(&self.embedded_data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write() {
+        let attachment = Attachment::embedded("notes.txt", "text/plain", b"hello".to_vec());
+        let block = Atblock::create(0, 24, 48, 0, &attachment);
+
+        let mut stream = Vec::new();
+        block.write(&mut stream, true);
+        assert_eq!(stream.len(), block.byte_len());
+
+        let (pos, read) = Atblock::read(&stream, 0, true);
+        assert_eq!(pos, stream.len());
+        assert_eq!(read, block);
+    }
+}
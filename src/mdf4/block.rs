@@ -1,20 +1,76 @@
+use super::block_header::BlockHeader;
+use crate::storage::Stream;
+use crate::utils;
+
 pub trait Block {
     fn new() -> Self;
+    #[allow(dead_code)]
     fn default() -> Self;
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self);
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self);
     fn byte_len(&self) -> usize;
     //fn is_empty(&self) -> bool;
 }
 
-pub trait LinkedBlock {
-    fn next(&self, stream: &[u8], little_endian: bool) -> Option<Self>
-    where
-        Self: std::marker::Sized;
-    fn list(&self, stream: &[u8], little_endian: bool) -> Vec<Self>
-    where
-        Self: std::marker::Sized;
+pub trait WriteBlock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool);
+}
+
+pub trait LinkedBlock: Block + Sized {
+    const ID: &'static [u8; 4];
+    /// The fewest links the block has.
+    const LINKS: u64;
+    /// Bytes of data following the links.
+    const DATA: u64;
+
+    /// Position of the next block, 0 for the last one.
+    fn link(&self) -> u64;
+
+    /// An error if the parts of the block whose size depends on its data do
+    /// not fit, `data` being the position of the data after the links.
+    fn check_layout(
+        _stream: &dyn Stream,
+        _data: usize,
+        _header: &BlockHeader,
+        _little_endian: bool,
+    ) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    /// Reads the block at `position`, an error unless a whole block of this
+    /// type lies there.
+    fn checked(
+        stream: &dyn Stream,
+        position: usize,
+        little_endian: bool,
+    ) -> Result<Self, &'static str> {
+        if position.saturating_add(4) > stream.len() {
+            return Err("Link past the end of the file");
+        }
+        if *stream.bytes(position, 4) != *Self::ID {
+            return Err("Link to a block of the wrong type");
+        }
+        let header = BlockHeader::check(stream, position, little_endian)?;
+        if header.link_count < Self::LINKS || header.data_length() < Self::DATA {
+            return Err("Block too short");
+        }
+        let data = position + 24 + 8 * header.link_count as usize;
+        Self::check_layout(stream, data, &header, little_endian)?;
+        Ok(Self::read(stream, position, little_endian).1)
+    }
+
+    /// The block at `position` and all following it, an error if the links
+    /// or blocks are corrupt.
+    fn list(
+        stream: &dyn Stream,
+        position: usize,
+        little_endian: bool,
+    ) -> Result<Vec<Self>, &'static str> {
+        utils::linked_list(stream, position, Self::link, |position| {
+            Self::checked(stream, position, little_endian)
+        })
+    }
 }
 
 pub trait DataBlock {
-    fn data_array(&self, stream: &[u8], little_endian: bool) -> Vec<u8>;
+    fn data_array(&self, stream: &dyn Stream, little_endian: bool) -> Vec<u8>;
 }
@@ -1,6 +1,7 @@
 use std::mem;
 
-use super::block::Block;
+use super::block::{Block, WriteBlock};
+use crate::storage::Stream;
 use crate::utils;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -26,6 +27,45 @@ impl BlockHeader {
             link_count: link_count as u64,
         }
     }
+
+    /// The header of the block at `position`, an error unless the block
+    /// with its links lies within the stream.
+    pub fn check(
+        stream: &dyn Stream,
+        position: usize,
+        little_endian: bool,
+    ) -> Result<Self, &'static str> {
+        if position.saturating_add(24) > stream.len() {
+            return Err("Link past the end of the file");
+        }
+        let (_pos, header) = Self::read(stream, position, little_endian);
+        let links = header
+            .link_count
+            .checked_mul(8)
+            .and_then(|x| x.checked_add(24));
+        match links {
+            Some(links) if links <= header.length => {}
+            _ => return Err("Block too short for its links"),
+        }
+        match (position as u64).checked_add(header.length) {
+            Some(end) if end <= stream.len() as u64 => Ok(header),
+            _ => Err("Block past the end of the file"),
+        }
+    }
+
+    /// Bytes of the block after its links.
+    pub fn data_length(&self) -> u64 {
+        self.length - 24 - 8 * self.link_count
+    }
+}
+
+impl WriteBlock for BlockHeader {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.id, little_endian);
+        utils::write(stream, self.reserved0, little_endian);
+        utils::write(stream, self.length, little_endian);
+        utils::write(stream, self.link_count, little_endian);
+    }
 }
 
 impl Block for BlockHeader {
@@ -45,7 +85,7 @@ impl Block for BlockHeader {
             link_count: 0,
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let mut pos = position;
         let id: [u8; 4] = utils::read(stream, little_endian, &mut pos);
         let reserved0: [u8; 4] = utils::read(stream, little_endian, &mut pos);
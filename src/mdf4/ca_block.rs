@@ -3,8 +3,10 @@ use std::mem;
 use super::block::Block;
 use super::block_header::*;
 use super::mdf4_file::link_extract;
+use crate::storage::Stream;
 use crate::utils;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq)]
 struct Cablock {
     header: BlockHeader,
@@ -71,7 +73,7 @@ impl Block for Cablock {
             ca_cycle_count: Vec::new(),
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id[..], "##CA".as_bytes()) {
@@ -1,14 +1,17 @@
 use std::mem;
 
-use super::block::Block;
+use super::block::{Block, WriteBlock};
 use super::block_header::*;
+use super::utils as mdf4_utils;
+use crate::conversion::Conversion;
 use crate::mdf4::mdf4_file::link_extract;
+use crate::storage::Stream;
 use crate::utils;
 
 use super::mdf4_enums::CCType;
 
 #[derive(Debug, Clone, PartialEq)]
-struct Ccblock {
+pub struct Ccblock {
     header: BlockHeader,
     cc_tx_name: u64,
     cc_md_unit: u64,
@@ -24,6 +27,108 @@ struct Ccblock {
     cc_phy_range_max: f64,
     cc_val: Vec<f64>,
 }
+impl Ccblock {
+    pub fn linear(offset: f64, factor: f64) -> Self {
+        let mut block = Self::new();
+        block.cc_type = CCType::Parametic;
+        block.cc_val_count = 2;
+        block.cc_val = vec![offset, factor];
+        block.header = BlockHeader::create("##CC", block.byte_len(), 4);
+        block
+    }
+
+    /// `cc_ref` holds the TX address for each value followed by the default.
+    pub fn value_to_text(values: Vec<f64>, cc_ref: Vec<u64>) -> Self {
+        let mut block = Self::new();
+        block.cc_type = CCType::ValueTableText;
+        block.cc_ref_count = cc_ref.len() as u16;
+        block.cc_ref = cc_ref;
+        block.cc_val_count = values.len() as u16;
+        block.cc_val = values;
+        block.header = BlockHeader::create("##CC", block.byte_len(), 4 + block.cc_ref.len());
+        block
+    }
+
+    /// Stores the table as interleaved `raw, phys` pairs.
+    pub fn table(interpolate: bool, raw: &[f64], phys: &[f64]) -> Self {
+        let mut block = Self::new();
+        block.cc_type = if interpolate {
+            CCType::ValueTableInterpolate
+        } else {
+            CCType::ValueTableNoInterpolate
+        };
+        block.cc_val = raw
+            .iter()
+            .zip(phys)
+            .flat_map(|(raw, phys)| [*raw, *phys])
+            .collect();
+        block.cc_val_count = block.cc_val.len() as u16;
+        block.header = BlockHeader::create("##CC", block.byte_len(), 4);
+        block
+    }
+
+    /// True for conversions other than identity that [`Ccblock::conversion`]
+    /// cannot express.
+    pub fn is_unsupported(&self, stream: &dyn Stream, little_endian: bool) -> bool {
+        self.cc_type != CCType::Direct && self.conversion(stream, little_endian).is_none()
+    }
+
+    pub fn conversion(&self, stream: &dyn Stream, little_endian: bool) -> Option<Conversion> {
+        match self.cc_type {
+            CCType::Parametic => Some(Conversion::Linear {
+                offset: self.cc_val[0],
+                factor: self.cc_val[1],
+            }),
+            CCType::ValueTableInterpolate => Some(Conversion::TabularInterpolation {
+                raw: self.cc_val.iter().step_by(2).copied().collect(),
+                phys: self.cc_val.iter().skip(1).step_by(2).copied().collect(),
+            }),
+            CCType::ValueTableNoInterpolate => Some(Conversion::Tabular {
+                raw: self.cc_val.iter().step_by(2).copied().collect(),
+                phys: self.cc_val.iter().skip(1).step_by(2).copied().collect(),
+            }),
+            CCType::ValueTableText => {
+                let mut texts: Vec<String> = self
+                    .cc_ref
+                    .iter()
+                    .map(|address| mdf4_utils::read_text(stream, *address, little_endian))
+                    .collect();
+                let default = texts.pop().unwrap_or_default();
+
+                Some(Conversion::ValueToText {
+                    values: self.cc_val.clone(),
+                    texts,
+                    default,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl WriteBlock for Ccblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        utils::write(stream, self.cc_tx_name, little_endian);
+        utils::write(stream, self.cc_md_unit, little_endian);
+        utils::write(stream, self.cc_md_comment, little_endian);
+        utils::write(stream, self.cc_cc_inverse, little_endian);
+        for address in &self.cc_ref {
+            utils::write(stream, *address, little_endian);
+        }
+        utils::write(stream, self.cc_type.clone() as u8, little_endian);
+        utils::write(stream, self.cc_precision, little_endian);
+        utils::write(stream, self.cc_flags, little_endian);
+        utils::write(stream, self.cc_ref_count, little_endian);
+        utils::write(stream, self.cc_val_count, little_endian);
+        utils::write(stream, self.cc_phy_range_min, little_endian);
+        utils::write(stream, self.cc_phy_range_max, little_endian);
+        for value in &self.cc_val {
+            utils::write(stream, *value, little_endian);
+        }
+    }
+}
+
 impl Block for Ccblock {
     fn new() -> Self {
         Self {
@@ -61,7 +166,7 @@ impl Block for Ccblock {
             cc_val: Vec::new(),
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id[..], "##CC".as_bytes()) {
@@ -140,6 +245,7 @@ impl Block for Ccblock {
 
 #[cfg(test)]
 mod tests {
+    use crate::mdf4::block::WriteBlock;
     use std::mem;
 
     use crate::mdf4::{block::Block, cc_block::Ccblock};
@@ -199,4 +305,14 @@ mod tests {
 
         assert_eq!(96, cc.byte_len());
     }
+
+    #[test]
+    fn write() {
+        let (pos, block) = Ccblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..pos], &stream[..]);
+    }
 }
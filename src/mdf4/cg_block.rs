@@ -1,13 +1,14 @@
 use std::mem;
 
-use super::block::Block;
+use super::block::{Block, WriteBlock};
 use super::block_header::*;
+use crate::storage::Stream;
 use crate::utils;
 
 use super::block::LinkedBlock;
 use super::cn_block::Cnblock;
 use super::mdf4_file::link_extract;
-use super::tx_block;
+use super::utils as mdf4_utils;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Cgblock {
@@ -47,31 +48,33 @@ pub struct Cgblock {
 }
 
 impl LinkedBlock for Cgblock {
-    fn next(&self, stream: &[u8], little_endian: bool) -> Option<Self> {
-        if self.cg_cg_next == 0 {
-            None
-        } else {
-            let (_, block) = Self::read(stream, self.cg_cg_next as usize, little_endian);
-            Some(block)
-        }
-    }
-
-    fn list(&self, stream: &[u8], little_endian: bool) -> Vec<Self> {
-        let mut all = Vec::new();
-
-        let next = self.next(stream, little_endian);
-
-        all.push(self.clone());
-        match next {
-            None => {}
-            Some(block) => all.append(&mut block.list(stream, little_endian)),
-        }
+    const ID: &'static [u8; 4] = b"##CG";
+    const LINKS: u64 = 6;
+    const DATA: u64 = 32;
 
-        all
+    fn link(&self) -> u64 {
+        self.cg_cg_next
     }
 }
 
 impl Cgblock {
+    pub fn create(
+        cg_cg_next: u64,
+        cg_cn_first: u64,
+        cg_md_comment: u64,
+        cg_cycle_count: u64,
+        cg_data_bytes: u32,
+    ) -> Self {
+        let mut block = Self::new();
+        block.cg_cg_next = cg_cg_next;
+        block.cg_cn_first = cg_cn_first;
+        block.cg_md_comment = cg_md_comment;
+        block.cg_cycle_count = cg_cycle_count;
+        block.cg_data_bytes = cg_data_bytes;
+        block.header = BlockHeader::create("##CG", block.byte_len(), 6);
+        block
+    }
+
     #[allow(dead_code)]
     pub fn data_length(&self) -> usize {
         (self.cg_record_id * self.cg_data_bytes as u64) as usize
@@ -82,42 +85,38 @@ impl Cgblock {
     }
 
     pub fn record_size(&self) -> usize {
-        self.cg_data_bytes as usize
+        self.cg_data_bytes as usize + self.cg_inval_bytes as usize
     }
 
-    pub fn first(&self, stream: &[u8], little_endian: bool) -> Cnblock {
-        let (_, block) = Cnblock::read(stream, self.cg_cn_first as usize, little_endian);
-        block
+    pub fn channels(
+        &self,
+        stream: &dyn Stream,
+        little_endian: bool,
+    ) -> Result<Vec<Cnblock>, &'static str> {
+        Cnblock::list(stream, self.cg_cn_first as usize, little_endian)
     }
 
-    pub fn channels(self, stream: &[u8], little_endian: bool) -> Vec<Cnblock> {
-        //let mut ch = Vec::new();
-        let first = self.first(stream, little_endian);
-        // ch.push(first.clone());
-
-        // let next = first;
-        // loop {
-        //     let next = next.next(stream, little_endian);
-
-        //     match next {
-        //         Some(cn) => ch.push(cn.clone()),
-        //         None => break,
-        //     }
-        // }
-        // ch
-
-        first.list(stream, little_endian)
+    pub fn comment(&self, stream: &dyn Stream, little_endian: bool) -> String {
+        mdf4_utils::read_text(stream, self.cg_md_comment, little_endian)
     }
+}
 
-    pub fn comment(&self, stream: &[u8], little_endian: bool) -> String {
-        if self.cg_md_comment == 0 {
-            return "".to_string();
-        }
-
-        let (_, tx_block) =
-            tx_block::Txblock::read(stream, self.cg_md_comment as usize, little_endian);
-
-        tx_block.text()
+impl WriteBlock for Cgblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        utils::write(stream, self.cg_cg_next, little_endian);
+        utils::write(stream, self.cg_cn_first, little_endian);
+        utils::write(stream, self.cg_tx_acq_name, little_endian);
+        utils::write(stream, self.cg_si_acq_source, little_endian);
+        utils::write(stream, self.cg_sr_first, little_endian);
+        utils::write(stream, self.cg_md_comment, little_endian);
+        utils::write(stream, self.cg_record_id, little_endian);
+        utils::write(stream, self.cg_cycle_count, little_endian);
+        utils::write(stream, self.cg_flags, little_endian);
+        utils::write(stream, self.cg_path_separator, little_endian);
+        utils::write(stream, self.cg_reserved, little_endian);
+        utils::write(stream, self.cg_data_bytes, little_endian);
+        utils::write(stream, self.cg_inval_bytes, little_endian);
     }
 }
 
@@ -158,7 +157,7 @@ impl Block for Cgblock {
             cg_inval_bytes: 0,
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##CG".as_bytes()) {
@@ -225,6 +224,7 @@ impl Block for Cgblock {
 
 #[cfg(test)]
 mod tests {
+    use crate::mdf4::block::WriteBlock;
     use crate::mdf4::{block::Block, cg_block::Cgblock};
 
     static RAW: [u8; 104] = [
@@ -262,4 +262,14 @@ mod tests {
 
         assert_eq!(pos, cg.byte_len());
     }
+
+    #[test]
+    fn write() {
+        let (pos, block) = Cgblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..pos], &stream[..]);
+    }
 }
@@ -1,5 +1,6 @@
 use std::mem;
 
+use crate::storage::Stream;
 use crate::utils;
 
 use super::block::Block;
@@ -42,7 +43,7 @@ impl Block for Chblock {
             ch_type: ChannelHierarchyType::Function,
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##CH".as_bytes()) {
@@ -1,15 +1,23 @@
 use std::mem;
 
-use super::block::Block;
+use super::block::{Block, WriteBlock};
 use super::block_header::*;
+use crate::storage::Stream;
 use crate::utils;
 
 use super::block::LinkedBlock;
 use super::mdf4_file::link_extract;
 use super::{
+    cc_block::Ccblock,
+    md_block::Mdblock,
     mdf4_enums::{ChannelType, DataType, SyncType},
+    si_block::Siblock,
     tx_block::Txblock,
+    utils as mdf4_utils,
 };
+use crate::conversion::Conversion;
+use crate::record::{self, Record};
+use crate::signal::Source;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Cnblock {
@@ -83,44 +91,111 @@ pub struct Cnblock {
 }
 
 impl LinkedBlock for Cnblock {
-    fn next(&self, stream: &[u8], little_endian: bool) -> Option<Self> {
-        if self.cn_cn_next == 0 {
-            None
-        } else {
-            let (_, block) = Cnblock::read(stream, self.cn_cn_next as usize, little_endian);
-            Some(block)
-        }
-    }
-
-    fn list(&self, stream: &[u8], little_endian: bool) -> Vec<Self> {
-        let mut all = Vec::new();
+    const ID: &'static [u8; 4] = b"##CN";
+    const LINKS: u64 = 8;
+    const DATA: u64 = 72;
 
-        let next = self.next(stream, little_endian);
+    fn link(&self) -> u64 {
+        self.cn_cn_next
+    }
 
-        all.push(self.clone());
-        match next {
-            None => {}
-            Some(block) => all.append(&mut block.list(stream, little_endian)),
+    /// The attachment and default X links follow the fixed links.
+    fn check_layout(
+        stream: &dyn Stream,
+        data: usize,
+        header: &BlockHeader,
+        little_endian: bool,
+    ) -> Result<(), &'static str> {
+        let flags: u32 = utils::read(stream, little_endian, &mut (data + 12));
+        let attachments: u16 = utils::read(stream, little_endian, &mut (data + 22));
+        let default_x = if flags & (1 << 12) != 0 { 3 } else { 0 };
+        match Self::LINKS + u64::from(attachments) + default_x <= header.link_count {
+            true => Ok(()),
+            false => Err("Block too short for its links"),
         }
-
-        all
     }
 }
 
 impl Cnblock {
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(
+        cn_cn_next: u64,
+        cn_tx_name: u64,
+        cn_cc_conversion: u64,
+        cn_md_unit: u64,
+        cn_md_comment: u64,
+        cn_si_source: u64,
+        channel_type: ChannelType,
+        data_type: DataType,
+        byte_offset: u32,
+        bit_count: u32,
+    ) -> Self {
+        let mut block = Self::new();
+        block.cn_cn_next = cn_cn_next;
+        block.cn_tx_name = cn_tx_name;
+        block.cn_cc_conversion = cn_cc_conversion;
+        block.cn_md_unit = cn_md_unit;
+        block.cn_md_comment = cn_md_comment;
+        block.cn_si_source = cn_si_source;
+        block.sync_type = if matches!(channel_type, ChannelType::Master) {
+            SyncType::Time
+        } else {
+            SyncType::None
+        };
+        block.channel_type = channel_type;
+        block.data_type = data_type;
+        block.byte_offset = byte_offset;
+        block.bit_count = bit_count;
+        block.header = BlockHeader::create("##CN", block.byte_len(), 8);
+        block
+    }
+
     pub fn byte_offset(&self) -> usize {
-        self.bit_offset as usize
+        self.byte_offset as usize
     }
 
+    #[allow(dead_code)]
     pub fn data_type(&self) -> DataType {
         self.data_type
     }
 
     pub fn data_type_len(&self) -> usize {
-        self.data_type.len()
+        match self.data_type {
+            DataType::UnsignedByteLE
+            | DataType::UnsignedByteBE
+            | DataType::SignedLE
+            | DataType::SignedBE => {
+                (self.bit_offset as usize + self.bit_count as usize).div_ceil(8)
+            }
+            _ => self.bit_count as usize / 8,
+        }
     }
 
-    pub fn comment(self, stream: &[u8], little_endian: bool) -> String {
+    pub fn data_type_read(&self) -> record::DataTypeRead {
+        let mut data_type_read = self.data_type.copy_to_data_type_read();
+        if self.bit_count == 32 && matches!(data_type_read.data_type, record::DataType::Float64) {
+            data_type_read.data_type = record::DataType::Float32;
+        }
+        data_type_read
+    }
+
+    /// Decodes this channel's value from a single record.
+    pub fn decode(&self, record: &[u8]) -> Record {
+        let start = self.byte_offset();
+        let raw = &record[start..start + self.data_type_len()];
+
+        match self.data_type {
+            DataType::UnsignedByteLE
+            | DataType::UnsignedByteBE
+            | DataType::SignedLE
+            | DataType::SignedBE => {
+                Record::new(raw, self.data_type_read()).mask(self.bit_offset as u32, self.bit_count)
+            }
+            _ => Record::new(raw, self.data_type_read()),
+        }
+    }
+
+    pub fn name(&self, stream: &dyn Stream, little_endian: bool) -> String {
         let mut name = "".to_string();
 
         if matches!(self.channel_type, ChannelType::Master) {
@@ -134,9 +209,93 @@ impl Cnblock {
         name
     }
 
+    pub fn unit(&self, stream: &dyn Stream, little_endian: bool) -> String {
+        mdf4_utils::read_text(stream, self.cn_md_unit, little_endian)
+    }
+
+    /// The `<display>` name of an XML comment, empty for plain comments.
+    pub fn display_name(&self, stream: &dyn Stream, little_endian: bool) -> String {
+        let position = self.cn_md_comment as usize;
+        if position == 0 || *stream.bytes(position, 4) != *b"##MD" {
+            return String::new();
+        }
+
+        let (_pos, md) = Mdblock::read(stream, position, little_endian);
+        utils::extract_name(md.text().as_bytes())
+    }
+
+    pub fn comment(&self, stream: &dyn Stream, little_endian: bool) -> String {
+        mdf4_utils::read_text(stream, self.cn_md_comment, little_endian)
+    }
+
+    pub fn conversion(&self, stream: &dyn Stream, little_endian: bool) -> Option<Conversion> {
+        if self.cn_cc_conversion == 0 {
+            return None;
+        }
+
+        let (_pos, cc) = Ccblock::read(stream, self.cn_cc_conversion as usize, little_endian);
+        cc.conversion(stream, little_endian)
+    }
+
+    pub fn has_unsupported_conversion(&self, stream: &dyn Stream, little_endian: bool) -> bool {
+        if self.cn_cc_conversion == 0 {
+            return false;
+        }
+
+        let (_pos, cc) = Ccblock::read(stream, self.cn_cc_conversion as usize, little_endian);
+        cc.is_unsupported(stream, little_endian)
+    }
+
+    pub fn source(&self, stream: &dyn Stream, little_endian: bool) -> Option<Source> {
+        if self.cn_si_source == 0 {
+            return None;
+        }
+
+        let (_pos, si) = Siblock::read(stream, self.cn_si_source as usize, little_endian);
+        Some(si.source(stream, little_endian))
+    }
+
     pub fn channel_type(&self) -> ChannelType {
         self.channel_type.clone()
     }
+
+    pub fn sync_type(&self) -> SyncType {
+        self.sync_type.clone()
+    }
+}
+
+impl WriteBlock for Cnblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        utils::write(stream, self.cn_cn_next, little_endian);
+        utils::write(stream, self.cn_composition, little_endian);
+        utils::write(stream, self.cn_tx_name, little_endian);
+        utils::write(stream, self.cn_si_source, little_endian);
+        utils::write(stream, self.cn_cc_conversion, little_endian);
+        utils::write(stream, self.cn_data, little_endian);
+        utils::write(stream, self.cn_md_unit, little_endian);
+        utils::write(stream, self.cn_md_comment, little_endian);
+        for address in self.cn_at_reference.iter().chain(&self.cn_default_x) {
+            utils::write(stream, *address, little_endian);
+        }
+        utils::write(stream, self.channel_type.clone() as u8, little_endian);
+        utils::write(stream, self.sync_type.clone() as u8, little_endian);
+        utils::write(stream, self.data_type as u8, little_endian);
+        utils::write(stream, self.bit_offset, little_endian);
+        utils::write(stream, self.byte_offset, little_endian);
+        utils::write(stream, self.bit_count, little_endian);
+        utils::write(stream, self.flags, little_endian);
+        utils::write(stream, self.pos_invalidation_bit, little_endian);
+        utils::write(stream, self.precision, little_endian);
+        utils::write(stream, self.reserved1, little_endian);
+        utils::write(stream, self.attachment_nr, little_endian);
+        utils::write(stream, self.min_raw_value, little_endian);
+        utils::write(stream, self.max_raw_value, little_endian);
+        utils::write(stream, self.lower_limit, little_endian);
+        utils::write(stream, self.upper_limit, little_endian);
+        utils::write(stream, self.lower_ext_limit, little_endian);
+        utils::write(stream, self.upper_ext_limit, little_endian);
+    }
 }
 
 impl Block for Cnblock {
@@ -204,7 +363,7 @@ impl Block for Cnblock {
             upper_ext_limit: 0.0,
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##CN".as_bytes()) {
@@ -246,7 +405,7 @@ impl Block for Cnblock {
             cn_at_reference.push(addresses.remove(0));
         }
 
-        let default_x = flags == 1 << 12;
+        let default_x = flags & (1 << 12) != 0;
 
         let mut cn_default_x = Vec::with_capacity(3);
         if default_x {
@@ -331,6 +490,7 @@ impl Block for Cnblock {
 
 #[cfg(test)]
 mod tests {
+    use crate::mdf4::block::WriteBlock;
     use std::mem;
 
     use crate::mdf4::{block::Block, cn_block::Cnblock};
@@ -396,4 +556,14 @@ mod tests {
         assert_eq!(8, mem::size_of_val(&cn.upper_ext_limit));
         assert_eq!(160, cn.byte_len());
     }
+
+    #[test]
+    fn write() {
+        let (pos, block) = Cnblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..pos], &stream[..]);
+    }
 }
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use super::{
     block::{Block, DataBlock, LinkedBlock},
     block_header::BlockHeader,
@@ -5,33 +7,120 @@ use super::{
     dt_block::Dtblock,
     dz_block::Dzblock,
 };
+use crate::storage::Stream;
+use crate::utils::Visited;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
+/// A block holding records. Data lists are resolved to the blocks they link
+/// by [`DataBlockType::positions`] when the file is opened.
 pub enum DataBlockType {
     Block(Dtblock),
     BlockComp(Dzblock),
-    List(Dlblock),
 }
 
 impl DataBlockType {
-    pub fn data_array(&self, stream: &[u8], little_endian: bool) -> Vec<u8> {
+    pub fn data_array(&self, stream: &dyn Stream, little_endian: bool) -> Vec<u8> {
         match self {
             Self::Block(block) => block.data_array(stream, little_endian),
             Self::BlockComp(block) => block.data_array(stream, little_endian),
-            Self::List(block) => {
-                let dl_list = block.list(stream, little_endian);
+        }
+    }
 
-                let mut data = Vec::new();
+    /// Positions of the data blocks behind the data link at `position`,
+    /// following data lists and the lists within them. An error if the
+    /// lists link back to one another or out of the file.
+    pub fn positions(
+        stream: &dyn Stream,
+        position: usize,
+        little_endian: bool,
+    ) -> Result<Vec<usize>, &'static str> {
+        let mut visited = Visited::create(stream);
+        let mut positions = Vec::new();
+        let mut pending = vec![position];
 
-                for dl in dl_list {
-                    data.append(&mut dl.data_array(stream, little_endian));
+        while let Some(position) = pending.pop() {
+            if position == 0 {
+                continue;
+            }
+            let header = BlockHeader::check(stream, position, little_endian)?;
+            match &header.id {
+                b"##DL" => {}
+                b"##DT" | b"##DZ" => {
+                    positions.push(position);
+                    continue;
                 }
+                _ => return Err("Link to a block of the wrong type"),
+            }
+
+            let mut links = Vec::new();
+            let mut next = position;
+            while next != 0 {
+                visited.visit(next)?;
+                let block = Dlblock::checked(stream, next, little_endian)?;
+                links.extend(block.data_blocks().iter().map(|&x| x as usize));
+                next = block.link() as usize;
+            }
+            pending.extend(links.into_iter().rev());
+        }
+
+        Ok(positions)
+    }
 
-                data
+    /// Offsets of the data of the blocks behind the data link at `position`
+    /// in their data joined together, in the order of
+    /// [`DataBlockType::positions`]. Lists within lists are not followed.
+    pub fn offsets(
+        stream: &dyn Stream,
+        position: usize,
+        little_endian: bool,
+    ) -> Result<Vec<usize>, &'static str> {
+        if position == 0 {
+            return Ok(Vec::new());
+        }
+        if BlockHeader::check(stream, position, little_endian)?.id != *b"##DL" {
+            return Ok(vec![0]);
+        }
+
+        let mut offsets = Vec::new();
+        for list in Dlblock::list(stream, position, little_endian)? {
+            offsets.append(&mut list.offsets(offsets.len()));
+        }
+        Ok(offsets)
+    }
+
+    /// The data of the blocks at `positions` joined together.
+    pub fn read_all(stream: &dyn Stream, positions: &[usize], little_endian: bool) -> Vec<u8> {
+        #[cfg(feature = "parallel")]
+        let positions = positions.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let positions = positions.iter();
+
+        let blocks: Vec<Vec<u8>> = positions
+            .map(|&position| match &stream.bytes(position, 4)[..] {
+                b"##DT" => Dtblock::data(stream, position, little_endian).into_owned(),
+                _ => Self::read(stream, position, little_endian).data_array(stream, little_endian),
+            })
+            .collect();
+        blocks.concat()
+    }
+
+    /// Like [`DataBlockType::read_all`], but borrowing the data of a single
+    /// uncompressed block from `stream`.
+    pub fn data<'a>(
+        stream: &'a dyn Stream,
+        positions: &[usize],
+        little_endian: bool,
+    ) -> Cow<'a, [u8]> {
+        match positions {
+            [position] if *stream.bytes(*position, 4) == *b"##DT" => {
+                Dtblock::data(stream, *position, little_endian)
             }
+            _ => Cow::Owned(Self::read_all(stream, positions, little_endian)),
         }
     }
 
-    pub fn read(stream: &[u8], position: usize, little_endian: bool) -> Self {
+    pub fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> Self {
         let (_pos, header) = BlockHeader::read(stream, position, little_endian);
 
         let data_block = match std::str::from_utf8(&header.id).unwrap() {
@@ -43,10 +132,6 @@ impl DataBlockType {
                 let (_pos, block) = Dzblock::read(stream, position, little_endian);
                 Self::BlockComp(block)
             }
-            "##DL" => {
-                let (_pos, block) = Dlblock::read(stream, position, little_endian);
-                Self::List(block)
-            }
             "##HL" => todo!(),
             _ => panic!("Error: wrong block type for data block"),
         };
@@ -1,10 +1,10 @@
 use std::mem;
 
-use super::block::{Block, LinkedBlock};
+use super::block::{Block, LinkedBlock, WriteBlock};
 use super::block_header::*;
 use super::cg_block::Cgblock;
-use super::data_block::DataBlockType;
 use super::mdf4_file::link_extract;
+use crate::storage::Stream;
 use crate::utils;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -24,85 +24,48 @@ pub struct Dgblock {
 }
 
 impl LinkedBlock for Dgblock {
-    fn next(&self, stream: &[u8], little_endian: bool) -> Option<Self> {
-        if self.dg_dg_next == 0 {
-            None
-        } else {
-            let (_pos, block) = Self::read(stream, self.dg_dg_next as usize, little_endian);
-            Some(block)
-        }
-    }
-    fn list(&self, stream: &[u8], little_endian: bool) -> Vec<Self> {
-        let mut all = Vec::new();
-
-        let next = self.next(stream, little_endian);
-
-        all.push(self.clone());
-        match next {
-            None => {}
-            Some(block) => all.append(&mut block.list(stream, little_endian)),
-        }
-
-        all
-
-        // let next_block = self;
-
-        // all.push(self.clone());
-        // loop {
-        //     let next_block = next_block.next(stream, little_endian);
-
-        //     match next_block {
-        //         Some(block) => all.push(block.clone()),
-        //         None => break,
-        //     }
-        // }
+    const ID: &'static [u8; 4] = b"##DG";
+    const LINKS: u64 = 4;
+    const DATA: u64 = 8;
 
-        // all
+    fn link(&self) -> u64 {
+        self.dg_dg_next
     }
 }
 
 impl Dgblock {
-    pub fn first(&self, stream: &[u8], little_endian: bool) -> Cgblock {
-        let (_, block) = Cgblock::read(stream, self.dg_cg_first as usize, little_endian);
+    pub fn create(dg_dg_next: u64, dg_cg_first: u64, dg_data: u64, dg_md_comment: u64) -> Self {
+        let mut block = Self::new();
+        block.dg_dg_next = dg_dg_next;
+        block.dg_cg_first = dg_cg_first;
+        block.dg_data = dg_data;
+        block.dg_md_comment = dg_md_comment;
+        block.header = BlockHeader::create("##DG", block.byte_len(), 4);
         block
     }
 
-    pub fn data_location(&self) -> usize {
-        self.dg_data as usize
+    pub fn channel_groups(
+        &self,
+        stream: &dyn Stream,
+        little_endian: bool,
+    ) -> Result<Vec<Cgblock>, &'static str> {
+        Cgblock::list(stream, self.dg_cg_first as usize, little_endian)
     }
 
-    pub fn read_data(&self, stream: &[u8], little_endian: bool) -> Vec<u8> {
-        let data_block = DataBlockType::read(stream, self.data_location(), little_endian);
-        data_block.data_array(stream, little_endian)
+    pub fn data_location(&self) -> usize {
+        self.dg_data as usize
     }
+}
 
-    // pub fn read_all(stream: &[u8], position: usize, little_endian: bool) -> Vec<Self> {
-    //     let mut all = Vec::new();
-    //     let mut next_dg = position;
-
-    //     while next_dg != 0 {
-    //         let (_pos, dg_block) = Dgblock::read(stream, next_dg, little_endian);
-    //         next_dg = dg_block.dg_dg_next as usize;
-    //         all.push(dg_block);
-    //     }
-
-    //     all
-    // }
-
-    pub fn read_channel_groups(self, stream: &[u8], little_endian: bool) -> Vec<Cgblock> {
-        let mut channel_grps = Vec::new();
-        let next = self.first(stream, little_endian); //dg_cg_first as usize;
-        channel_grps.push(next.clone());
-
-        loop {
-            let next = next.next(stream, little_endian);
-
-            match next {
-                Some(dg) => channel_grps.push(dg.clone()),
-                None => break,
-            }
-        }
-        channel_grps
+impl WriteBlock for Dgblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        utils::write(stream, self.dg_dg_next, little_endian);
+        utils::write(stream, self.dg_cg_first, little_endian);
+        utils::write(stream, self.dg_data, little_endian);
+        utils::write(stream, self.dg_md_comment, little_endian);
+        utils::write(stream, self.dg_rec_id_size, little_endian);
+        utils::write(stream, self.dg_reserved, little_endian);
     }
 }
 
@@ -129,7 +92,7 @@ impl Block for Dgblock {
             dg_reserved: [0_u8; 7],
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
         let (mut pos, mut address) = link_extract(stream, pos, little_endian, header.link_count);
 
@@ -156,11 +119,13 @@ impl Block for Dgblock {
     }
 
     fn byte_len(&self) -> usize {
-        mem::size_of_val(&self.dg_dg_next)
+        self.header.byte_len()
+            + mem::size_of_val(&self.dg_dg_next)
             + mem::size_of_val(&self.dg_cg_first)
             + mem::size_of_val(&self.dg_data)
             + mem::size_of_val(&self.dg_md_comment)
             + mem::size_of_val(&self.dg_rec_id_size)
+            + mem::size_of_val(&self.dg_reserved)
     }
 }
 
@@ -168,6 +133,8 @@ impl Block for Dgblock {
 mod tests {
     use super::Dgblock;
     use crate::mdf4::block::Block;
+    use crate::mdf4::block::LinkedBlock;
+    use crate::mdf4::block::WriteBlock;
 
     static RAW: [u8; 64] = [
         0x23, 0x23, 0x44, 0x47, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -189,4 +156,41 @@ mod tests {
         assert_eq!(0, dg.dg_rec_id_size);
         assert_eq!([0_u8; 7], dg.dg_reserved);
     }
+
+    #[test]
+    fn byte_len() {
+        let (pos, dg) = Dgblock::read(&RAW, 0, true);
+
+        assert_eq!(pos, dg.byte_len());
+    }
+
+    #[test]
+    fn write() {
+        let (pos, block) = Dgblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..pos], &stream[..]);
+    }
+
+    #[test]
+    fn list() {
+        assert_eq!(
+            Dgblock::list(&[RAW, RAW].concat(), 64, true),
+            Err("Link past the end of the file")
+        );
+
+        let mut stream = [RAW, RAW, RAW].concat();
+        stream[88..96].copy_from_slice(&128_u64.to_le_bytes());
+        stream[152..160].copy_from_slice(&0_u64.to_le_bytes());
+        assert_eq!(Dgblock::list(&stream, 64, true).unwrap().len(), 2);
+
+        // Back to the first block
+        stream[152..160].copy_from_slice(&64_u64.to_le_bytes());
+        assert_eq!(
+            Dgblock::list(&stream, 64, true),
+            Err("Linked blocks form a cycle")
+        );
+    }
 }
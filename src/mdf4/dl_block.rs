@@ -1,3 +1,6 @@
+use std::mem;
+
+use crate::storage::Stream;
 use crate::utils;
 
 use super::block::{Block, DataBlock, LinkedBlock};
@@ -18,7 +21,7 @@ pub struct Dlblock {
 }
 
 impl DataBlock for Dlblock {
-    fn data_array(&self, stream: &[u8], little_endian: bool) -> Vec<u8> {
+    fn data_array(&self, stream: &dyn Stream, little_endian: bool) -> Vec<u8> {
         let mut data_list = Vec::new();
 
         for data_block in &self.dl_data {
@@ -30,31 +33,52 @@ impl DataBlock for Dlblock {
     }
 }
 
-impl LinkedBlock for Dlblock {
-    fn next(&self, stream: &[u8], little_endian: bool) -> Option<Self>
-    where
-        Self: std::marker::Sized,
-    {
-        if self.dl_dl_next == 0 {
-            None
+impl Dlblock {
+    /// Links to the data blocks of this list.
+    pub fn data_blocks(&self) -> &[u64] {
+        &self.dl_data
+    }
+
+    /// Offset of the data of each block in the data of the whole list. For
+    /// blocks of equal length `index` counts the blocks of the lists before
+    /// this one.
+    pub fn offsets(&self, index: usize) -> Vec<usize> {
+        if self.dl_flags & (1 << 0) != 0 {
+            (index..index + self.dl_data.len())
+                .map(|i| i * self.dl_equal_length as usize)
+                .collect()
         } else {
-            let (_pos, block) = Self::read(stream, self.dl_dl_next as usize, little_endian);
-            Some(block)
+            self.dl_offset.iter().map(|&x| x as usize).collect()
         }
     }
+}
 
-    fn list(&self, stream: &[u8], little_endian: bool) -> Vec<Self> {
-        let mut all = Vec::new();
+impl LinkedBlock for Dlblock {
+    const ID: &'static [u8; 4] = b"##DL";
+    const LINKS: u64 = 1;
+    const DATA: u64 = 8;
 
-        let next = self.next(stream, little_endian);
+    fn link(&self) -> u64 {
+        self.dl_dl_next
+    }
 
-        all.push(self.clone());
-        match next {
-            None => {}
-            Some(block) => all.append(&mut block.list(stream, little_endian)),
+    /// A common block length or an offset for each block follows the count.
+    fn check_layout(
+        stream: &dyn Stream,
+        data: usize,
+        header: &BlockHeader,
+        little_endian: bool,
+    ) -> Result<(), &'static str> {
+        let flags: u8 = utils::read(stream, little_endian, &mut { data });
+        let count: u32 = utils::read(stream, little_endian, &mut (data + 4));
+        let lengths = match flags & 1 != 0 {
+            true => 8,
+            false => 8 * u64::from(count),
+        };
+        match Self::DATA + lengths <= header.data_length() {
+            true => Ok(()),
+            false => Err("Block too short for its data"),
         }
-
-        all
     }
 }
 
@@ -83,7 +107,7 @@ impl Block for Dlblock {
             dl_offset: Vec::new(),
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##DL".as_bytes()) {
@@ -104,8 +128,10 @@ impl Block for Dlblock {
             0
         };
         let mut dl_offset = Vec::new();
-        for _i in 0..dl_count {
-            dl_offset.push(utils::read(stream, little_endian, &mut pos));
+        if !equal_length_flag {
+            for _i in 0..dl_count {
+                dl_offset.push(utils::read(stream, little_endian, &mut pos));
+            }
         }
 
         let dl_dl_next = address.remove(0);
@@ -127,7 +153,19 @@ impl Block for Dlblock {
     }
 
     fn byte_len(&self) -> usize {
-        todo!()
+        let mut length = self.header.byte_len()
+            + mem::size_of_val(&self.dl_dl_next)
+            + mem::size_of::<u64>() * self.dl_data.len()
+            + mem::size_of_val(&self.dl_flags)
+            + mem::size_of_val(&self.dl_reserved)
+            + mem::size_of_val(&self.dl_count)
+            + mem::size_of::<u64>() * self.dl_offset.len();
+
+        if self.dl_flags & (1 << 0) != 0 {
+            length += mem::size_of_val(&self.dl_equal_length);
+        }
+
+        length
     }
 }
 
@@ -366,4 +404,11 @@ mod tests {
         assert_eq!(pos, 88);
         assert_eq!(dl.dl_count, 3);
     }
+
+    #[test]
+    fn byte_len() {
+        let (pos, dl) = Dlblock::read(&RAW, 0, true);
+
+        assert_eq!(pos, dl.byte_len());
+    }
 }
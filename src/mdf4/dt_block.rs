@@ -1,5 +1,8 @@
-use super::block::{Block, DataBlock};
+use std::borrow::Cow;
+
+use super::block::{Block, DataBlock, WriteBlock};
 use super::block_header::*;
+use crate::storage::Stream;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Dtblock {
@@ -7,8 +10,31 @@ pub struct Dtblock {
     dt_data: Vec<u8>,
 }
 
+impl Dtblock {
+    pub fn create(data: Vec<u8>) -> Self {
+        Self {
+            header: BlockHeader::create("##DT", 24 + data.len(), 0),
+            dt_data: data,
+        }
+    }
+
+    /// The data of the block at `position`, borrowed from `stream` where it
+    /// keeps its bytes.
+    pub fn data(stream: &dyn Stream, position: usize, little_endian: bool) -> Cow<'_, [u8]> {
+        let (pos, header) = BlockHeader::read(stream, position, little_endian);
+        stream.data(pos, header.length as usize - header.byte_len())
+    }
+}
+
+impl WriteBlock for Dtblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        stream.extend_from_slice(&self.dt_data);
+    }
+}
+
 impl DataBlock for Dtblock {
-    fn data_array(&self, _stream: &[u8], _little_endian: bool) -> Vec<u8> {
+    fn data_array(&self, _stream: &dyn Stream, _little_endian: bool) -> Vec<u8> {
         self.dt_data.clone()
     }
 }
@@ -28,10 +54,9 @@ impl Block for Dtblock {
         }
     }
 
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
-
-        let dt_data = stream[pos..(pos + header.length as usize - header.byte_len())].to_vec();
+        let dt_data = Self::data(stream, position, little_endian).into_owned();
 
         let pos = pos + dt_data.len();
 
@@ -39,13 +64,14 @@ impl Block for Dtblock {
     }
 
     fn byte_len(&self) -> usize {
-        todo!()
+        self.header.byte_len() + self.dt_data.len()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::mdf4::block::Block;
+    use crate::mdf4::block::WriteBlock;
 
     use super::Dtblock;
 
@@ -135,4 +161,21 @@ mod tests {
 
         assert_eq!(pos, 1140);
     }
+
+    #[test]
+    fn byte_len() {
+        let (pos, dt) = Dtblock::read(&RAW, 0, true);
+
+        assert_eq!(pos, dt.byte_len());
+    }
+
+    #[test]
+    fn write() {
+        let (pos, block) = Dtblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..pos], &stream[..]);
+    }
 }
@@ -1,5 +1,7 @@
 use std::io::Read;
+use std::mem;
 
+use crate::storage::Stream;
 use crate::utils;
 
 use super::block::{Block, DataBlock};
@@ -7,6 +9,8 @@ use super::block_header::*;
 use super::mdf4_enums::ZipType;
 
 use flate2::read::ZlibDecoder;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Dzblock {
@@ -27,22 +31,50 @@ pub struct Dzblock {
 }
 
 impl DataBlock for Dzblock {
-    fn data_array(&self, _stream: &[u8], _little_endian: bool) -> Vec<u8> {
+    fn data_array(&self, _stream: &dyn Stream, _little_endian: bool) -> Vec<u8> {
         let mut zlib_decoder = ZlibDecoder::new(&self.dz_data[..]);
         let mut decompressed_data = vec![0u8; self.dz_org_data_length as usize];
- 
+
         let decompress_result = zlib_decoder.read_exact(&mut decompressed_data);
         match decompress_result {
-            Ok(_) => {},
+            Ok(_) => {}
             Err(e) => {
                 panic!("{:?}", e);
             }
         }
 
-        decompressed_data
+        match self.dz_zip_type {
+            ZipType::Deflate => decompressed_data,
+            ZipType::TransposeDeflate => {
+                transpose(&decompressed_data, self.dz_zip_parameter as usize)
+            }
+        }
     }
 }
 
+/// Restores the rows of `data` stored column by column, with `columns`
+/// bytes per row. The bytes of an incomplete last row are not transposed.
+fn transpose(data: &[u8], columns: usize) -> Vec<u8> {
+    if columns == 0 {
+        return data.to_vec();
+    }
+    let rows = data.len() / columns;
+    let mut records = data.to_vec();
+
+    #[cfg(feature = "parallel")]
+    let chunks = records[..rows * columns].par_chunks_mut(columns);
+    #[cfg(not(feature = "parallel"))]
+    let chunks = records[..rows * columns].chunks_mut(columns);
+
+    chunks.enumerate().for_each(|(row, record)| {
+        for (column, byte) in record.iter_mut().enumerate() {
+            *byte = data[column * rows + row];
+        }
+    });
+
+    records
+}
+
 impl Block for Dzblock {
     fn new() -> Self {
         Self {
@@ -68,7 +100,7 @@ impl Block for Dzblock {
             dz_data: Vec::new(),
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (mut pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##DZ".as_bytes()) {
@@ -81,7 +113,7 @@ impl Block for Dzblock {
         let dz_zip_parameter = utils::read(stream, little_endian, &mut pos);
         let dz_org_data_length = utils::read(stream, little_endian, &mut pos);
         let dz_data_length = utils::read(stream, little_endian, &mut pos);
-        let dz_data = stream[pos..pos + dz_data_length as usize].to_vec();
+        let dz_data = stream.data(pos, dz_data_length as usize).into_owned();
 
         pos += dz_data.len();
 
@@ -101,6 +133,25 @@ impl Block for Dzblock {
     }
 
     fn byte_len(&self) -> usize {
-        todo!()
+        self.header.byte_len()
+            + mem::size_of_val(&self.dz_org_block_type)
+            + mem::size_of_val(&self.dz_zip_type)
+            + mem::size_of_val(&self.dz_reserved)
+            + mem::size_of_val(&self.dz_zip_parameter)
+            + mem::size_of_val(&self.dz_org_data_length)
+            + mem::size_of_val(&self.dz_data_length)
+            + self.dz_data.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::transpose;
+
+    #[test]
+    fn transposed() {
+        let data = [1, 4, 7, 2, 5, 8, 3, 6, 9, 10];
+        assert_eq!(transpose(&data, 3), [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(transpose(&data, 0), data);
     }
 }
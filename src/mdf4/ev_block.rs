@@ -1,10 +1,16 @@
+use std::mem;
+
+use crate::event::Event;
+use crate::storage::Stream;
 use crate::utils;
 
-use super::block::Block;
+use super::block::{Block, WriteBlock};
 use super::block_header::*;
 use super::mdf4_enums::{EventCause, EventSyncType, EventType, RangeType};
 use super::mdf4_file::link_extract;
+use super::utils as mdf4_utils;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq)]
 pub struct EVBlock {
     header: BlockHeader,
@@ -45,6 +51,64 @@ pub struct EVBlock {
     ev_sync_factor: f64,
 }
 
+impl EVBlock {
+    /// A trigger event at `time` seconds, stored as base value 1 and factor `time`.
+    pub fn create(ev_ev_next: u64, ev_tx_name: u64, ev_md_comment: u64, time: f64) -> Self {
+        let mut block = Self::new();
+        block.ev_ev_next = ev_ev_next;
+        block.ev_tx_name = ev_tx_name;
+        block.ev_md_comment = ev_md_comment;
+        block.ev_type = EventType::Trigger;
+        block.ev_sync_type = EventSyncType::Seconds;
+        block.ev_cause = EventCause::Tool;
+        block.ev_sync_base_value = 1;
+        block.ev_sync_factor = time;
+        block.header = BlockHeader::create("##EV", block.byte_len(), 5);
+        block
+    }
+
+    pub fn next(&self) -> u64 {
+        self.ev_ev_next
+    }
+
+    pub fn event(&self, stream: &dyn Stream, little_endian: bool) -> Option<Event> {
+        if self.ev_sync_type != EventSyncType::Seconds {
+            return None;
+        }
+
+        Some(Event::new(
+            self.ev_sync_base_value as f64 * self.ev_sync_factor,
+            &mdf4_utils::read_text(stream, self.ev_tx_name, little_endian),
+            &mdf4_utils::read_text(stream, self.ev_md_comment, little_endian),
+        ))
+    }
+}
+
+impl WriteBlock for EVBlock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        utils::write(stream, self.ev_ev_next, little_endian);
+        utils::write(stream, self.ev_ev_parent, little_endian);
+        utils::write(stream, self.ev_ev_range, little_endian);
+        utils::write(stream, self.ev_tx_name, little_endian);
+        utils::write(stream, self.ev_md_comment, little_endian);
+        for link in self.ev_scope.iter().chain(&self.ev_at_reference) {
+            utils::write(stream, *link, little_endian);
+        }
+        utils::write(stream, self.ev_type.clone() as u8, little_endian);
+        utils::write(stream, self.ev_sync_type.clone() as u8, little_endian);
+        utils::write(stream, self.ev_range_type.clone() as u8, little_endian);
+        utils::write(stream, self.ev_cause.clone() as u8, little_endian);
+        utils::write(stream, self.ev_flags, little_endian);
+        utils::write(stream, self.ev_reserved, little_endian);
+        utils::write(stream, self.ev_scope_count, little_endian);
+        utils::write(stream, self.ev_attachment_count, little_endian);
+        utils::write(stream, self.ev_creator_index, little_endian);
+        utils::write(stream, self.ev_sync_base_value, little_endian);
+        utils::write(stream, self.ev_sync_factor, little_endian);
+    }
+}
+
 impl Block for EVBlock {
     fn new() -> Self {
         Self {
@@ -92,7 +156,7 @@ impl Block for EVBlock {
             ev_sync_factor: 0_f64,
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
         let (mut pos, mut address) = link_extract(stream, pos, little_endian, header.link_count);
 
@@ -151,6 +215,24 @@ impl Block for EVBlock {
     }
 
     fn byte_len(&self) -> usize {
-        todo!()
+        self.header.byte_len()
+            + mem::size_of_val(&self.ev_ev_next)
+            + mem::size_of_val(&self.ev_ev_parent)
+            + mem::size_of_val(&self.ev_ev_range)
+            + mem::size_of_val(&self.ev_tx_name)
+            + mem::size_of_val(&self.ev_md_comment)
+            + mem::size_of::<u64>() * self.ev_scope.len()
+            + mem::size_of::<u64>() * self.ev_at_reference.len()
+            + mem::size_of_val(&self.ev_type)
+            + mem::size_of_val(&self.ev_sync_type)
+            + mem::size_of_val(&self.ev_range_type)
+            + mem::size_of_val(&self.ev_cause)
+            + mem::size_of_val(&self.ev_flags)
+            + mem::size_of_val(&self.ev_reserved)
+            + mem::size_of_val(&self.ev_scope_count)
+            + mem::size_of_val(&self.ev_attachment_count)
+            + mem::size_of_val(&self.ev_creator_index)
+            + mem::size_of_val(&self.ev_sync_base_value)
+            + mem::size_of_val(&self.ev_sync_factor)
     }
 }
@@ -1,12 +1,13 @@
 use std::mem;
 
-use super::block::Block;
+use super::block::{Block, WriteBlock};
 use super::block_header::*;
 use super::mdf4_file::link_extract;
+use crate::storage::Stream;
 use crate::utils;
 
 #[derive(Debug, Clone, PartialEq)]
-struct Fhblock {
+pub struct Fhblock {
     header: BlockHeader,
 
     fh_fh_next: u64,
@@ -23,6 +24,29 @@ struct Fhblock {
 
     fh_reserved: [u8; 3],
 }
+impl Fhblock {
+    pub fn create(fh_md_comment: u64, fh_time_ns: u64) -> Self {
+        let mut block = Self::new();
+        block.fh_md_comment = fh_md_comment;
+        block.fh_time_ns = fh_time_ns;
+        block.header = BlockHeader::create("##FH", block.byte_len(), 2);
+        block
+    }
+}
+
+impl WriteBlock for Fhblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        utils::write(stream, self.fh_fh_next, little_endian);
+        utils::write(stream, self.fh_md_comment, little_endian);
+        utils::write(stream, self.fh_time_ns, little_endian);
+        utils::write(stream, self.fh_tz_offset_min, little_endian);
+        utils::write(stream, self.fh_dst_offset_min, little_endian);
+        utils::write(stream, self.fh_time_flags, little_endian);
+        utils::write(stream, self.fh_reserved, little_endian);
+    }
+}
+
 impl Block for Fhblock {
     fn new() -> Self {
         Self {
@@ -48,7 +72,7 @@ impl Block for Fhblock {
             fh_reserved: [0_u8; 3],
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##FH".as_bytes()) {
@@ -95,6 +119,7 @@ impl Block for Fhblock {
 
 #[cfg(test)]
 mod tests {
+    use crate::mdf4::block::WriteBlock;
     use crate::mdf4::{block::Block, fh_block::Fhblock};
 
     static RAW: [u8; 56] = [
@@ -123,4 +148,14 @@ mod tests {
 
         assert_eq!(pos, fh.byte_len());
     }
+
+    #[test]
+    fn write() {
+        let (pos, block) = Fhblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..pos], &stream[..]);
+    }
 }
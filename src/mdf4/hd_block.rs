@@ -1,11 +1,19 @@
 use std::mem;
 
-use super::{block::Block, block_header::*};
+use super::{
+    block::{Block, LinkedBlock, WriteBlock},
+    block_header::*,
+};
+use crate::storage::Stream;
 use crate::utils;
 
+use super::at_block::Atblock;
 use super::dg_block::Dgblock;
-use super::md_block;
+use super::ev_block::EVBlock;
 use super::mdf4_file::link_extract;
+use super::utils as mdf4_utils;
+use crate::attachment::Attachment;
+use crate::event::Event;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Hdblock {
@@ -43,24 +51,83 @@ pub struct Hdblock {
 }
 
 impl Hdblock {
-    pub fn first_data_group(&self, stream: &[u8], little_endian: bool) -> Dgblock {
-        if self.hd_dg_first == 0 {
-            panic!("No data group found!");
-        }
-
-        let (_, block) = Dgblock::read(stream, self.hd_dg_first as usize, little_endian);
+    pub fn create(
+        hd_dg_first: u64,
+        hd_fh_first: u64,
+        hd_at_first: u64,
+        hd_ev_first: u64,
+        hd_md_comment: u64,
+        hd_start_time_ns: u64,
+    ) -> Self {
+        let mut block = Self::new();
+        block.hd_dg_first = hd_dg_first;
+        block.hd_fh_first = hd_fh_first;
+        block.hd_at_first = hd_at_first;
+        block.hd_ev_first = hd_ev_first;
+        block.hd_md_comment = hd_md_comment;
+        block.hd_start_time_ns = hd_start_time_ns;
+        block.header = BlockHeader::create("##HD", block.byte_len(), 6);
         block
     }
 
-    pub fn comment(&self, stream: &[u8], little_endian: bool) -> String {
-        if self.hd_md_comment == 0 {
-            return "".to_string();
+    pub fn data_groups(
+        &self,
+        stream: &dyn Stream,
+        little_endian: bool,
+    ) -> Result<Vec<Dgblock>, &'static str> {
+        Dgblock::list(stream, self.hd_dg_first as usize, little_endian)
+    }
+
+    pub fn comment(&self, stream: &dyn Stream, little_endian: bool) -> String {
+        mdf4_utils::read_text(stream, self.hd_md_comment, little_endian)
+    }
+
+    pub fn start_time_ns(&self) -> u64 {
+        self.hd_start_time_ns
+    }
+
+    /// Events synchronised in seconds, others are skipped.
+    pub fn events(&self, stream: &dyn Stream, little_endian: bool) -> Vec<Event> {
+        let mut events = Vec::new();
+        let mut next = self.hd_ev_first;
+        while next != 0 {
+            let (_, ev) = EVBlock::read(stream, next as usize, little_endian);
+            events.extend(ev.event(stream, little_endian));
+            next = ev.next();
         }
+        events
+    }
 
-        let (_, md_block) =
-            md_block::Mdblock::read(stream, self.hd_md_comment as usize, little_endian);
+    pub fn attachments(&self, stream: &dyn Stream, little_endian: bool) -> Vec<Attachment> {
+        let mut attachments = Vec::new();
+        let mut next = self.hd_at_first;
+        while next != 0 {
+            let (_, at) = Atblock::read(stream, next as usize, little_endian);
+            attachments.push(at.attachment(stream, little_endian));
+            next = at.next();
+        }
+        attachments
+    }
+}
 
-        md_block.text()
+impl WriteBlock for Hdblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        utils::write(stream, self.hd_dg_first, little_endian);
+        utils::write(stream, self.hd_fh_first, little_endian);
+        utils::write(stream, self.hd_ch_first, little_endian);
+        utils::write(stream, self.hd_at_first, little_endian);
+        utils::write(stream, self.hd_ev_first, little_endian);
+        utils::write(stream, self.hd_md_comment, little_endian);
+        utils::write(stream, self.hd_start_time_ns, little_endian);
+        utils::write(stream, self.hd_tz_offset_min, little_endian);
+        utils::write(stream, self.hd_dst_offset_min, little_endian);
+        utils::write(stream, self.hd_time_flags, little_endian);
+        utils::write(stream, self.hd_time_class, little_endian);
+        utils::write(stream, self.hd_flags, little_endian);
+        utils::write(stream, self.hd_reserved, little_endian);
+        utils::write(stream, self.hd_start_angle_rad, little_endian);
+        utils::write(stream, self.hd_start_distance_m, little_endian);
     }
 }
 
@@ -105,7 +172,7 @@ impl Block for Hdblock {
             hd_start_distance_m: 0.0,
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##HD".as_bytes()) {
@@ -176,6 +243,7 @@ impl Block for Hdblock {
 
 #[cfg(test)]
 mod tests {
+    use crate::mdf4::block::WriteBlock;
     use crate::mdf4::{block::Block, hd_block::Hdblock};
 
     static RAW: [u8; 104] = [
@@ -217,4 +285,14 @@ mod tests {
 
         assert_eq!(pos, hd_block.byte_len());
     }
+
+    #[test]
+    fn write() {
+        let (pos, block) = Hdblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..pos], &stream[..]);
+    }
 }
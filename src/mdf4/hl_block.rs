@@ -1,3 +1,6 @@
+use std::mem;
+
+use crate::storage::Stream;
 use crate::utils;
 
 use super::block::Block;
@@ -5,6 +8,7 @@ use super::block_header::*;
 use super::mdf4_enums::ZipType;
 use super::mdf4_file::link_extract;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq)]
 struct Hlblock {
     header: BlockHeader,
@@ -35,7 +39,7 @@ impl Block for Hlblock {
             hl_reserved: [0_u8; 5],
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##HL".as_bytes()) {
@@ -62,6 +66,10 @@ impl Block for Hlblock {
     }
 
     fn byte_len(&self) -> usize {
-        todo!()
+        self.header.byte_len()
+            + mem::size_of_val(&self.hl_dl_first)
+            + mem::size_of_val(&self.hl_flags)
+            + mem::size_of_val(&self.hl_zip_type)
+            + mem::size_of_val(&self.hl_reserved)
     }
 }
@@ -1,6 +1,7 @@
 use std::mem;
 
-use super::block::Block;
+use super::block::{Block, WriteBlock};
+use crate::storage::Stream;
 use crate::utils;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -18,6 +19,46 @@ pub struct Idblock {
     #[allow(dead_code)]
     id_reserved2: [u8; 34],
 }
+impl Idblock {
+    /// `version` is the MDF version times 100, e.g. 410 for MDF 4.10.
+    pub fn create(program: &str, version: u16) -> Self {
+        let mut id_prog = [b' '; 8];
+        for (byte, char) in id_prog.iter_mut().zip(program.bytes()) {
+            *byte = char;
+        }
+
+        let mut id_vers = [b' '; 8];
+        let vers = format!("{}.{:02}", version / 100, version % 100);
+        for (byte, char) in id_vers.iter_mut().zip(vers.bytes()) {
+            *byte = char;
+        }
+
+        Self {
+            id_file: *b"MDF     ",
+            id_vers,
+            id_prog,
+            id_reserved1: [0; 4],
+            id_ver: version,
+            id_reserved2: [0; 34],
+        }
+    }
+
+    pub fn version(&self) -> u16 {
+        self.id_ver
+    }
+}
+
+impl WriteBlock for Idblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        utils::write(stream, self.id_file, little_endian);
+        utils::write(stream, self.id_vers, little_endian);
+        utils::write(stream, self.id_prog, little_endian);
+        utils::write(stream, self.id_reserved1, little_endian);
+        utils::write(stream, self.id_ver, little_endian);
+        utils::write(stream, self.id_reserved2, little_endian);
+    }
+}
+
 impl Block for Idblock {
     fn new() -> Self {
         Self {
@@ -39,7 +80,7 @@ impl Block for Idblock {
             id_reserved2: [0; 34],
         }
     }
-    fn read(stream: &[u8], _position: usize, _little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, _position: usize, _little_endian: bool) -> (usize, Self) {
         let mut pos = 0;
         let litte_endian = true;
         let id_file = utils::read(stream, _little_endian, &mut pos);
@@ -73,8 +114,8 @@ impl Block for Idblock {
 }
 
 #[cfg(test)]
-
 mod tests {
+    use crate::mdf4::block::WriteBlock;
     use crate::{
         mdf4::{block::Block, id_block::Idblock},
         utils,
@@ -107,4 +148,14 @@ mod tests {
 
         assert_eq!(64, id_result.byte_len());
     }
+
+    #[test]
+    fn write() {
+        let (pos, block) = Idblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..pos], &stream[..]);
+    }
 }
@@ -1,7 +1,8 @@
-use super::block::Block;
+use super::block::{Block, WriteBlock};
 use super::block_header::*;
 use super::utils as mdf4_utils;
 
+use crate::storage::Stream;
 use crate::utils;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -12,11 +13,28 @@ pub struct Mdblock {
 }
 
 impl Mdblock {
+    pub fn create(xml: &str) -> Self {
+        let mut block = Self {
+            header: BlockHeader::create("##MD", 24, 0),
+            md_data: xml.to_string(),
+        };
+        block.header = BlockHeader::create("##MD", block.byte_len(), 0);
+        block
+    }
+
     pub fn text(&self) -> String {
         self.clone().md_data
     }
 }
 
+impl WriteBlock for Mdblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        stream.extend_from_slice(self.md_data.as_bytes());
+        stream.push(0);
+    }
+}
+
 impl Block for Mdblock {
     fn new() -> Self {
         Self {
@@ -30,7 +48,7 @@ impl Block for Mdblock {
             md_data: "".to_string(),
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##MD".as_bytes()) {
@@ -38,7 +56,7 @@ impl Block for Mdblock {
         }
 
         let string_length = header.length as usize - header.byte_len();
-        let md_data: String = mdf4_utils::str_from_u8(&stream[pos..(pos + string_length)]);
+        let md_data: String = mdf4_utils::str_from_u8(&stream.bytes(pos, string_length));
 
         ((pos + string_length), Self { header, md_data })
     }
@@ -50,6 +68,7 @@ impl Block for Mdblock {
 
 #[cfg(test)]
 mod tests {
+    use crate::mdf4::block::WriteBlock;
     use crate::mdf4::{block::Block, md_block::Mdblock};
 
     static RAW: [u8; 472] = [
@@ -101,4 +120,14 @@ mod tests {
         assert_eq!(469, pos);
         assert_eq!(469, md_block.byte_len());
     }
+
+    #[test]
+    fn write() {
+        let (pos, block) = Mdblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..pos], &stream[..]);
+    }
 }
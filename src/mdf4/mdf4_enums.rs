@@ -1,5 +1,6 @@
 use std::mem;
 
+use crate::mdf::MasterType;
 use crate::record;
 
 #[allow(dead_code)]
@@ -33,6 +34,7 @@ impl ChannelHierarchyType {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EventType {
     Recording,
@@ -65,10 +67,11 @@ impl EventType {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 
 pub enum EventSyncType {
-    Seconds,
+    Seconds = 1,
 
     Radians,
 
@@ -89,6 +92,7 @@ impl EventSyncType {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 
 pub enum RangeType {
@@ -110,6 +114,7 @@ impl RangeType {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EventCause {
     Other,
@@ -136,6 +141,7 @@ impl EventCause {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SourceType {
     Other,
@@ -165,6 +171,7 @@ impl SourceType {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BusType {
     None,
@@ -266,6 +273,18 @@ impl SyncType {
     }
 }
 
+impl From<SyncType> for MasterType {
+    fn from(sync_type: SyncType) -> Self {
+        match sync_type {
+            SyncType::None => Self::None,
+            SyncType::Time => Self::Time,
+            SyncType::Angle => Self::Angle,
+            SyncType::Distance => Self::Distance,
+            SyncType::Index => Self::Index,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DataType {
     UnsignedByteLE,
@@ -304,6 +323,20 @@ impl DataType {
         }
     }
 
+    pub fn from_data_type_read(data_type_read: record::DataTypeRead) -> Self {
+        let little_endian = data_type_read.little_endian;
+        match data_type_read.data_type {
+            record::DataType::UnsignedInt if little_endian => Self::UnsignedByteLE,
+            record::DataType::UnsignedInt => Self::UnsignedByteBE,
+            record::DataType::SignedInt if little_endian => Self::SignedLE,
+            record::DataType::SignedInt => Self::SignedBE,
+            record::DataType::Float32 | record::DataType::Float64 if little_endian => Self::FloatLE,
+            record::DataType::Float32 | record::DataType::Float64 => Self::FloatBE,
+            record::DataType::StringNullTerm => Self::StringUTF8,
+            _ => Self::ByteArray,
+        }
+    }
+
     pub fn copy_to_data_type_read(&self) -> record::DataTypeRead {
         let dt = match self {
             Self::UnsignedByteLE => record::DataType::UnsignedInt,
@@ -313,7 +346,7 @@ impl DataType {
             Self::FloatLE => record::DataType::Float64,
             Self::FloatBE => record::DataType::Float64,
             Self::StringLatin => record::DataType::StringNullTerm,
-            Self::StringUTF8 => panic!(""),
+            Self::StringUTF8 => record::DataType::StringNullTerm,
             Self::StringUTF16LE => panic!(""),
             Self::StringUTF16BE => panic!(""),
             Self::ByteArray => record::DataType::ByteArray,
@@ -330,7 +363,7 @@ impl DataType {
             Self::FloatLE => true,
             Self::FloatBE => false,
             Self::StringLatin => false,
-            Self::StringUTF8 => panic!(""),
+            Self::StringUTF8 => false,
             Self::StringUTF16LE => panic!(""),
             Self::StringUTF16BE => panic!(""),
             Self::ByteArray => false,
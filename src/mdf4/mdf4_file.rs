@@ -1,20 +1,34 @@
 use super::cg_block::Cgblock;
 use super::cn_block::Cnblock;
-use crate::mdf::{self, MDFFile, MdfChannel, RasterType};
-use crate::record::Record;
-use crate::signal::{self, Signal};
+use super::data_block::DataBlockType;
+use crate::attachment::Attachment;
+use crate::conversion::Conversion;
+use crate::event::Event;
+use crate::export::{self, ExportOptions};
+use crate::mdf::{
+    self, ChannelInfo, ChannelSelector, MDFFile, MasterType, MdfChannel, Measurement, RasterType,
+    RecordChunks,
+};
+use crate::record::{Record, RecordView};
+use crate::signal::{Interpolation, Signal, Source};
+use crate::storage::{Storage, Stream};
 use crate::utils;
-use std::fs::File;
-use std::io::prelude::*;
-
-use super::block::{Block, LinkedBlock};
-use super::dg_block::Dgblock;
+use chrono::{DateTime, TimeZone, Utc};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::io;
+use std::ops::Range;
+
+use super::block::Block;
+use super::block_header::BlockHeader;
 use super::hd_block::Hdblock;
 use super::id_block::Idblock;
 use super::mdf4_enums::ChannelType;
 
 pub fn link_extract(
-    stream: &[u8],
+    stream: &dyn Stream,
     position: usize,
     little_endian: bool,
     no_links: u64,
@@ -35,38 +49,222 @@ pub struct MDF4 {
     id: Idblock,
     header: Hdblock,
     comment: String,
-    data_groups: Vec<Dgblock>,
-    channels: Vec<Cnblock>,
-    channel_groups: Vec<Cgblock>,
+    data_groups: Vec<DataGroup>,
     little_endian: bool,
-    file: Vec<u8>,
+    file: Storage,
 }
 
-impl MDFFile for MDF4 {
-    fn channels(&self) -> Vec<MdfChannel> {
-        let mut mdf_channels = Vec::new();
+/// A data group read once when the file is opened.
+#[derive(Debug, Clone, PartialEq)]
+struct DataGroup {
+    /// Positions of the DT and DZ blocks holding the records, in order.
+    data_blocks: Vec<usize>,
+    /// Offset of the records of each data block in the data of the group.
+    offsets: Vec<usize>,
+    channel_groups: Vec<ChannelGroup>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ChannelGroup {
+    block: Cgblock,
+    comment: String,
+    /// Index of the master channel.
+    master: Option<usize>,
+    master_type: MasterType,
+    channels: Vec<Channel>,
+}
 
+#[derive(Debug, Clone, PartialEq)]
+struct Channel {
+    block: Cnblock,
+    name: String,
+    display_name: String,
+    unit: String,
+    comment: String,
+    source: Option<Source>,
+    conversion: Option<Conversion>,
+    unsupported_conversion: bool,
+}
+
+impl MDF4 {
+    /// Writes `measurement` as a file of the same version, 4.10 for versions
+    /// the writer does not support.
+    pub fn rebuild(&self, measurement: &Measurement) -> Self {
+        let version = if self.id.version() >= 420 { 420 } else { 410 };
+        let bytes = measurement.mdf4(version).unwrap_or_else(|x| panic!("{x}"));
+        Self::from_bytes(bytes)
+    }
+
+    pub fn from_bytes(stream: Vec<u8>) -> Self {
+        Self::from_storage(Storage::Memory(stream)).unwrap_or_else(|x| panic!("{x}"))
+    }
+
+    /// Reads the metadata blocks of the file, data blocks are read when the
+    /// samples are. An error if the blocks are linked in a corrupt way.
+    pub fn from_storage(stream: Storage) -> Result<Self, &'static str> {
         let little_endian = true;
+        let position = 0;
 
-        let (position, _id_block) = Idblock::read(&self.file, 0, little_endian);
-        let (_pos, hd_block) = Hdblock::read(&self.file, position, little_endian);
+        if stream.len() < 64 {
+            return Err("Not an MDF file");
+        }
+        let hd = BlockHeader::check(&stream, 64, little_endian)?;
+        if hd.id != *b"##HD" || hd.link_count < 6 || hd.data_length() < 32 {
+            return Err("No header block found");
+        }
 
-        let next_dg = hd_block.first_data_group(&self.file, little_endian);
+        let (pos, id) = Idblock::read(&stream, position, little_endian);
+        let (_pos, header) = Hdblock::read(&stream, pos, little_endian);
+        let comment = header.comment(&stream, little_endian);
+        let mut mdf = Self {
+            id,
+            header,
+            comment,
+            data_groups: Vec::new(),
+            little_endian,
+            file: stream,
+        };
 
-        let data_groups = next_dg.list(&self.file, little_endian);
+        mdf.data_groups = mdf.tree()?;
 
-        for (dg_no, dg) in data_groups.iter().enumerate() {
-            let first_cg = dg.first(&self.file, little_endian);
-            let channel_groups = first_cg.list(&self.file, little_endian);
+        Ok(mdf)
+    }
 
-            for (cg_no, cg) in channel_groups.iter().enumerate() {
-                let first_cn = cg.first(&self.file, little_endian);
-                let channels = first_cn.list(&self.file, little_endian);
+    /// Reads the data groups, channel groups and channels of the file.
+    fn tree(&self) -> Result<Vec<DataGroup>, &'static str> {
+        let (stream, little_endian) = (&self.file, self.little_endian);
+
+        let mut data_groups = Vec::new();
+        for dg in self.header.data_groups(stream, little_endian)? {
+            let mut channel_groups = Vec::new();
+            for cg in dg.channel_groups(stream, little_endian)? {
+                let channels: Vec<Channel> = cg
+                    .channels(stream, little_endian)?
+                    .into_iter()
+                    .map(|cn| Channel {
+                        name: cn.name(stream, little_endian),
+                        display_name: cn.display_name(stream, little_endian),
+                        unit: cn.unit(stream, little_endian),
+                        comment: cn.comment(stream, little_endian),
+                        source: cn.source(stream, little_endian),
+                        conversion: cn.conversion(stream, little_endian),
+                        unsupported_conversion: cn
+                            .has_unsupported_conversion(stream, little_endian),
+                        block: cn,
+                    })
+                    .collect();
+                let master_type = channels
+                    .iter()
+                    .find(|x| {
+                        matches!(
+                            x.block.channel_type(),
+                            ChannelType::Master | ChannelType::VirtualMaster
+                        )
+                    })
+                    .map_or(MasterType::None, |x| x.block.sync_type().into());
+
+                channel_groups.push(ChannelGroup {
+                    comment: cg.comment(stream, little_endian),
+                    master: channels
+                        .iter()
+                        .position(|x| matches!(x.block.channel_type(), ChannelType::Master)),
+                    master_type,
+                    channels,
+                    block: cg,
+                });
+            }
 
-                for (cn_no, cn) in channels.iter().enumerate() {
-                    let name = cn.clone().comment(&self.file, little_endian);
+            data_groups.push(DataGroup {
+                data_blocks: DataBlockType::positions(stream, dg.data_location(), little_endian)?,
+                offsets: DataBlockType::offsets(stream, dg.data_location(), little_endian)?,
+                channel_groups,
+            });
+        }
+
+        Ok(data_groups)
+    }
+
+    fn group(&self, datagroup: usize, channel_grp: usize) -> &ChannelGroup {
+        &self.data_groups[datagroup].channel_groups[channel_grp]
+    }
+
+    /// The records of the data group, joined from all its data blocks or
+    /// borrowed from the file when they lie in a single uncompressed block.
+    fn data(&self, datagroup: usize) -> Cow<'_, [u8]> {
+        DataBlockType::data(
+            &self.file,
+            &self.data_groups[datagroup].data_blocks,
+            self.little_endian,
+        )
+    }
+
+    /// The bytes of `records` of a channel group with records of
+    /// `record_size`, read from the data blocks they lie in only.
+    fn record_range(&self, datagroup: usize, record_size: usize, records: Range<usize>) -> Vec<u8> {
+        let data_group = &self.data_groups[datagroup];
+        let (start, end) = (records.start * record_size, records.end * record_size);
+        let mut first = 0;
+
+        let mut data = if data_group.offsets.len() == data_group.data_blocks.len() {
+            first = start;
+            let mut positions = Vec::new();
+            for (i, (&position, &offset)) in data_group
+                .data_blocks
+                .iter()
+                .zip(&data_group.offsets)
+                .enumerate()
+            {
+                let next = data_group.offsets.get(i + 1).copied().unwrap_or(usize::MAX);
+                if offset < end && next > start {
+                    first = first.min(offset);
+                    positions.push(position);
+                }
+            }
+            DataBlockType::read_all(&self.file, &positions, self.little_endian)
+        } else {
+            self.data(datagroup).into_owned()
+        };
+
+        data.truncate(end - first);
+        data.drain(..(start - first).min(data.len()));
+        data
+    }
+
+    /// A signal of `channel` with its metadata and conversion.
+    fn signal(&self, channel: &Channel, timestamps: Vec<f64>, samples: Vec<Record>) -> Signal {
+        let signal = Signal::new(
+            timestamps,
+            samples,
+            channel.unit.clone(),
+            channel.name.clone(),
+            channel.comment.clone(),
+            channel.conversion.is_some(),
+        );
+        let signal = match &channel.source {
+            Some(source) => signal.with_source(source.clone()),
+            None => signal,
+        };
+
+        match &channel.conversion {
+            Some(conversion) => signal.with_conversion(conversion.clone()),
+            None => signal,
+        }
+    }
+
+    pub fn storage(&self) -> &Storage {
+        &self.file
+    }
+}
+
+impl MDFFile for MDF4 {
+    fn channels(&self) -> Vec<MdfChannel> {
+        let mut mdf_channels = Vec::new();
+
+        for (dg_no, dg) in self.data_groups.iter().enumerate() {
+            for (cg_no, cg) in dg.channel_groups.iter().enumerate() {
+                for (cn_no, cn) in cg.channels.iter().enumerate() {
                     mdf_channels.push(mdf::MdfChannel {
-                        name,
+                        name: cn.name.clone(),
                         data_group: dg_no,
                         channel_group: cg_no,
                         channel: cn_no,
@@ -80,192 +278,383 @@ impl MDFFile for MDF4 {
 
     fn find_time_channel(
         &self,
-        _datagroup: usize,
+        datagroup: usize,
         channel_grp: usize,
     ) -> Result<usize, &'static str> {
-        let channel_group = self.channel_groups[channel_grp]
-            .clone()
-            .channels(&self.file, self.little_endian);
-        for (i, channel) in channel_group.iter().enumerate() {
-            if matches!(channel.channel_type(), ChannelType::Master) {
-                return Ok(i);
-            }
-        }
-
-        Err("No time series found for the channel group selected")
+        self.group(datagroup, channel_grp)
+            .master
+            .ok_or("No time series found for the channel group selected")
     }
 
     fn read_channel(&self, datagroup: usize, channel_grp: usize, channel: usize) -> Vec<Record> {
-        let dg = &self.data_groups[datagroup];
-        let channel_groups = dg
-            .first(&self.file, self.little_endian)
-            .list(&self.file, self.little_endian);
-
-        let channel_group = &channel_groups[channel_grp];
-        let channels = channel_group
-            .first(&self.file, self.little_endian)
-            .list(&self.file, self.little_endian);
-        let cn = &channels[channel];
-
-        let data = dg.read_data(&self.file, self.little_endian);
-        // &self.file[dg.data_location() as usize..(dg.data_location() as usize + data_length)];
-
-        println!("Record Number: {}", channel_group.record_number());
-
-        let mut data_blocks: Vec<&[u8]> = vec![&[0_u8]; channel_group.record_number()];
-        // let mut data_blocks = Vec::new();
-
-        println!("Vec len: {}", data_blocks.len());
-
-        for (i, db) in data_blocks.iter_mut().enumerate() {
-            *db = &data[(i * channel_group.record_size())..((i + 1) * channel_group.record_size())];
-        }
-
-        let byte_offset = cn.byte_offset();
-
-        let mut records = Vec::new();
-        let mut pos = 0;
-        for _i in 0..channel_group.record_number() {
-            records.push(&data[pos..pos + channel_group.record_size()]);
-            pos += channel_group.record_size();
-        }
-
-        let mut raw_data = Vec::new();
-        let end = byte_offset + cn.data_type_len();
-
-        for rec in records {
-            raw_data.push(&rec[byte_offset..end]);
-        }
+        let cn = &self.group(datagroup, channel_grp).channels[channel].block;
 
-        let mut extracted_data = Vec::new();
-        for raw in raw_data {
-            extracted_data.push(Record::new(raw, cn.data_type().copy_to_data_type_read()));
-        }
+        self.records(datagroup, channel_grp)
+            .iter()
+            .map(|record| cn.decode(record))
+            .collect()
+    }
 
-        extracted_data
+    fn records(&self, datagroup: usize, channel_grp: usize) -> RecordView<'_> {
+        let group = self.group(datagroup, channel_grp);
+        RecordView::create(
+            self.data(datagroup),
+            group.block.record_size(),
+            group.block.record_number(),
+        )
     }
 
-    #[must_use]
     fn new(filepath: &str) -> Self {
-        let mut file = File::open(filepath).expect("Could not read file");
-        let mut stream = Vec::new();
-        let _ = file.read_to_end(&mut stream);
+        Self::from_storage(Storage::new(filepath).expect("Could not read file"))
+            .unwrap_or_else(|x| panic!("{x}"))
+    }
 
-        let little_endian = true;
-        let position = 0;
+    fn comment(&self) -> String {
+        self.comment.clone()
+    }
 
-        let (pos, id) = Idblock::read(&stream, position, little_endian);
-        let (_pos, header) = Hdblock::read(&stream, pos, little_endian);
-        let comment = header.comment(&stream, little_endian);
-        let mut mdf = Self {
-            id,
-            header: header.clone(),
-            comment,
-            data_groups: header
-                .first_data_group(&stream, little_endian)
-                .list(&stream, little_endian),
-            channels: Vec::new(),
-            channel_groups: Vec::new(),
-            little_endian,
-            file: stream,
-        };
+    fn start_time(&self) -> DateTime<Utc> {
+        Utc.timestamp_nanos(self.header.start_time_ns() as i64)
+    }
 
-        mdf.read_all();
+    fn events(&self) -> Vec<Event> {
+        self.header.events(&self.file, self.little_endian)
+    }
 
-        mdf
+    fn attachments(&self) -> Vec<Attachment> {
+        self.header.attachments(&self.file, self.little_endian)
     }
 
-    fn read_all(&mut self) {
-        let mut channel_groups = Vec::with_capacity(self.data_groups.len());
-        for group in &self.data_groups {
-            let group1 = group.clone();
-            let mut grp = group1.read_channel_groups(&self.file, self.little_endian);
-            channel_groups.append(&mut grp);
-        }
+    fn group_comment(&self, datagroup: usize, channel_grp: usize) -> String {
+        self.group(datagroup, channel_grp).comment.clone()
+    }
 
-        let mut channels = Vec::new();
-        for grp in &channel_groups {
-            let grp1 = grp.clone();
+    fn record_count(&self, datagroup: usize, channel_grp: usize) -> usize {
+        self.group(datagroup, channel_grp).block.record_number()
+    }
 
-            channels.append(&mut grp1.channels(&self.file, self.little_endian));
+    fn channel_info(&self, datagroup: usize, channel_grp: usize, channel: usize) -> ChannelInfo {
+        let group = self.group(datagroup, channel_grp);
+        let cn = &group.channels[channel];
+
+        ChannelInfo {
+            display_name: cn.display_name.clone(),
+            unit: cn.unit.clone(),
+            comment: cn.comment.clone(),
+            source: cn.source.clone(),
+            data_type: cn.block.data_type_read().data_type,
+            master: group.master_type,
         }
-
-        self.channel_groups = channel_groups;
-        self.channels = channels;
     }
 
-    fn list_data_groups(&mut self) {
-        let little_endian = true;
-        let position = 0;
-
-        let (position, _id_block) = Idblock::read(&self.file, position, little_endian);
-        let (_pos, hd_block) = Hdblock::read(&self.file, position, little_endian);
-
-        let dg = hd_block
-            .first_data_group(&self.file, little_endian)
-            .list(&self.file, little_endian);
-        self.data_groups = dg;
+    fn has_unsupported_conversion(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channel: usize,
+    ) -> bool {
+        self.group(datagroup, channel_grp).channels[channel].unsupported_conversion
     }
 
-    fn list_channels(&self) {
-        let little_endian = true;
-
-        let (position, _id_block) = Idblock::read(&self.file, 0, little_endian);
-        let (_pos, hd_block) = Hdblock::read(&self.file, position, little_endian);
-
-        let next_dg = hd_block.first_data_group(&self.file, little_endian);
-
-        let data_groups = next_dg.list(&self.file, little_endian);
-
-        for dg in data_groups {
-            let first_cg = dg.first(&self.file, little_endian);
-            let channel_groups = first_cg.list(&self.file, little_endian);
+    /// The block tree is read when the file is opened.
+    fn read_all(&mut self) {}
 
-            for cg in channel_groups {
-                let first_cn = cg.first(&self.file, little_endian);
-                let channels = first_cn.list(&self.file, little_endian);
+    /// The block tree is read when the file is opened.
+    fn list_data_groups(&mut self) {}
 
-                println!("Channel Group: {}", cg.comment(&self.file, little_endian));
+    fn list_channels(&self) {
+        for dg in &self.data_groups {
+            for cg in &dg.channel_groups {
+                println!("Channel Group: {}", cg.comment);
 
-                for cn in channels {
-                    println!("Channel: {}", cn.comment(&self.file, little_endian));
+                for cn in &cg.channels {
+                    println!("Channel: {}", cn.name);
                 }
             }
         }
     }
 
-    #[must_use]
     fn read(&self, datagroup: usize, channel_grp: usize, channel: usize) -> Signal {
         let time_channel = self.find_time_channel(datagroup, channel_grp);
         let time_channel = match time_channel {
             Ok(x) => x,
             Err(e) => panic!("{}", e),
         };
-        println!("Time Channel: {}", time_channel);
         let time = self.read_channel(datagroup, channel_grp, time_channel);
         let some = self.read_channel(datagroup, channel_grp, channel);
 
-        signal::Signal::new(
-            time.iter().map(|x| x.extract()).collect(),
-            some,
-            "Unit".to_string(),
-            "Measurement".to_string(),
-            "This is some measurement".to_string(),
-            false,
-        )
+        let cn = &self.group(datagroup, channel_grp).channels[channel];
+        self.signal(cn, time.iter().map(|x| x.extract()).collect(), some)
+    }
+
+    fn cut(&self, start: f64, end: f64, include_ends: bool, time_from_zero: bool) -> Self {
+        let mut measurement = Measurement::read(self);
+        measurement.cut(start, end, include_ends, time_from_zero);
+        self.rebuild(&measurement)
     }
 
-    fn cut(&self, _start: f64, _end: f64, _include_ends: bool, _time_from_zero: bool) {
-        // let _delta = if time_from_zero { start } else { 0.0 };
+    fn export(&self, format: &str, filename: &str, options: &ExportOptions) -> io::Result<()> {
+        let measurement = match options.channels.is_empty() {
+            true => Measurement::read(self),
+            false => Measurement::read_matching(self, &options.channels),
+        };
+        export::export(measurement, format, filename, options)
+    }
+
+    fn filter(&self, channels: &[ChannelSelector]) -> Self {
+        self.rebuild(&Measurement::read_matching(self, channels))
+    }
+    fn resample(
+        &self,
+        raster: &RasterType,
+        interpolation: Interpolation,
+        time_from_zero: bool,
+    ) -> Result<Self, &'static str> {
+        let mut measurement = Measurement::read(self);
+        measurement.resample(raster, interpolation, time_from_zero)?;
+        Ok(self.rebuild(&measurement))
+    }
+
+    fn select(
+        &self,
+        channels: &[MdfChannel],
+        record_offset: usize,
+        record_count: Option<usize>,
+        raw: bool,
+    ) -> Result<Vec<Signal>, &'static str> {
+        let mut signals = vec![None; channels.len()];
+        for (datagroup, channel_grp) in mdf::groups(channels) {
+            let group = self.group(datagroup, channel_grp);
+            let master = self.find_time_channel(datagroup, channel_grp)?;
+            let wanted: Vec<usize> = (0..channels.len())
+                .filter(|&i| {
+                    channels[i].data_group == datagroup && channels[i].channel_group == channel_grp
+                })
+                .collect();
+
+            let record_size = group.block.record_size();
+            let range =
+                mdf::record_window(group.block.record_number(), record_offset, record_count);
+            let data = match record_size {
+                0 => Vec::new(),
+                _ => self.record_range(datagroup, record_size, range),
+            };
+            let records: Vec<&[u8]> = data.chunks_exact(record_size.max(1)).collect();
+            let timestamps: Vec<f64> = records
+                .iter()
+                .map(|record| group.channels[master].block.decode(record).extract())
+                .collect();
+
+            #[cfg(feature = "parallel")]
+            let wanted = wanted.par_iter();
+            #[cfg(not(feature = "parallel"))]
+            let wanted = wanted.iter();
+
+            let read: Vec<(usize, Signal)> = wanted
+                .map(|&i| {
+                    let channel = &group.channels[channels[i].channel];
+                    let samples = records.iter().map(|x| channel.block.decode(x)).collect();
+                    let signal = self.signal(channel, timestamps.clone(), samples);
+                    match raw {
+                        true => (i, signal),
+                        false => (i, signal.physical()),
+                    }
+                })
+                .collect();
+            for (i, signal) in read {
+                signals[i] = Some(signal);
+            }
+        }
+
+        Ok(signals.into_iter().flatten().collect())
+    }
+
+    fn read_range(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channel: usize,
+        t_start: f64,
+        t_end: f64,
+    ) -> Result<Signal, &'static str> {
+        let group = self.group(datagroup, channel_grp);
+        let master = &group.channels[self.find_time_channel(datagroup, channel_grp)?].block;
+        let cn = &group.channels[channel];
+        let record_size = group.block.record_size();
+        if record_size == 0 {
+            return Ok(self.signal(cn, Vec::new(), Vec::new()));
+        }
+        let record_number = group.block.record_number();
+        let time = |record: usize| match record < record_number {
+            true => master
+                .decode(&self.record_range(datagroup, record_size, record..record + 1))
+                .extract(),
+            false => f64::INFINITY,
+        };
+
+        // The first record starting in each data block, found without the
+        // offsets all records lie in a single block. The time of a probed
+        // record is kept, so each block is inflated once for both searches
+        let data_group = &self.data_groups[datagroup];
+        let starts: Vec<(usize, OnceCell<f64>)> =
+            match data_group.offsets.len() == data_group.data_blocks.len() {
+                true => data_group
+                    .offsets
+                    .iter()
+                    .map(|x| (x.div_ceil(record_size), OnceCell::new()))
+                    .collect(),
+                false => vec![(0, OnceCell::new())],
+            };
+        let probe =
+            |(record, probed): &(usize, OnceCell<f64>)| *probed.get_or_init(|| time(*record));
+        let first = starts.partition_point(|x| probe(x) < t_start);
+        let last = starts.partition_point(|x| probe(x) <= t_end);
+        let from = first.checked_sub(1).map_or(0, |i| starts[i].0);
+        let to = starts
+            .get(last)
+            .map_or(record_number, |x| x.0.min(record_number));
+
+        let mut timestamps = Vec::new();
+        let mut samples = Vec::new();
+        for record in self
+            .record_range(datagroup, record_size, from..to.max(from))
+            .chunks_exact(record_size)
+        {
+            let time = master.decode(record).extract();
+            if (t_start..=t_end).contains(&time) {
+                timestamps.push(time);
+                samples.push(cn.block.decode(record));
+            }
+        }
+
+        Ok(self.signal(cn, timestamps, samples))
     }
 
-    fn export(&self, _format: &str, _filename: &str) {}
-    fn filter(&self, _channels: &str) {}
-    #[must_use]
-    fn resample(&self, _raster: RasterType, _version: &str, _time_from_zero: bool) -> Self {
-        self.clone()
+    fn chunks(
+        &self,
+        datagroup: usize,
+        channel_grp: usize,
+        channels: &[MdfChannel],
+        chunk_records: usize,
+    ) -> Result<RecordChunks<'_>, &'static str> {
+        let group = self.group(datagroup, channel_grp);
+        let master = &group.channels[self.find_time_channel(datagroup, channel_grp)?].block;
+        let wanted: Vec<&Cnblock> = channels
+            .iter()
+            .map(|x| &group.channels[x.channel].block)
+            .collect();
+        let blocks = self.data_groups[datagroup]
+            .data_blocks
+            .iter()
+            .map(|&position| {
+                DataBlockType::read(&self.file, position, self.little_endian)
+                    .data_array(&self.file, self.little_endian)
+            });
+
+        Ok(RecordChunks::create(
+            datagroup,
+            channel_grp,
+            channels,
+            channels
+                .iter()
+                .map(|x| group.channels[x.channel].conversion.clone())
+                .collect(),
+            group.block.record_size(),
+            group.block.record_number(),
+            chunk_records,
+            Box::new(blocks),
+            Box::new(move |record| {
+                (
+                    master.decode(record).extract(),
+                    wanted.iter().map(|cn| cn.decode(record)).collect(),
+                )
+            }),
+        ))
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-struct Rdblock {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let mdf = MDF4::new("example_files/ASAP2_Demo_V171.mf4");
+        let channels = mdf.channels();
+        assert!(mdf.data_groups.len() > 1);
+        assert_eq!(
+            channels.len(),
+            mdf.data_groups
+                .iter()
+                .flat_map(|x| &x.channel_groups)
+                .map(|x| x.channels.len())
+                .sum::<usize>()
+        );
+
+        for (dg, data_group) in mdf.data_groups.iter().enumerate() {
+            assert!(!data_group.data_blocks.is_empty());
+            assert_eq!(data_group.offsets.len(), data_group.data_blocks.len());
+            assert_eq!(data_group.offsets[0], 0);
+            for (cg, group) in data_group.channel_groups.iter().enumerate() {
+                let master = mdf.find_time_channel(dg, cg).unwrap();
+                assert_eq!(group.channels[master].name, "time");
+                assert_eq!(
+                    mdf.read_channel(dg, cg, master).len(),
+                    mdf.record_count(dg, cg)
+                );
+            }
+        }
+
+        let time = mdf.read(1, 0, 0);
+        assert_eq!(
+            time.timestamps,
+            time.samples.iter().map(|x| x.extract()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn compressed() {
+        let plain = MDF4::new("example_files/ASAP2_Demo_V171.mf4");
+        let channels = plain.channels();
+        let signals = plain.select(&channels, 0, None, true).unwrap();
+
+        for path in [
+            "example_files/ASAP2_Demo_V171_deflate.mf4",
+            "example_files/ASAP2_Demo_V171_transpose_deflate.mf4",
+        ] {
+            let mdf = MDF4::new(path);
+            assert_eq!(mdf.channels(), channels);
+            // The files differ in the metadata and the VLSD data written
+            for (signal, expected) in mdf
+                .select(&channels, 0, None, true)
+                .unwrap()
+                .iter()
+                .zip(&signals)
+            {
+                assert_eq!(signal.timestamps, expected.timestamps);
+                if !matches!(expected.samples[0], Record::StringNullTerm(_)) {
+                    assert_eq!(signal.samples, expected.samples);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn records() {
+        let path = "example_files/ASAP2_Demo_V171.mf4";
+        let file = MDF4::from_storage(Storage::open(path).unwrap()).unwrap();
+        let memory = MDF4::from_bytes(std::fs::read(path).unwrap());
+
+        let view = memory.records(0, 0);
+        assert!(view.is_borrowed());
+        assert!(!file.records(0, 0).is_borrowed());
+        assert_eq!(view.len(), memory.group(0, 0).block.record_number());
+        assert!(view.get(view.len()).is_none());
+        for (dg, group) in memory.data_groups.iter().enumerate() {
+            for cg in 0..group.channel_groups.len() {
+                let (expected, view) = (file.records(dg, cg), memory.records(dg, cg));
+                assert!(view.iter().eq(expected.iter()));
+            }
+        }
+    }
+}
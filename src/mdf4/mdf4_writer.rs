@@ -0,0 +1,508 @@
+use std::fs::File;
+use std::io::{self, Write};
+
+use chrono::{DateTime, Utc};
+
+use super::at_block::Atblock;
+use super::block::{Block, WriteBlock};
+use super::cc_block::Ccblock;
+use super::cg_block::Cgblock;
+use super::cn_block::Cnblock;
+use super::dg_block::Dgblock;
+use super::dt_block::Dtblock;
+use super::ev_block::EVBlock;
+use super::fh_block::Fhblock;
+use super::hd_block::Hdblock;
+use super::id_block::Idblock;
+use super::md_block::Mdblock;
+use super::mdf4_enums::{ChannelType, DataType};
+use super::si_block::Siblock;
+use super::tx_block::Txblock;
+use crate::attachment::Attachment;
+use crate::conversion::Conversion;
+use crate::event::Event;
+use crate::record::{self, Record};
+use crate::signal::{Signal, Source};
+use crate::utils;
+
+const ID_LENGTH: usize = 64;
+
+struct ChannelGroup {
+    comment: String,
+    signals: Vec<Signal>,
+}
+
+/// Builds a sorted MDF 4.x file, one data group per channel group, each with
+/// a float64 master channel followed by the signals.
+pub struct Mdf4Writer {
+    comment: String,
+    start_time: DateTime<Utc>,
+    version: u16,
+    events: Vec<Event>,
+    attachments: Vec<Attachment>,
+    groups: Vec<ChannelGroup>,
+}
+
+impl Default for Mdf4Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mdf4Writer {
+    pub fn new() -> Self {
+        Self {
+            comment: String::new(),
+            start_time: Utc::now(),
+            version: 410,
+            events: Vec::new(),
+            attachments: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    pub fn set_comment(&mut self, comment: &str) {
+        self.comment = comment.to_string();
+    }
+
+    /// An error for times before 1970 or after 2262, which the header block
+    /// cannot store as nanoseconds.
+    pub fn set_start_time(&mut self, start_time: DateTime<Utc>) -> Result<(), &'static str> {
+        nanoseconds(start_time).ok_or("Start time out of range for MDF 4")?;
+        self.start_time = start_time;
+        Ok(())
+    }
+
+    /// Sets the version written to the identification block, 410 or 420.
+    pub fn set_version(&mut self, version: u16) -> Result<(), &'static str> {
+        match version {
+            410 | 420 => {
+                self.version = version;
+                Ok(())
+            }
+            _ => Err("Only MDF 4.10 and 4.20 can be written"),
+        }
+    }
+
+    /// Adds a trigger event, pre and post trigger times are not stored.
+    pub fn add_event(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    pub fn add_attachment(&mut self, attachment: Attachment) {
+        self.attachments.push(attachment);
+    }
+
+    pub fn add_channel_group(
+        &mut self,
+        comment: &str,
+        signals: Vec<Signal>,
+    ) -> Result<(), &'static str> {
+        let first = match signals.first() {
+            Some(signal) => signal,
+            None => return Err("A channel group needs at least one signal"),
+        };
+
+        for signal in &signals {
+            if signal.samples.len() != signal.timestamps.len() {
+                return Err("Signal samples and timestamps differ in length");
+            }
+            if signal.timestamps != first.timestamps {
+                return Err("Signals in a channel group must share the same timestamps");
+            }
+        }
+
+        self.groups.push(ChannelGroup {
+            comment: comment.to_string(),
+            signals,
+        });
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let little_endian = true;
+        let start_time_ns = nanoseconds(self.start_time).unwrap_or_default();
+
+        let mut stream = Vec::new();
+        Idblock::create("rsmdf", self.version).write(&mut stream, little_endian);
+        // Leave room for the header block, it is written last once its links are known
+        stream.resize(ID_LENGTH + Hdblock::new().byte_len(), 0);
+
+        let fh_comment = append(
+            &mut stream,
+            &Mdblock::create(&format!(
+                "<FHcomment><TX>created</TX><tool_id>rsmdf</tool_id><tool_vendor>rsmdf</tool_vendor><tool_version>{}</tool_version></FHcomment>",
+                env!("CARGO_PKG_VERSION")
+            )),
+            little_endian,
+        );
+        let fh = append(
+            &mut stream,
+            &Fhblock::create(fh_comment, start_time_ns),
+            little_endian,
+        );
+
+        let hd_comment = if self.comment.is_empty() {
+            0
+        } else {
+            append(
+                &mut stream,
+                &Mdblock::create(&format!(
+                    "<HDcomment><TX>{}</TX></HDcomment>",
+                    escape(&self.comment)
+                )),
+                little_endian,
+            )
+        };
+
+        let mut at_next = 0;
+        for attachment in self.attachments.iter().rev() {
+            let file_name = append_text(&mut stream, &attachment.file_name, little_endian);
+            let mime = append_text(&mut stream, &attachment.mime, little_endian);
+            let comment = append_text(&mut stream, &attachment.comment, little_endian);
+            at_next = append(
+                &mut stream,
+                &Atblock::create(at_next, file_name, mime, comment, attachment),
+                little_endian,
+            );
+        }
+
+        let mut ev_next = 0;
+        for event in self.events.iter().rev() {
+            let name = append_text(&mut stream, &event.name, little_endian);
+            let comment = append_text(&mut stream, &event.comment, little_endian);
+            ev_next = append(
+                &mut stream,
+                &EVBlock::create(ev_next, name, comment, event.time),
+                little_endian,
+            );
+        }
+
+        let mut dg_next = 0;
+        for group in self.groups.iter().rev() {
+            dg_next = write_group(&mut stream, dg_next, group, little_endian);
+        }
+
+        let mut header = Vec::new();
+        Hdblock::create(dg_next, fh, at_next, ev_next, hd_comment, start_time_ns)
+            .write(&mut header, little_endian);
+        stream[ID_LENGTH..ID_LENGTH + header.len()].copy_from_slice(&header);
+
+        stream
+    }
+
+    pub fn save(&self, filepath: &str) -> io::Result<()> {
+        let mut file = File::create(filepath)?;
+        file.write_all(&self.to_bytes())
+    }
+}
+
+/// Writes the block at the next 8 byte aligned address and returns that address.
+fn append<T: WriteBlock>(stream: &mut Vec<u8>, block: &T, little_endian: bool) -> u64 {
+    while !stream.len().is_multiple_of(8) {
+        stream.push(0);
+    }
+    let address = stream.len() as u64;
+    block.write(stream, little_endian);
+    address
+}
+
+fn append_text(stream: &mut Vec<u8>, text: &str, little_endian: bool) -> u64 {
+    if text.is_empty() {
+        0
+    } else {
+        append(stream, &Txblock::create(text), little_endian)
+    }
+}
+
+fn append_source(stream: &mut Vec<u8>, source: &Source, little_endian: bool) -> u64 {
+    let name = append_text(stream, &source.name, little_endian);
+    let path = append_text(stream, &source.path, little_endian);
+    let comment = append_text(stream, &source.comment, little_endian);
+    append(stream, &Siblock::create(name, path, comment), little_endian)
+}
+
+fn append_conversion(stream: &mut Vec<u8>, conversion: &Conversion, little_endian: bool) -> u64 {
+    let block = match conversion {
+        Conversion::Linear { offset, factor } => Ccblock::linear(*offset, *factor),
+        Conversion::ValueToText {
+            values,
+            texts,
+            default,
+        } => {
+            let refs = texts
+                .iter()
+                .chain(std::iter::once(default))
+                .map(|text| append_text(stream, text, little_endian))
+                .collect();
+            Ccblock::value_to_text(values.clone(), refs)
+        }
+        Conversion::TabularInterpolation { raw, phys } => Ccblock::table(true, raw, phys),
+        Conversion::Tabular { raw, phys } => Ccblock::table(false, raw, phys),
+    };
+
+    append(stream, &block, little_endian)
+}
+
+fn write_group(
+    stream: &mut Vec<u8>,
+    dg_next: u64,
+    group: &ChannelGroup,
+    little_endian: bool,
+) -> u64 {
+    let timestamps = &group.signals[0].timestamps;
+
+    let master_len = 8;
+    let mut layout = Vec::with_capacity(group.signals.len());
+    let mut record_size = master_len;
+    for signal in &group.signals {
+        let (data_type, byte_len) = Record::storage(&signal.samples);
+        layout.push((data_type, record_size, byte_len));
+        record_size += byte_len;
+    }
+
+    let mut data = Vec::with_capacity(record_size * timestamps.len());
+    for (i, time) in timestamps.iter().enumerate() {
+        utils::write(&mut data, *time, little_endian);
+        for (signal, (_, _, byte_len)) in group.signals.iter().zip(&layout) {
+            data.append(&mut signal.samples[i].to_bytes(*byte_len, little_endian));
+        }
+    }
+    let dt = append(stream, &Dtblock::create(data), little_endian);
+
+    let mut cn_next = 0;
+    for (signal, (data_type, byte_offset, byte_len)) in group.signals.iter().zip(&layout).rev() {
+        let name = append_text(stream, &signal.name, little_endian);
+        let unit = append_text(stream, &signal.unit, little_endian);
+        let comment = append_text(stream, &signal.comment, little_endian);
+        let conversion = match (&signal.conversion, signal.raw) {
+            (Some(conversion), true) => append_conversion(stream, conversion, little_endian),
+            _ => 0,
+        };
+        let source = match &signal.source {
+            Some(source) => append_source(stream, source, little_endian),
+            None => 0,
+        };
+        let data_type = DataType::from_data_type_read(record::DataTypeRead {
+            data_type: *data_type,
+            little_endian,
+        });
+
+        cn_next = append(
+            stream,
+            &Cnblock::create(
+                cn_next,
+                name,
+                conversion,
+                unit,
+                comment,
+                source,
+                ChannelType::FixedLength,
+                data_type,
+                *byte_offset as u32,
+                (*byte_len * 8) as u32,
+            ),
+            little_endian,
+        );
+    }
+
+    let name = append_text(stream, "time", little_endian);
+    let unit = append_text(stream, "s", little_endian);
+    let cn_first = append(
+        stream,
+        &Cnblock::create(
+            cn_next,
+            name,
+            0,
+            unit,
+            0,
+            0,
+            ChannelType::Master,
+            DataType::FloatLE,
+            0,
+            (master_len * 8) as u32,
+        ),
+        little_endian,
+    );
+
+    let comment = append_text(stream, &group.comment, little_endian);
+    let cg = append(
+        stream,
+        &Cgblock::create(
+            0,
+            cn_first,
+            comment,
+            timestamps.len() as u64,
+            record_size as u32,
+        ),
+        little_endian,
+    );
+
+    append(stream, &Dgblock::create(dg_next, cg, dt, 0), little_endian)
+}
+
+fn nanoseconds(time: DateTime<Utc>) -> Option<u64> {
+    time.timestamp_nanos_opt()
+        .and_then(|x| u64::try_from(x).ok())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mdf4Writer;
+    use crate::conversion::Conversion;
+    use crate::mdf::{MDFFile, MDF};
+    use crate::record::Record;
+    use crate::signal::Signal;
+    use crate::utils::TempFile;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn signal(name: &str, samples: Vec<Record>) -> Signal {
+        Signal::new(
+            (0..samples.len()).map(|x| x as f64 * 0.1).collect(),
+            samples,
+            "V".to_string(),
+            name.to_string(),
+            format!("{} comment", name),
+            false,
+        )
+    }
+
+    #[test]
+    fn add_channel_group() {
+        let mut writer = Mdf4Writer::new();
+
+        assert!(writer.add_channel_group("empty", Vec::new()).is_err());
+
+        let mut other = signal("b", vec![Record::Uint(1)]);
+        other.timestamps = vec![5.0];
+        assert!(writer
+            .add_channel_group("mismatch", vec![signal("a", vec![Record::Uint(1)]), other])
+            .is_err());
+    }
+
+    #[test]
+    fn set_start_time() {
+        let mut writer = Mdf4Writer::new();
+
+        assert!(writer.set_start_time(Utc.timestamp_nanos(-1)).is_err());
+        assert!(writer.set_start_time(DateTime::<Utc>::MAX_UTC).is_err());
+        assert!(writer.set_start_time(Utc.timestamp_nanos(0)).is_ok());
+        assert_eq!(writer.start_time, Utc.timestamp_nanos(0));
+    }
+
+    #[test]
+    fn round_trip() {
+        let status = Signal::new(
+            vec![0.0, 0.1, 0.2],
+            vec![Record::Uint(0), Record::Uint(1), Record::Uint(2)],
+            "".to_string(),
+            "status".to_string(),
+            "".to_string(),
+            true,
+        )
+        .with_conversion(Conversion::ValueToText {
+            values: vec![0.0, 1.0],
+            texts: vec!["Off".to_string(), "On".to_string()],
+            default: "Unknown".to_string(),
+        });
+        let speed = Signal::new(
+            vec![0.0, 0.1, 0.2],
+            vec![Record::Int(-300), Record::Int(0), Record::Int(40000)],
+            "km/h".to_string(),
+            "speed".to_string(),
+            "vehicle speed".to_string(),
+            true,
+        )
+        .with_conversion(Conversion::Linear {
+            offset: 1.0,
+            factor: 0.5,
+        });
+
+        let mut writer = Mdf4Writer::new();
+        writer.set_comment("round trip");
+        writer
+            .add_channel_group("first", vec![status, speed.clone()])
+            .unwrap();
+        writer
+            .add_channel_group(
+                "second",
+                vec![
+                    signal(
+                        "voltage",
+                        vec![Record::Float64(1.5), Record::Float64(-2.25)],
+                    ),
+                    signal("ratio", vec![Record::Float32(0.5), Record::Float32(0.25)]),
+                    signal(
+                        "label",
+                        vec![
+                            Record::StringNullTerm("idle".to_string()),
+                            Record::StringNullTerm("running".to_string()),
+                        ],
+                    ),
+                ],
+            )
+            .unwrap();
+
+        let file = TempFile::create("writer.mf4");
+        writer.save(file.path()).unwrap();
+        let mdf = MDF::new(file.path());
+
+        let names: Vec<String> = mdf.channels.iter().map(|x| x.name.clone()).collect();
+        assert_eq!(
+            names,
+            vec!["time", "status", "speed", "time", "voltage", "ratio", "label"]
+        );
+
+        let read = mdf.read_channel(&mdf.search_channels("speed").unwrap());
+        assert_eq!(read, speed);
+        assert_eq!(
+            read.physical().samples,
+            vec![
+                Record::Float64(-149.0),
+                Record::Float64(1.0),
+                Record::Float64(20001.0)
+            ]
+        );
+
+        let status = mdf.read_channel(&mdf.search_channels("status").unwrap());
+        assert_eq!(
+            status.physical().samples,
+            vec![
+                Record::StringNullTerm("Off".to_string()),
+                Record::StringNullTerm("On".to_string()),
+                Record::StringNullTerm("Unknown".to_string())
+            ]
+        );
+
+        let voltage = mdf.read_channel(&mdf.search_channels("voltage").unwrap());
+        assert_eq!(voltage.timestamps, vec![0.0, 0.1]);
+        assert_eq!(
+            voltage.samples,
+            vec![Record::Float64(1.5), Record::Float64(-2.25)]
+        );
+        assert_eq!(voltage.unit, "V");
+        assert_eq!(voltage.comment, "voltage comment");
+
+        let ratio = mdf.read_channel(&mdf.search_channels("ratio").unwrap());
+        assert_eq!(
+            ratio.samples,
+            vec![Record::Float32(0.5), Record::Float32(0.25)]
+        );
+
+        let label = mdf.read_channel(&mdf.search_channels("label").unwrap());
+        assert_eq!(
+            label.samples,
+            vec![
+                Record::StringNullTerm("idle".to_string()),
+                Record::StringNullTerm("running".to_string())
+            ]
+        );
+    }
+}
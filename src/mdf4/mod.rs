@@ -19,6 +19,7 @@ mod id_block;
 mod md_block;
 mod mdf4_enums;
 pub mod mdf4_file;
+pub mod mdf4_writer;
 mod sd_block;
 mod si_block;
 mod sr_block;
@@ -1,6 +1,8 @@
 use super::block::Block;
 use super::block_header::*;
+use crate::storage::Stream;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Sdblock {
     header: BlockHeader,
@@ -22,10 +24,12 @@ impl Block for Sdblock {
         }
     }
 
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
-        let sd_data = stream[pos..(pos + header.length as usize - header.byte_len())].to_vec();
+        let sd_data = stream
+            .data(pos, header.length as usize - header.byte_len())
+            .into_owned();
 
         let pos = pos + sd_data.len();
 
@@ -1,12 +1,16 @@
 use std::mem;
 
+use crate::signal::Source;
+use crate::storage::Stream;
 use crate::utils;
 
-use super::block::Block;
+use super::block::{Block, WriteBlock};
 use super::block_header::*;
 use super::mdf4_enums::{BusType, SourceType};
 use super::mdf4_file::link_extract;
+use super::utils as mdf4_utils;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Siblock {
     header: BlockHeader,
@@ -18,6 +22,40 @@ pub struct Siblock {
     si_flags: u8,
     si_reserved: [u8; 5],
 }
+impl Siblock {
+    pub fn create(si_tx_name: u64, si_tx_path: u64, si_md_comment: u64) -> Self {
+        let mut block = Self::new();
+        block.si_tx_name = si_tx_name;
+        block.si_tx_path = si_tx_path;
+        block.si_md_comment = si_md_comment;
+        block.si_type = SourceType::Other;
+        block.si_bus_type = BusType::None;
+        block.header = BlockHeader::create("##SI", block.byte_len(), 3);
+        block
+    }
+
+    pub fn source(&self, stream: &dyn Stream, little_endian: bool) -> Source {
+        Source {
+            name: mdf4_utils::read_text(stream, self.si_tx_name, little_endian),
+            path: mdf4_utils::read_text(stream, self.si_tx_path, little_endian),
+            comment: mdf4_utils::read_text(stream, self.si_md_comment, little_endian),
+        }
+    }
+}
+
+impl WriteBlock for Siblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        utils::write(stream, self.si_tx_name, little_endian);
+        utils::write(stream, self.si_tx_path, little_endian);
+        utils::write(stream, self.si_md_comment, little_endian);
+        utils::write(stream, self.si_type.clone() as u8, little_endian);
+        utils::write(stream, self.si_bus_type.clone() as u8, little_endian);
+        utils::write(stream, self.si_flags, little_endian);
+        utils::write(stream, self.si_reserved, little_endian);
+    }
+}
+
 impl Block for Siblock {
     fn new() -> Self {
         Siblock {
@@ -43,7 +81,7 @@ impl Block for Siblock {
             si_reserved: [0_u8; 5],
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##SI".as_bytes()) {
@@ -93,7 +131,7 @@ impl Block for Siblock {
 mod tests {
     use crate::{
         mdf4::{
-            block::Block,
+            block::{Block, WriteBlock},
             mdf4_enums::{BusType, SourceType},
             si_block::Siblock,
         },
@@ -127,4 +165,14 @@ mod tests {
 
         assert_eq!(pos, si.byte_len());
     }
+
+    #[test]
+    fn write() {
+        let (pos, si) = Siblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        si.write(&mut stream, true);
+
+        assert_eq!(&RAW[..pos], &stream[..]);
+    }
 }
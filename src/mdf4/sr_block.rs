@@ -1,9 +1,13 @@
+use std::mem;
+
+use crate::storage::Stream;
 use crate::utils;
 
 use super::block::Block;
 use super::block_header::*;
 use super::mdf4_file::link_extract;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq)]
 struct Srblock {
     header: BlockHeader,
@@ -47,7 +51,7 @@ impl Block for Srblock {
             sr_reserved: [0_u8; 6],
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##SR".as_bytes()) {
@@ -81,6 +85,13 @@ impl Block for Srblock {
     }
 
     fn byte_len(&self) -> usize {
-        todo!()
+        self.header.byte_len()
+            + mem::size_of_val(&self.sr_sr_next)
+            + mem::size_of_val(&self.sr_data)
+            + mem::size_of_val(&self.sr_cycle_count)
+            + mem::size_of_val(&self.sr_interval)
+            + mem::size_of_val(&self.sr_sync_type)
+            + mem::size_of_val(&self.sr_flags)
+            + mem::size_of_val(&self.sr_reserved)
     }
 }
@@ -1,6 +1,7 @@
-use super::block::Block;
+use super::block::{Block, WriteBlock};
 use super::block_header::*;
 use super::utils as mdf4_utils;
+use crate::storage::Stream;
 use crate::utils;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,11 +11,28 @@ pub struct Txblock {
 }
 
 impl Txblock {
+    pub fn create(text: &str) -> Self {
+        let mut block = Self {
+            header: BlockHeader::create("##TX", 24, 0),
+            tx_data: text.to_string(),
+        };
+        block.header = BlockHeader::create("##TX", block.byte_len(), 0);
+        block
+    }
+
     pub fn text(&self) -> String {
         self.clone().tx_data
     }
 }
 
+impl WriteBlock for Txblock {
+    fn write(&self, stream: &mut Vec<u8>, little_endian: bool) {
+        self.header.write(stream, little_endian);
+        stream.extend_from_slice(self.tx_data.as_bytes());
+        stream.push(0);
+    }
+}
+
 impl Block for Txblock {
     fn new() -> Self {
         Self {
@@ -28,7 +46,7 @@ impl Block for Txblock {
             tx_data: String::new(),
         }
     }
-    fn read(stream: &[u8], position: usize, little_endian: bool) -> (usize, Self) {
+    fn read(stream: &dyn Stream, position: usize, little_endian: bool) -> (usize, Self) {
         let (pos, header) = BlockHeader::read(stream, position, little_endian);
 
         if !utils::eq(&header.id, "##TX".as_bytes()) {
@@ -38,7 +56,7 @@ impl Block for Txblock {
 
         let length = header.length as usize - header.byte_len();
 
-        let tx_data = mdf4_utils::str_from_u8(&stream[pos..(pos + length)]);
+        let tx_data = mdf4_utils::str_from_u8(&stream.bytes(pos, length));
 
         (pos + length, Self { header, tx_data })
     }
@@ -50,6 +68,7 @@ impl Block for Txblock {
 
 #[cfg(test)]
 mod tests {
+    use crate::mdf4::block::WriteBlock;
     use crate::mdf4::{block::Block, tx_block::Txblock};
 
     static RAW: [u8; 40] = [
@@ -74,4 +93,14 @@ mod tests {
         assert_eq!(33, pos);
         assert_eq!(33, tx.byte_len());
     }
+
+    #[test]
+    fn write() {
+        let (pos, block) = Txblock::read(&RAW, 0, true);
+
+        let mut stream = Vec::new();
+        block.write(&mut stream, true);
+
+        assert_eq!(&RAW[..pos], &stream[..]);
+    }
 }
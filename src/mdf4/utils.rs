@@ -1,3 +1,10 @@
+use super::block::Block;
+use super::block_header::BlockHeader;
+use super::md_block::Mdblock;
+use super::tx_block::Txblock;
+use crate::storage::Stream;
+use crate::utils;
+
 /// # Safety
 ///
 /// This function should not be called before the horsemen are ready.
@@ -12,3 +19,22 @@ unsafe fn str_from_u8_nul_utf8_unchecked(utf8_src: &[u8]) -> &str {
 pub fn str_from_u8(c_string: &[u8]) -> String {
     unsafe { str_from_u8_nul_utf8_unchecked(c_string).to_string() }
 }
+
+/// Reads the text behind a TX or MD link, taking the `<TX>` element of an MD
+/// block. A zero or corrupt link gives an empty string.
+pub fn read_text(stream: &dyn Stream, position: u64, little_endian: bool) -> String {
+    if position == 0 || BlockHeader::check(stream, position as usize, little_endian).is_err() {
+        return "".to_string();
+    }
+
+    match &stream.bytes(position as usize, 4)[..] {
+        b"##MD" => {
+            let (_pos, md) = Mdblock::read(stream, position as usize, little_endian);
+            utils::extract_tx(md.text().as_bytes())
+        }
+        _ => {
+            let (_pos, tx) = Txblock::read(stream, position as usize, little_endian);
+            tx.text()
+        }
+    }
+}
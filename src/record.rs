@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::mem;
 
 use crate::utils;
@@ -135,6 +136,31 @@ impl DataTypeRead {
     pub fn is_empty(self) -> bool {
         self.len() == 0
     }
+
+    /// The MDF 3 data type code, using the default byte order codes when the
+    /// byte order matches the file's.
+    pub fn code(self, little_endian: bool) -> u16 {
+        let default = self.little_endian == little_endian;
+        match (self.data_type, default, self.little_endian) {
+            (DataType::UnsignedInt, true, _) => UNSIGNED_INT_DEFAULT,
+            (DataType::SignedInt, true, _) => SIGNED_INT_DEFAULT,
+            (DataType::Float32, true, _) => FLOAT32_DEFAULT,
+            (DataType::Float64, true, _) => FLOAT64_DEFAULT,
+            (DataType::UnsignedInt, false, false) => UNSIGNED_INT_BIGENDIAN,
+            (DataType::SignedInt, false, false) => SIGNED_INT_BIGENDIAN,
+            (DataType::Float32, false, false) => FLOAT32_BIGENDIAN,
+            (DataType::Float64, false, false) => FLOAT64_BIGENDIAN,
+            (DataType::UnsignedInt, false, true) => UNSIGNED_INT_LITTLEENDIAN,
+            (DataType::SignedInt, false, true) => SIGNED_INT_LITTLEENDIAN,
+            (DataType::Float32, false, true) => FLOAT32_INT_LITTLEENDIAN,
+            (DataType::Float64, false, true) => FLOAT64_INT_LITTLEENDIAN,
+            (DataType::FFloat, _, _) => FFLOAT_DEFAULT,
+            (DataType::GFloat, _, _) => GFLOAT_DEFAULT,
+            (DataType::DFloat, _, _) => DFLOAT_DEFAULT,
+            (DataType::StringNullTerm, _, _) => STRING_NULL_TERM,
+            (DataType::ByteArray, _, _) => BYTE_ARRAY,
+        }
+    }
 }
 
 pub fn _print_record(value: Record) {
@@ -148,11 +174,10 @@ pub fn _print_record(value: Record) {
     };
 }
 
-
 #[derive(Debug, PartialEq, Clone)]
 pub enum Record {
-    Uint(u8),
-    Int(i8),
+    Uint(u64),
+    Int(i64),
     Float32(f32),
     Float64(f64),
     StringNullTerm(String),
@@ -175,33 +200,144 @@ impl Record {
             Record::Uint(number) => *number as f64,
             Record::Int(number) => *number as f64,
             Record::Float32(number) => *number as f64,
-            Record::Float64(number) => *number as f64,
+            Record::Float64(number) => *number,
             Record::StringNullTerm(string) => string.parse::<f64>().unwrap(),
         }
     }
 
-    fn string_null_term(stream: &[u8], _dtype: DataTypeRead) -> Self {
-        let mut string = String::new();
+    /// The value as a number, `None` for text that does not parse as one.
+    pub fn number(&self) -> Option<f64> {
+        match self {
+            Record::StringNullTerm(string) => string.trim().parse::<f64>().ok(),
+            _ => Some(self.extract()),
+        }
+    }
+
+    /// Narrows an integer record to `bit_count` bits starting at `bit_offset`,
+    /// sign extending signed values.
+    pub fn mask(self, bit_offset: u32, bit_count: u32) -> Self {
+        let shift = |value: u64| {
+            let value = value >> bit_offset;
+            if bit_count >= 64 {
+                value
+            } else {
+                value & ((1_u64 << bit_count) - 1)
+            }
+        };
 
-        for char in stream {
-            if *char == 0 {
-                break;
+        match self {
+            Record::Uint(number) => Record::Uint(shift(number)),
+            Record::Int(number) => {
+                let value = shift(number as u64);
+                if bit_count == 0 || bit_count >= 64 {
+                    Record::Int(value as i64)
+                } else {
+                    let unused = 64 - bit_count;
+                    Record::Int(((value << unused) as i64) >> unused)
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// Encodes the record into exactly `byte_len` bytes.
+    pub fn to_bytes(&self, byte_len: usize, little_endian: bool) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(byte_len);
+        match self {
+            Record::Uint(number) => utils::write(&mut bytes, *number, little_endian),
+            Record::Int(number) => utils::write(&mut bytes, *number, little_endian),
+            Record::Float32(number) => utils::write(&mut bytes, *number, little_endian),
+            Record::Float64(number) => utils::write(&mut bytes, *number, little_endian),
+            Record::StringNullTerm(string) => bytes.extend_from_slice(string.as_bytes()),
+        }
+
+        match self {
+            Record::Uint(_) | Record::Int(_) if !little_endian => {
+                bytes.drain(..bytes.len().saturating_sub(byte_len));
+            }
+            _ => {}
+        }
+        bytes.resize(byte_len, 0);
+
+        bytes
+    }
+
+    /// Picks the smallest storage that holds every sample, returning the type
+    /// and its length in bytes.
+    pub fn storage(samples: &[Record]) -> (DataType, usize) {
+        let fits = |bits: u32| {
+            [8_usize, 16, 32, 64]
+                .into_iter()
+                .find(|b| *b >= bits as usize)
+                .unwrap()
+                / 8
+        };
+
+        match samples.first() {
+            None | Some(Record::Float64(_)) => (DataType::Float64, 8),
+            Some(Record::Float32(_)) => (DataType::Float32, 4),
+            Some(Record::Uint(_)) => {
+                let max = samples
+                    .iter()
+                    .map(|x| x.extract() as u64)
+                    .max()
+                    .unwrap_or(0);
+                (DataType::UnsignedInt, fits(64 - max.leading_zeros()))
+            }
+            Some(Record::Int(_)) => {
+                let bits = samples
+                    .iter()
+                    .map(|x| {
+                        let value = x.extract() as i64;
+                        65 - if value < 0 {
+                            value.leading_ones()
+                        } else {
+                            value.leading_zeros()
+                        }
+                    })
+                    .max()
+                    .unwrap_or(1);
+                (DataType::SignedInt, fits(bits.min(64)))
+            }
+            Some(Record::StringNullTerm(_)) => {
+                let longest = samples
+                    .iter()
+                    .map(|x| match x {
+                        Record::StringNullTerm(string) => string.len(),
+                        _ => 0,
+                    })
+                    .max()
+                    .unwrap_or(0);
+                (DataType::StringNullTerm, longest + 1)
             }
-            string.push(*char as char);
         }
-        Record::StringNullTerm(string)
+    }
+
+    fn string_null_term(stream: &[u8], _dtype: DataTypeRead) -> Self {
+        let end = stream.iter().position(|x| *x == 0).unwrap_or(stream.len());
+
+        Record::StringNullTerm(String::from_utf8_lossy(&stream[..end]).into_owned())
     }
 
     fn unsigned_int(stream: &[u8], dtype: DataTypeRead) -> Self {
-        let records = utils::read(stream, dtype.little_endian, &mut 0);
+        let mut bytes = [0_u8; 8];
+        let len = stream.len().min(8);
+        if dtype.little_endian {
+            bytes[..len].copy_from_slice(&stream[..len]);
+        } else {
+            bytes[8 - len..].copy_from_slice(&stream[stream.len() - len..]);
+        }
+        let records = utils::read(&bytes, dtype.little_endian, &mut 0);
 
         Self::Uint(records)
     }
 
     fn signed_int(stream: &[u8], dtype: DataTypeRead) -> Self {
-        let records = utils::read(stream, dtype.little_endian, &mut 0);
-
-        Self::Int(records)
+        let bits = (stream.len().min(8) * 8) as u32;
+        match Self::unsigned_int(stream, dtype) {
+            Self::Uint(number) => Self::Int(number as i64).mask(0, bits),
+            _ => unreachable!(),
+        }
     }
 
     fn float32(stream: &[u8], dtype: DataTypeRead) -> Self {
@@ -215,3 +351,53 @@ impl Record {
         Self::Float64(records)
     }
 }
+
+/// The records of a channel group, borrowed from the file where it holds
+/// them uncompressed in a single block.
+#[derive(Debug, Clone)]
+pub struct RecordView<'a> {
+    data: Cow<'a, [u8]>,
+    record_size: usize,
+    record_number: usize,
+}
+
+impl<'a> RecordView<'a> {
+    /// Records of `record_size` bytes in `data`, as many as it holds up to
+    /// `record_number`.
+    pub fn create(data: Cow<'a, [u8]>, record_size: usize, record_number: usize) -> Self {
+        let record_number = match record_size {
+            0 => 0,
+            size => record_number.min(data.len() / size),
+        };
+
+        Self {
+            data,
+            record_size,
+            record_number,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.record_number
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.record_number == 0
+    }
+
+    /// True when the records are read straight from the file, not a copy.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.data, Cow::Borrowed(_))
+    }
+
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        let start = index * self.record_size;
+        (index < self.record_number).then(|| &self.data[start..start + self.record_size])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.data
+            .chunks_exact(self.record_size.max(1))
+            .take(self.record_number)
+    }
+}
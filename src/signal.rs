@@ -1,3 +1,6 @@
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::conversion::Conversion;
 use crate::record::Record;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -8,6 +11,16 @@ pub struct Signal {
     pub name: String,
     pub comment: String,
     pub raw: bool,
+    pub conversion: Option<Conversion>,
+    pub source: Option<Source>,
+}
+
+/// The ECU, bus or tool a signal was acquired from.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Source {
+    pub name: String,
+    pub path: String,
+    pub comment: String,
 }
 
 impl Signal {
@@ -37,112 +50,226 @@ impl Signal {
             name,
             comment,
             raw,
+            conversion: None,
+            source: None,
         }
     }
-    #[must_use]
-    pub fn cut(&self, start: f64, end: f64, include_ends: bool) -> Self {
-        let mut adjusted = self.clone();
-        if adjusted.is_empty() {
-            return adjusted;
-        }
 
-        if (start - end).abs() < 0.001 {
-            return adjusted;
-        }
+    #[must_use]
+    pub fn with_conversion(mut self, conversion: Conversion) -> Self {
+        self.conversion = Some(conversion);
+        self
+    }
 
-        let (start_index, end_index) = if include_ends {
-            let start_index = self
-                .timestamps
-                .iter()
-                .position(|&x| start <= x)
-                .expect("Couldn't find time within the range given");
-            let end_index = self
-                .timestamps
-                .iter()
-                .position(|&x| x <= end)
-                .expect("Couldn't find time within the range given");
-            (start_index, end_index)
+    #[must_use]
+    pub fn with_source(mut self, source: Source) -> Self {
+        self.source = Some(source);
+        self
+    }
+    /// Keeps the samples between `start` and `end`, including samples at the
+    /// ends when `include_ends` is set. `time_from_zero` shifts the
+    /// timestamps so `start` becomes zero.
+    #[must_use]
+    pub fn cut(&self, start: f64, end: f64, include_ends: bool, time_from_zero: bool) -> Self {
+        let (first, last) = if include_ends {
+            (
+                self.timestamps.partition_point(|&x| x < start),
+                self.timestamps.partition_point(|&x| x <= end),
+            )
         } else {
-            let start_index = self
-                .timestamps
-                .iter()
-                .position(|&x| start < x)
-                .expect("Couldn't find time within the range given");
-            let end_index = self
-                .timestamps
-                .iter()
-                .position(|&x| x < end)
-                .expect("Couldn't find time within the range given");
-            (start_index, end_index)
+            (
+                self.timestamps.partition_point(|&x| x <= start),
+                self.timestamps.partition_point(|&x| x < end),
+            )
         };
 
-        if start_index == end_index {}
+        self.slice(first, last, if time_from_zero { start } else { 0.0 })
+    }
+
+    /// Like [`Signal::cut`] with the ends included, adding samples
+    /// interpolated at `start` and `end` when they fall inside the recorded
+    /// range.
+    #[must_use]
+    pub fn cut_interpolated(
+        &self,
+        start: f64,
+        end: f64,
+        interpolation_mode: Interpolation,
+        time_from_zero: bool,
+    ) -> Self {
+        let cut = self.cut(start, end, true, false);
+        let inside = |time: f64| {
+            self.timestamps.first().is_some_and(|&x| x <= time)
+                && self.timestamps.last().is_some_and(|&x| time <= x)
+        };
 
-        adjusted.timestamps = adjusted.timestamps[start_index..end_index].to_vec();
-        adjusted.samples = adjusted.samples[start_index..end_index].to_vec();
+        let mut timestamps = cut.timestamps;
+        if start <= end && inside(start) && timestamps.first() != Some(&start) {
+            timestamps.insert(0, start);
+        }
+        if start <= end && inside(end) && timestamps.last() != Some(&end) {
+            timestamps.push(end);
+        }
 
-        adjusted
+        let shift = if time_from_zero { start } else { 0.0 };
+        let mut cut = self.interp(timestamps, interpolation_mode);
+        cut.timestamps.iter_mut().for_each(|x| *x -= shift);
+        cut
     }
 
+    /// Keeps the samples from index `start` up to, not including, `end`.
+    /// `time_from_zero` shifts the timestamps so the first kept sample is at
+    /// zero.
     #[must_use]
-    pub fn extend(&self, other: Self) -> Self {
-        let last_stamp = if !self.is_empty() {
-            *self.timestamps.last().unwrap()
-        } else {
-            0.0
+    pub fn cut_index(&self, start: usize, end: usize, time_from_zero: bool) -> Self {
+        let shift = match self.timestamps.get(start) {
+            Some(&time) if time_from_zero => time,
+            _ => 0.0,
         };
 
-        if !other.is_empty() {
-            let other_first_sample = other.timestamps[0];
-            let mut timestamps = if other_first_sample <= last_stamp {
-                other
-                    .timestamps
-                    .into_iter()
-                    .map(|x| x + last_stamp)
-                    .collect()
-            } else {
-                other.timestamps
-            };
-
-            let mut new_samples = Vec::with_capacity(self.samples.len() + other.samples.len());
-            new_samples.append(&mut self.samples.clone());
-            #[allow(clippy::redundant_clone)]
-            new_samples.append(&mut other.samples.clone());
-            let mut new_timestamps = Vec::with_capacity(self.timestamps.len() + timestamps.len());
-            new_timestamps.append(&mut self.timestamps.clone());
-            new_timestamps.append(&mut timestamps);
+        self.slice(start, end, shift)
+    }
 
-            Self {
-                samples: new_samples,
-                timestamps: new_timestamps,
-                unit: self.unit.clone(),
-                name: self.name.clone(),
-                comment: self.comment.clone(),
-                raw: self.raw,
+    fn slice(&self, first: usize, last: usize, shift: f64) -> Self {
+        let last = last.min(self.len());
+        let first = first.min(last);
+
+        Self {
+            samples: self.samples[first..last].to_vec(),
+            timestamps: self.timestamps[first..last]
+                .iter()
+                .map(|x| x - shift)
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Appends the samples of `other`, which has to start after the last
+    /// timestamp of the signal.
+    pub fn extend(&mut self, other: Self) -> Result<(), &'static str> {
+        match (self.timestamps.last(), other.timestamps.first()) {
+            (Some(end), Some(start)) if start <= end => Err("Timestamps overlap"),
+            _ => {
+                self.timestamps.extend(other.timestamps);
+                self.samples.extend(other.samples);
+                Ok(())
             }
-        } else {
-            self.clone()
         }
     }
 
+    /// Samples the signal at `new_timestamps`, holding the first and last
+    /// sample outside the recorded range.
     #[must_use]
     pub fn interp(&self, new_timestamps: Vec<f64>, interpolation_mode: Interpolation) -> Self {
-        if self.samples.is_empty() || new_timestamps.is_empty() {
+        self.interp_with(new_timestamps, interpolation_mode, Extrapolation::Hold)
+    }
+
+    /// Samples the signal at `new_timestamps`. Integer samples are rounded
+    /// back to their type, text and value to text channels always repeat the
+    /// previous sample.
+    #[must_use]
+    pub fn interp_with(
+        &self,
+        new_timestamps: Vec<f64>,
+        interpolation_mode: Interpolation,
+        extrapolation: Extrapolation,
+    ) -> Self {
+        if self.samples.is_empty() {
             return self.clone();
         }
 
-        let signal = self.clone();
-        match interpolation_mode {
-            Interpolation::RepeatPreviousSample => {}
-            Interpolation::LinearInterpolation => {}
+        let (first, last) = (self.timestamps[0], self.timestamps[self.len() - 1]);
+        let new_timestamps: Vec<f64> = match extrapolation {
+            Extrapolation::Drop => new_timestamps
+                .into_iter()
+                .filter(|&x| first <= x && x <= last)
+                .collect(),
+            _ => new_timestamps,
+        };
+
+        let discrete = matches!(self.samples[0], Record::StringNullTerm(_))
+            || (self.raw && matches!(self.conversion, Some(Conversion::ValueToText { .. })));
+        let mode = if discrete {
+            Interpolation::RepeatPreviousSample
+        } else {
+            interpolation_mode
+        };
+
+        let samples = new_timestamps
+            .iter()
+            .map(|&time| self.sample_at(time, mode, extrapolation))
+            .collect();
+
+        Self {
+            samples,
+            timestamps: new_timestamps,
+            ..self.clone()
+        }
+    }
+
+    fn sample_at(&self, time: f64, mode: Interpolation, extrapolation: Extrapolation) -> Record {
+        let last = self.len() - 1;
+        if last == 0 {
+            return self.samples[0].clone();
+        }
+
+        // Index of the first sample after `time`
+        let next = self.timestamps.partition_point(|&x| x <= time);
+        let (before, after) = match (next, extrapolation) {
+            (0, Extrapolation::Linear) if mode == Interpolation::LinearInterpolation => (0, 1),
+            (0, _) => return self.samples[0].clone(),
+            (n, Extrapolation::Linear)
+                if n > last && mode == Interpolation::LinearInterpolation =>
+            {
+                (last - 1, last)
+            }
+            (n, _) if n > last => return self.samples[last].clone(),
+            (n, _) => (n - 1, n),
+        };
+
+        if self.timestamps[before] == time {
+            return self.samples[before].clone();
         }
 
-        signal
+        match mode {
+            Interpolation::RepeatPreviousSample => self.samples[before].clone(),
+            Interpolation::NextSample => self.samples[after].clone(),
+            Interpolation::LinearInterpolation => {
+                let (t0, t1) = (self.timestamps[before], self.timestamps[after]);
+                let (y0, y1) = (
+                    self.samples[before].extract(),
+                    self.samples[after].extract(),
+                );
+                let value = if t1 == t0 {
+                    y1
+                } else {
+                    y0 + (y1 - y0) * (time - t0) / (t1 - t0)
+                };
+
+                match self.samples[before] {
+                    Record::Uint(_) => Record::Uint(value.round().max(0.0) as u64),
+                    Record::Int(_) => Record::Int(value.round() as i64),
+                    Record::Float32(_) => Record::Float32(value as f32),
+                    _ => Record::Float64(value),
+                }
+            }
+        }
     }
 
     pub fn as_type() {}
 
-    pub fn physical() {}
+    /// Applies the conversion to raw samples, leaving physical signals as they are.
+    #[must_use]
+    pub fn physical(&self) -> Self {
+        match (&self.conversion, self.raw) {
+            (Some(conversion), true) => Self {
+                samples: conversion.convert_all(&self.samples),
+                raw: false,
+                ..self.clone()
+            },
+            _ => self.clone(),
+        }
+    }
 
     pub fn validate() {}
 
@@ -155,10 +282,440 @@ impl Signal {
     pub fn max_time(&self) -> f64 {
         *self.timestamps.last().expect("No time value found")
     }
+
+    /// Samples above `other`, as 0/1 unsigned samples.
+    #[must_use]
+    pub fn gt<'a>(&self, other: impl Into<Operand<'a>>) -> Self {
+        self.compare(other.into(), ">", |a, b| a > b)
+    }
+
+    /// Samples below `other`, as 0/1 unsigned samples.
+    #[must_use]
+    pub fn lt<'a>(&self, other: impl Into<Operand<'a>>) -> Self {
+        self.compare(other.into(), "<", |a, b| a < b)
+    }
+
+    /// Samples equal to `other`, as 0/1 unsigned samples. Use `==` to compare
+    /// whole signals.
+    #[must_use]
+    pub fn equal_to<'a>(&self, other: impl Into<Operand<'a>>) -> Self {
+        self.compare(other.into(), "==", |a, b| a == b)
+    }
+
+    fn compare(&self, other: Operand, symbol: &str, op: fn(f64, f64) -> bool) -> Self {
+        let result = self.combine(other, symbol, |a, b| f64::from(u8::from(op(a, b))));
+        Self {
+            samples: result
+                .samples
+                .iter()
+                .map(|x| Record::Uint(x.extract() as u64))
+                .collect(),
+            unit: String::new(),
+            ..result
+        }
+    }
+
+    /// Applies `op` to the physical samples, aligning two signals on the
+    /// union of their timestamps with linear interpolation. Samples that are
+    /// not numbers are left out.
+    fn combine(&self, other: Operand, symbol: &str, op: impl Fn(f64, f64) -> f64) -> Self {
+        let float = |signal: &Self| {
+            let signal = signal.physical();
+            let (timestamps, samples) = signal
+                .timestamps
+                .iter()
+                .zip(&signal.samples)
+                .filter_map(|(&t, x)| Some((t, Record::Float64(x.number()?))))
+                .unzip();
+            Self {
+                timestamps,
+                samples,
+                ..signal
+            }
+        };
+        let left = float(self);
+        let (name, left, right) = match other {
+            Operand::Value(value) => {
+                let right = vec![value; left.len()];
+                (format!("{} {symbol} {value}", self.name), left, right)
+            }
+            Operand::Signal(other) => {
+                let right = float(other);
+                let mut timestamps = [&left.timestamps[..], &right.timestamps[..]].concat();
+                if left.is_empty() || right.is_empty() {
+                    timestamps.clear();
+                }
+                timestamps.sort_by(f64::total_cmp);
+                timestamps.dedup();
+
+                let mode = Interpolation::LinearInterpolation;
+                let right = right.interp(timestamps.clone(), mode);
+                let right = right.samples.iter().map(Record::extract).collect();
+                let name = format!("{} {symbol} {}", self.name, other.name);
+                (name, left.interp(timestamps, mode), right)
+            }
+        };
+
+        Self {
+            samples: left
+                .samples
+                .iter()
+                .zip(right)
+                .map(|(a, b)| Record::Float64(op(a.extract(), b)))
+                .collect(),
+            name,
+            conversion: None,
+            ..left
+        }
+    }
+}
+
+/// The right hand side of a [`Signal`] comparison.
+#[derive(Debug, Clone, Copy)]
+pub enum Operand<'a> {
+    Signal(&'a Signal),
+    Value(f64),
+}
+
+impl<'a> From<&'a Signal> for Operand<'a> {
+    fn from(signal: &'a Signal) -> Self {
+        Self::Signal(signal)
+    }
+}
+
+impl From<f64> for Operand<'_> {
+    fn from(value: f64) -> Self {
+        Self::Value(value)
+    }
+}
+
+fn sum_unit(left: &str, right: &str) -> String {
+    if left == right {
+        left.to_string()
+    } else {
+        String::new()
+    }
+}
+
+fn product_unit(left: &str, right: &str) -> String {
+    match (left.is_empty(), right.is_empty()) {
+        (_, true) => left.to_string(),
+        (true, false) => right.to_string(),
+        (false, false) => format!("{left}*{right}"),
+    }
+}
+
+fn quotient_unit(left: &str, right: &str) -> String {
+    match (left.is_empty(), right.is_empty()) {
+        _ if left == right => String::new(),
+        (_, true) => left.to_string(),
+        (true, false) => format!("1/{right}"),
+        (false, false) => format!("{left}/{right}"),
+    }
+}
+
+macro_rules! impl_signal_op {
+    ($trait:ident, $method:ident, $symbol:literal, $unit:ident) => {
+        impl $trait<&Signal> for &Signal {
+            type Output = Signal;
+
+            fn $method(self, other: &Signal) -> Signal {
+                Signal {
+                    unit: $unit(&self.unit, &other.unit),
+                    ..self.combine(Operand::Signal(other), $symbol, |a, b| a.$method(b))
+                }
+            }
+        }
+
+        impl $trait for Signal {
+            type Output = Signal;
+
+            fn $method(self, other: Signal) -> Signal {
+                (&self).$method(&other)
+            }
+        }
+
+        impl $trait<f64> for &Signal {
+            type Output = Signal;
+
+            fn $method(self, other: f64) -> Signal {
+                self.combine(Operand::Value(other), $symbol, |a, b| a.$method(b))
+            }
+        }
+
+        impl $trait<f64> for Signal {
+            type Output = Signal;
+
+            fn $method(self, other: f64) -> Signal {
+                (&self).$method(other)
+            }
+        }
+    };
 }
 
-#[derive(PartialEq, Eq)]
+impl_signal_op!(Add, add, "+", sum_unit);
+impl_signal_op!(Sub, sub, "-", sum_unit);
+impl_signal_op!(Mul, mul, "*", product_unit);
+impl_signal_op!(Div, div, "/", quotient_unit);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Interpolation {
     RepeatPreviousSample,
     LinearInterpolation,
+    NextSample,
+}
+
+/// How [`Signal::interp_with`] fills timestamps outside the recorded range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Extrapolation {
+    /// Repeats the first or last sample.
+    #[default]
+    Hold,
+    /// Extends the slope of the first or last two samples with linear
+    /// interpolation, holds otherwise.
+    Linear,
+    /// Leaves the timestamps outside the range out of the result.
+    Drop,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal() -> Signal {
+        Signal::new(
+            vec![1.0, 2.0, 3.0],
+            vec![Record::Uint(10), Record::Uint(20), Record::Uint(40)],
+            "".to_string(),
+            "signal".to_string(),
+            "".to_string(),
+            false,
+        )
+    }
+
+    #[test]
+    fn interp() {
+        let timestamps = vec![0.0, 1.5, 2.0, 2.5, 4.0];
+
+        let previous = signal().interp(timestamps.clone(), Interpolation::RepeatPreviousSample);
+        assert_eq!(previous.timestamps, timestamps);
+        assert_eq!(previous.samples, [10, 10, 20, 20, 40].map(Record::Uint));
+
+        let next = signal().interp(timestamps.clone(), Interpolation::NextSample);
+        assert_eq!(next.samples, [10, 20, 20, 40, 40].map(Record::Uint));
+
+        let linear = signal().interp(timestamps.clone(), Interpolation::LinearInterpolation);
+        assert_eq!(linear.samples, [10, 15, 20, 30, 40].map(Record::Uint));
+
+        let mut signed = signal();
+        signed.samples = [-1, 2, 4].map(Record::Int).to_vec();
+        let linear = signed.interp(vec![1.5, 2.25], Interpolation::LinearInterpolation);
+        assert_eq!(linear.samples, [1, 3].map(Record::Int));
+
+        let mut float = signal();
+        float.samples = [1.0, 2.0, 4.0].map(Record::Float32).to_vec();
+        let linear = float.interp(vec![2.25], Interpolation::LinearInterpolation);
+        assert_eq!(linear.samples, [Record::Float32(2.5)]);
+    }
+
+    #[test]
+    fn interp_text() {
+        let mut text = signal();
+        text.samples = ["a", "b", "c"]
+            .map(|x| Record::StringNullTerm(x.to_string()))
+            .to_vec();
+        let linear = text.interp(vec![1.5, 2.5], Interpolation::LinearInterpolation);
+        assert_eq!(linear.samples, text.samples[..2]);
+        let next = text.interp(vec![1.5], Interpolation::NextSample);
+        assert_eq!(next.samples, text.samples[..1]);
+
+        let states = signal().with_conversion(Conversion::ValueToText {
+            values: vec![10.0, 20.0, 40.0],
+            texts: vec!["low".to_string(), "mid".to_string(), "high".to_string()],
+            default: String::new(),
+        });
+        let states = Signal {
+            raw: true,
+            ..states
+        };
+        let linear = states.interp(vec![1.5], Interpolation::LinearInterpolation);
+        assert_eq!(linear.samples, [Record::Uint(10)]);
+    }
+
+    #[test]
+    fn extrapolation() {
+        let timestamps = vec![0.0, 2.0, 5.0];
+
+        let hold = signal().interp_with(
+            timestamps.clone(),
+            Interpolation::LinearInterpolation,
+            Extrapolation::Hold,
+        );
+        assert_eq!(hold.samples, [10, 20, 40].map(Record::Uint));
+
+        let linear = signal().interp_with(
+            timestamps.clone(),
+            Interpolation::LinearInterpolation,
+            Extrapolation::Linear,
+        );
+        assert_eq!(linear.samples, [0, 20, 80].map(Record::Uint));
+
+        let next = signal().interp_with(
+            timestamps.clone(),
+            Interpolation::NextSample,
+            Extrapolation::Linear,
+        );
+        assert_eq!(next.samples, [10, 20, 40].map(Record::Uint));
+
+        let dropped = signal().interp_with(
+            timestamps,
+            Interpolation::LinearInterpolation,
+            Extrapolation::Drop,
+        );
+        assert_eq!(dropped.timestamps, vec![2.0]);
+        assert_eq!(dropped.samples, [Record::Uint(20)]);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let voltage = Signal {
+            unit: "V".to_string(),
+            name: "voltage".to_string(),
+            ..signal()
+        };
+        let current = Signal::new(
+            vec![1.5, 2.5],
+            vec![Record::Float64(2.0), Record::Float64(4.0)],
+            "A".to_string(),
+            "current".to_string(),
+            "".to_string(),
+            false,
+        );
+
+        let power = &voltage * &current;
+        assert_eq!(power.name, "voltage * current");
+        assert_eq!(power.unit, "V*A");
+        assert_eq!(power.timestamps, vec![1.0, 1.5, 2.0, 2.5, 3.0]);
+        assert_eq!(
+            power.samples,
+            [20.0, 30.0, 60.0, 120.0, 160.0].map(Record::Float64)
+        );
+
+        let resistance = voltage.clone() / current.clone();
+        assert_eq!(resistance.unit, "V/A");
+        assert_eq!(resistance.samples[1], Record::Float64(7.5));
+        assert_eq!((&voltage / &voltage).unit, "");
+        assert_eq!((&voltage + &current).unit, "");
+        assert_eq!((&voltage - &voltage).samples, [0.0; 3].map(Record::Float64));
+
+        let scaled = voltage.clone() * 0.5 + 1.0;
+        assert_eq!(scaled.unit, "V");
+        assert_eq!(scaled.timestamps, voltage.timestamps);
+        assert_eq!(scaled.samples, [6.0, 11.0, 21.0].map(Record::Float64));
+
+        let raw = Signal {
+            raw: true,
+            ..signal().with_conversion(Conversion::Linear {
+                offset: 1.0,
+                factor: 2.0,
+            })
+        };
+        assert_eq!(
+            (&raw - 1.0).samples,
+            [20.0, 40.0, 80.0].map(Record::Float64)
+        );
+    }
+
+    #[test]
+    fn comparison() {
+        let threshold = Signal::new(
+            vec![1.0, 3.0],
+            vec![Record::Uint(20), Record::Uint(20)],
+            "".to_string(),
+            "threshold".to_string(),
+            "".to_string(),
+            false,
+        );
+
+        let above = signal().gt(&threshold);
+        assert_eq!(above.timestamps, vec![1.0, 2.0, 3.0]);
+        assert_eq!(above.samples, [0, 0, 1].map(Record::Uint));
+        assert_eq!(signal().lt(20.0).samples, [1, 0, 0].map(Record::Uint));
+        assert_eq!(signal().equal_to(20.0).samples, [0, 1, 0].map(Record::Uint));
+        assert_eq!(signal().equal_to(&threshold).name, "signal == threshold");
+        assert!(signal() == signal());
+
+        let text = Signal::new(
+            vec![1.0, 2.0, 3.0],
+            ["5", "high", "30"]
+                .map(|x| Record::StringNullTerm(x.to_string()))
+                .to_vec(),
+            "".to_string(),
+            "text".to_string(),
+            "".to_string(),
+            false,
+        );
+        let above = text.gt(10.0);
+        assert_eq!(above.timestamps, vec![1.0, 3.0]);
+        assert_eq!(above.samples, [0, 1].map(Record::Uint));
+        assert_eq!((&text + &signal()).timestamps, vec![1.0, 2.0, 3.0]);
+        let words = text.cut(2.0, 2.0, true, false);
+        assert!(words.gt(&signal()).is_empty());
+    }
+
+    #[test]
+    fn extend() {
+        let mut extended = signal();
+        let later = Signal {
+            timestamps: vec![4.0, 5.0, 6.0],
+            ..signal()
+        };
+        extended.extend(later).unwrap();
+        assert_eq!(extended.timestamps, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(extended.samples.len(), 6);
+
+        assert_eq!(extended.extend(signal()), Err("Timestamps overlap"));
+        assert_eq!(extended.len(), 6);
+    }
+
+    #[test]
+    fn cut() {
+        let signal = Signal::new(
+            (0..10).map(f64::from).collect(),
+            (0..10).map(Record::Uint).collect(),
+            "".to_string(),
+            "signal".to_string(),
+            "".to_string(),
+            false,
+        );
+
+        let cut = signal.cut(2.0, 5.0, true, false);
+        assert_eq!(cut.timestamps, vec![2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(cut.samples, (2..6).map(Record::Uint).collect::<Vec<_>>());
+
+        let cut = signal.cut(2.0, 5.0, false, true);
+        assert_eq!(cut.timestamps, vec![1.0, 2.0]);
+        assert_eq!(cut.samples, [3, 4].map(Record::Uint));
+
+        assert_eq!(signal.cut(-5.0, 0.5, true, false).len(), 1);
+        assert!(signal.cut(20.0, 30.0, true, false).is_empty());
+        assert!(signal.cut(-5.0, -1.0, true, false).is_empty());
+        assert!(signal.cut(5.0, 2.0, true, false).is_empty());
+
+        let cut = signal.cut_interpolated(2.5, 4.2, Interpolation::LinearInterpolation, true);
+        assert_eq!(cut.timestamps.len(), 4);
+        assert_eq!(cut.timestamps[0], 0.0);
+        assert!((cut.timestamps[3] - 1.7).abs() < 1e-9);
+        assert_eq!(cut.samples, [3, 3, 4, 4].map(Record::Uint));
+        let cut = signal.cut_interpolated(-1.0, 1.0, Interpolation::LinearInterpolation, false);
+        assert_eq!(cut.timestamps, vec![0.0, 1.0]);
+        assert!(signal
+            .cut_interpolated(20.0, 30.0, Interpolation::LinearInterpolation, false)
+            .is_empty());
+
+        let cut = signal.cut_index(7, 20, true);
+        assert_eq!(cut.timestamps, vec![0.0, 1.0, 2.0]);
+        assert_eq!(cut.samples, [7, 8, 9].map(Record::Uint));
+        assert!(signal.cut_index(12, 20, false).is_empty());
+    }
 }
@@ -0,0 +1,358 @@
+//! The bytes behind an opened file.
+//!
+//! Block readers take a [`Stream`] and absolute positions. A [`Storage`]
+//! opened from a path only reads the parts of the file the readers touch, so
+//! opening a file loads its metadata blocks and data blocks are read when
+//! the samples are.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Bytes are read in chunks of at least this size, so the small blocks
+/// following each other in the metadata are read together.
+const CHUNK: usize = 4096;
+
+/// Chunks read from a file are dropped, least recently used first, once
+/// they take more than this many bytes.
+const CACHE: usize = 1 << 24;
+
+/// Storages are compared in parts of this size.
+const COMPARE: usize = 1 << 20;
+
+/// Shared between threads by the `parallel` feature.
+pub trait Stream: Sync {
+    /// `length` bytes from `position`, panics past the end of the stream.
+    fn bytes(&self, position: usize, length: usize) -> Cow<'_, [u8]>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Like [`Stream::bytes`] but for large data blocks, which streams read
+    /// from a file do not keep around.
+    fn data(&self, position: usize, length: usize) -> Cow<'_, [u8]> {
+        self.bytes(position, length)
+    }
+}
+
+impl Stream for [u8] {
+    fn bytes(&self, position: usize, length: usize) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self[position..position + length])
+    }
+
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+}
+
+impl Stream for Vec<u8> {
+    fn bytes(&self, position: usize, length: usize) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self[position..position + length])
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+}
+
+impl<const N: usize> Stream for [u8; N] {
+    fn bytes(&self, position: usize, length: usize) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self[position..position + length])
+    }
+
+    fn len(&self) -> usize {
+        N
+    }
+}
+
+/// The bytes of a file, either in memory, read from disk on demand or
+/// memory mapped.
+pub enum Storage {
+    Memory(Vec<u8>),
+    File(FileStream),
+    /// A map of the file at the path.
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap, PathBuf),
+}
+
+impl Storage {
+    /// Opens `path`, memory mapped with the `mmap` feature.
+    #[cfg(feature = "mmap")]
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::map(path)
+    }
+
+    /// Opens `path`, memory mapped with the `mmap` feature.
+    #[cfg(not(feature = "mmap"))]
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::open(path)
+    }
+
+    /// Opens `path` for reading on demand.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::File(FileStream::open(path.as_ref())?))
+    }
+
+    /// Maps `path` into memory.
+    ///
+    /// The file must not be changed while it is open.
+    #[cfg(feature = "mmap")]
+    pub fn map(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path.as_ref())?;
+        // Safety: the file is not expected to change while mapped
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Self::Mapped(map, path.as_ref().to_path_buf()))
+    }
+
+    /// The path of the file the bytes are read from, if any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Memory(_) => None,
+            Self::File(file) => Some(&file.path),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(_, path) => Some(path),
+        }
+    }
+
+    /// Writes all bytes to `writer`.
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        match self {
+            Self::Memory(bytes) => writer.write_all(bytes),
+            Self::File(file) => file.write_to(writer),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(map, _) => writer.write_all(map),
+        }
+    }
+}
+
+impl Stream for Storage {
+    fn bytes(&self, position: usize, length: usize) -> Cow<'_, [u8]> {
+        match self {
+            Self::Memory(bytes) => bytes.bytes(position, length),
+            Self::File(file) => Cow::Owned(
+                file.bytes(position, length)
+                    .unwrap_or_else(|x| panic!("Error: {x}")),
+            ),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(map, _) => Cow::Borrowed(&map[position..position + length]),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Memory(bytes) => bytes.len(),
+            Self::File(file) => file.length,
+            #[cfg(feature = "mmap")]
+            Self::Mapped(map, _) => map.len(),
+        }
+    }
+
+    fn data(&self, position: usize, length: usize) -> Cow<'_, [u8]> {
+        match self {
+            Self::File(file) => Cow::Owned(
+                file.read(position, length)
+                    .unwrap_or_else(|x| panic!("Error: {x}")),
+            ),
+            storage => storage.bytes(position, length),
+        }
+    }
+}
+
+impl fmt::Debug for Storage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Memory(bytes) => write!(f, "Memory({} bytes)", bytes.len()),
+            Self::File(file) => write!(f, "File({:?})", file.path),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(_, path) => write!(f, "Mapped({path:?})"),
+        }
+    }
+}
+
+impl Clone for Storage {
+    /// Files are opened or mapped again, panics if that fails.
+    fn clone(&self) -> Self {
+        match self {
+            Self::Memory(bytes) => Self::Memory(bytes.clone()),
+            Self::File(file) => {
+                Self::File(FileStream::open(&file.path).expect("Could not read file"))
+            }
+            #[cfg(feature = "mmap")]
+            Self::Mapped(_, path) => Self::map(path).expect("Could not read file"),
+        }
+    }
+}
+
+impl PartialEq for Storage {
+    /// Storages of the same file are equal, others are compared a part at a
+    /// time so large files are never read whole.
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        if self.path().is_some() && self.path() == other.path() {
+            return true;
+        }
+
+        (0..self.len()).step_by(COMPARE).all(|position| {
+            let length = COMPARE.min(self.len() - position);
+            self.data(position, length) == other.data(position, length)
+        })
+    }
+}
+
+/// Chunks of a file by start position and length, with the time each was
+/// last used.
+type Chunks = BTreeMap<(usize, usize), (Box<[u8]>, u64)>;
+
+/// A file read with `Read + Seek`, keeping the chunks read last through
+/// [`Stream::bytes`] in memory.
+pub struct FileStream {
+    path: PathBuf,
+    length: usize,
+    file: Mutex<File>,
+    cache: Mutex<Cache>,
+}
+
+/// The chunks read so far, bounded to [`CACHE`] bytes.
+#[derive(Default)]
+struct Cache {
+    chunks: Chunks,
+    size: usize,
+    clock: u64,
+}
+
+impl FileStream {
+    fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            length: file.metadata()?.len() as usize,
+            file: Mutex::new(file),
+            cache: Mutex::new(Cache::default()),
+        })
+    }
+
+    /// An error if `length` bytes from `position` do not lie in the file.
+    fn check(&self, position: usize, length: usize) -> Result<(), &'static str> {
+        match position.checked_add(length) {
+            Some(end) if end <= self.length => Ok(()),
+            _ => Err("reading past the end of the file"),
+        }
+    }
+
+    fn read(&self, position: usize, length: usize) -> Result<Vec<u8>, &'static str> {
+        self.check(position, length)?;
+
+        let mut file = self.file.lock().expect("File lock poisoned");
+        let mut bytes = vec![0; length];
+        file.seek(SeekFrom::Start(position as u64))
+            .and_then(|_| file.read_exact(&mut bytes))
+            .map_err(|_| "Could not read file")?;
+        Ok(bytes)
+    }
+
+    /// A copy of `length` bytes from `position`, read through the cache.
+    fn bytes(&self, position: usize, length: usize) -> Result<Vec<u8>, &'static str> {
+        self.check(position, length)?;
+
+        let mut cache = self.cache.lock().expect("Cache lock poisoned");
+        cache.clock += 1;
+        let clock = cache.clock;
+        let cached = cache
+            .chunks
+            .range_mut(..=(position, usize::MAX))
+            .next_back()
+            .filter(|((start, size), _)| position + length <= start + size);
+        if let Some(((start, _), (chunk, used))) = cached {
+            *used = clock;
+            return Ok(chunk[position - *start..position - *start + length].to_vec());
+        }
+
+        let end = (position + length.max(CHUNK)).min(self.length);
+        let chunk = self.read(position, end - position)?.into_boxed_slice();
+        let bytes = chunk[..length].to_vec();
+        cache.size += chunk.len();
+        cache.chunks.insert((position, chunk.len()), (chunk, clock));
+
+        while cache.size > CACHE && cache.chunks.len() > 1 {
+            let oldest = cache
+                .chunks
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| *key)
+                .expect("Cache is not empty");
+            cache.size -= oldest.1;
+            cache.chunks.remove(&oldest);
+        }
+        Ok(bytes)
+    }
+
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut file = self.file.lock().expect("File lock poisoned");
+        file.seek(SeekFrom::Start(0))?;
+        io::copy(&mut *file, writer).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let path = "example_files/ASAP2_Demo_V171.mf4";
+        let bytes = std::fs::read(path).unwrap();
+        let storage = Storage::open(path).unwrap();
+
+        assert_eq!(storage.len(), bytes.len());
+        assert_eq!(*storage.bytes(0, 8), *b"MDF     ");
+        assert_eq!(storage.bytes(64, 24), &bytes[64..88]);
+        let end = bytes.len() - 10;
+        assert_eq!(storage.bytes(end, 10), &bytes[end..]);
+        assert_eq!(&storage.data(100, 5000)[..], &bytes[100..5100]);
+
+        // A longer read at the start of a chunk read before leaves it alone
+        let short = storage.bytes(64, 8);
+        let long = storage.bytes(64, CHUNK * 2);
+        assert_eq!(short, &bytes[64..72]);
+        assert_eq!(long, &bytes[64..64 + CHUNK * 2]);
+        assert_eq!(storage.bytes(64, 8), short);
+
+        // Reads past the end fail, also when they start inside the file
+        let Storage::File(file) = &storage else {
+            unreachable!()
+        };
+        assert!(file.bytes(bytes.len() - 4, 8).is_err());
+        assert!(file.bytes(bytes.len() + 4, 8).is_err());
+        assert!(file.bytes(usize::MAX, 8).is_err());
+        assert!(file.read(bytes.len() - 4, 8).is_err());
+
+        // Reading the whole file keeps the cache bounded
+        for position in (0..bytes.len() - CHUNK).step_by(CHUNK / 2) {
+            assert_eq!(storage.bytes(position, 16), &bytes[position..position + 16]);
+        }
+        assert!(file.cache.lock().unwrap().size <= CACHE.max(CHUNK * 2));
+
+        let mut copy = Vec::new();
+        storage.clone().write_to(&mut copy).unwrap();
+        assert_eq!(copy, bytes);
+        assert_eq!(storage.clone(), storage);
+        assert_eq!(storage, Storage::Memory(bytes.clone()));
+        assert_ne!(storage, Storage::Memory(bytes[1..].to_vec()));
+
+        #[cfg(feature = "mmap")]
+        {
+            let map = Storage::map(path).unwrap();
+            assert!(matches!(map.clone(), Storage::Mapped(..)));
+            assert_eq!(map, storage);
+        }
+    }
+}
@@ -1,6 +1,11 @@
+use std::collections::HashSet;
 use std::mem;
 use xml::reader::{EventReader, XmlEvent};
 
+use crate::storage::Stream;
+
+/// The `<display>` name of an XML comment, empty if there is none or the
+/// comment is not XML.
 pub fn extract_name(text: &[u8]) -> String {
     let parser = EventReader::new(text);
 
@@ -9,32 +14,54 @@ pub fn extract_name(text: &[u8]) -> String {
     let mut display = false;
     for e in parser {
         match e {
-            Ok(XmlEvent::StartElement { name, .. }) => {
-                //println!("{}", name);
-                if name.to_string().contains("display") {
-                    display = true;
-                }
+            Ok(XmlEvent::StartElement { name, .. }) if name.to_string().contains("display") => {
+                display = true;
             }
-            Ok(XmlEvent::Characters(text)) => {
-                if display {
-                    //println!("{}", text); // or something else
-                    display_name = text;
-                }
+            Ok(XmlEvent::Characters(text)) if display => {
+                display_name = text;
             }
-            Ok(XmlEvent::EndElement { name }) => {
-                if name.to_string().contains("display") {
-                    display = false;
-                }
+            Ok(XmlEvent::EndElement { name }) if name.to_string().contains("display") => {
+                display = false;
             }
-            Err(e) => {
-                println!("Error: {}", e);
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    display_name
+}
+
+/// The `<TX>` element of an XML comment, the whole text if it is not XML.
+pub fn extract_tx(text: &[u8]) -> String {
+    let parser = EventReader::new(text);
+
+    let mut tx = "".to_string();
+
+    let mut inside = false;
+    for e in parser {
+        match e {
+            Ok(XmlEvent::StartElement { name, .. }) if name.local_name == "TX" => {
+                inside = true;
+            }
+            Ok(XmlEvent::Characters(text)) if inside => {
+                tx = text;
+            }
+            Ok(XmlEvent::EndElement { name }) if name.local_name == "TX" => {
                 break;
             }
+            // Not XML up to a <TX> element, the text is the comment itself
+            Err(_) if !inside => {
+                tx = String::from_utf8_lossy(text)
+                    .trim_end_matches('\0')
+                    .to_string();
+                break;
+            }
+            Err(_) => break,
             _ => {}
         }
     }
 
-    display_name
+    tx
 }
 
 pub trait FromBytes {
@@ -152,16 +179,212 @@ pub fn read_be<T: FromBytes>(input: &[u8]) -> T {
 pub fn read_le<T: FromBytes>(input: &[u8]) -> T {
     T::from_le_bytes(input)
 }
-pub fn read<T: FromBytes>(input: &[u8], little_endian: bool, position: &mut usize) -> T {
+pub fn read<T: FromBytes, S: Stream + ?Sized>(
+    input: &S,
+    little_endian: bool,
+    position: &mut usize,
+) -> T {
     let old = *position;
-    *position += mem::size_of::<T>() / mem::size_of::<u8>();
+    let size = mem::size_of::<T>() / mem::size_of::<u8>();
+    *position += size;
     if little_endian {
-        read_le(&input[old..])
+        read_le(&input.bytes(old, size))
     } else {
-        read_be(&input[old..])
+        read_be(&input.bytes(old, size))
+    }
+}
+
+pub trait ToBytes {
+    fn to_be_bytes(&self) -> Vec<u8>;
+    fn to_le_bytes(&self) -> Vec<u8>;
+}
+
+impl<const N: usize> ToBytes for [u8; N] {
+    fn to_be_bytes(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+    fn to_le_bytes(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+macro_rules! impl_to_bytes {
+    ($($t:ty),*) => {
+        $(
+            impl ToBytes for $t {
+                fn to_be_bytes(&self) -> Vec<u8> {
+                    <$t>::to_be_bytes(*self).to_vec()
+                }
+                fn to_le_bytes(&self) -> Vec<u8> {
+                    <$t>::to_le_bytes(*self).to_vec()
+                }
+            }
+        )*
+    };
+}
+
+impl_to_bytes!(u64, u32, u16, u8, i64, i32, i16, i8, f64, f32);
+
+pub fn write<T: ToBytes>(stream: &mut Vec<u8>, value: T, little_endian: bool) {
+    if little_endian {
+        stream.append(&mut value.to_le_bytes());
+    } else {
+        stream.append(&mut value.to_be_bytes());
+    }
+}
+
+/// The longest chain of linked blocks read, far more than the channels any
+/// real file holds.
+pub const MAX_LINKED_BLOCKS: usize = 1 << 22;
+
+/// Positions of the linked blocks read so far, to stop at links that point
+/// back to one of them or past the end of the stream.
+pub struct Visited {
+    positions: HashSet<usize>,
+    length: usize,
+}
+
+impl Visited {
+    pub fn create(stream: &dyn Stream) -> Self {
+        Self {
+            positions: HashSet::new(),
+            length: stream.len(),
+        }
+    }
+
+    /// Marks the block at `position` read, an error if the link to it is
+    /// corrupt.
+    pub fn visit(&mut self, position: usize) -> Result<(), &'static str> {
+        self.check(position)?;
+        if self.positions.len() >= MAX_LINKED_BLOCKS {
+            return Err("Too many linked blocks");
+        }
+        if !self.positions.insert(position) {
+            return Err("Linked blocks form a cycle");
+        }
+        Ok(())
+    }
+
+    /// An error if `position` lies past the end of the stream.
+    pub fn check(&self, position: usize) -> Result<(), &'static str> {
+        match position < self.length {
+            true => Ok(()),
+            false => Err("Link past the end of the file"),
+        }
     }
 }
 
+/// The blocks read with `read` from `position` on, following the positions
+/// `link` returns until that is 0. Stops at the first error of `read`.
+pub fn linked_list<T>(
+    stream: &dyn Stream,
+    position: usize,
+    link: impl Fn(&T) -> u64,
+    read: impl Fn(usize) -> Result<T, &'static str>,
+) -> Result<Vec<T>, &'static str> {
+    let mut visited = Visited::create(stream);
+    let mut all = Vec::new();
+    let mut position = position;
+
+    while position != 0 {
+        visited.visit(position)?;
+        let block = read(position)?;
+        position = link(&block) as usize;
+        all.push(block);
+    }
+
+    Ok(all)
+}
+
 pub fn eq(array1: &[u8], other: &[u8]) -> bool {
     array1.iter().zip(other.iter()).all(|(a, b)| a == b)
 }
+
+/// Glob style matching where `*` matches any run of characters and `?` a
+/// single one.
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|x| *x == '*')
+}
+
+/// A file in the temporary directory of the tests, removed when dropped.
+#[cfg(test)]
+pub struct TempFile {
+    path: std::path::PathBuf,
+}
+
+#[cfg(test)]
+impl TempFile {
+    /// A file named after `name` and the process, so parallel test runs do
+    /// not collide.
+    pub fn create(name: &str) -> Self {
+        Self::from(std::env::temp_dir().join(format!("rsmdf_{}_{name}", std::process::id())))
+    }
+
+    pub fn path(&self) -> &str {
+        self.path
+            .to_str()
+            .expect("Temporary path is not valid UTF-8")
+    }
+}
+
+#[cfg(test)]
+impl From<std::path::PathBuf> for TempFile {
+    fn from(path: std::path::PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_name, extract_tx, wildcard_match};
+
+    #[test]
+    fn extract() {
+        let xml = b"<CNcomment><TX>speed</TX><display>Speed</display></CNcomment>\ntrailing";
+        assert_eq!(extract_tx(xml), "speed");
+        assert_eq!(extract_name(xml), "Speed");
+        assert_eq!(extract_tx(b"plain comment\0"), "plain comment");
+        assert_eq!(extract_name(b"plain comment"), "");
+    }
+
+    #[test]
+    fn wildcard() {
+        assert!(wildcard_match("ASAM.M.*", "ASAM.M.SCALAR.UBYTE"));
+        assert!(wildcard_match("*.UBYTE", "ASAM.M.SCALAR.UBYTE"));
+        assert!(wildcard_match("*SCALAR*BYTE", "ASAM.M.SCALAR.UBYTE"));
+        assert!(wildcard_match("speed?", "speed1"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("speed?", "speed"));
+        assert!(!wildcard_match("*.SBYTE", "ASAM.M.SCALAR.UBYTE"));
+        assert!(!wildcard_match("time", "times"));
+    }
+}
@@ -0,0 +1,59 @@
+/* Reads an example file through the C interface, exits non zero on failure. */
+
+#include <math.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "rsmdf.h"
+
+#define CHECK(condition)                                                      \
+    if (!(condition)) {                                                       \
+        const char *error = rsmdf_last_error();                               \
+        fprintf(stderr, "%s:%d: %s failed: %s\n", __FILE__, __LINE__,         \
+                #condition, error ? error : "no error");                      \
+        return 1;                                                             \
+    }
+
+int main(int argc, char **argv) {
+    CHECK(argc == 2);
+
+    CHECK(rsmdf_open("missing.mf4") == NULL);
+    CHECK(rsmdf_last_error() != NULL);
+
+    RsmdfFile *file = rsmdf_open(argv[1]);
+    CHECK(file != NULL);
+    CHECK(rsmdf_last_error() == NULL);
+    CHECK(rsmdf_channel_count(file) > 0);
+
+    int64_t index = rsmdf_find_channel(file, "ASAM.M.SCALAR.UBYTE.IDENTICAL");
+    CHECK(index > 0);
+    CHECK(strcmp(rsmdf_channel_name(file, index), "ASAM.M.SCALAR.UBYTE.IDENTICAL") == 0);
+    CHECK(strcmp(rsmdf_channel_unit(file, index), "hours") == 0);
+    CHECK(rsmdf_channel_comment(file, index) != NULL);
+
+    double values[4];
+    double timestamps[4];
+    int64_t length = rsmdf_read_channel(file, index, values, timestamps, 4);
+    CHECK(length > 4);
+
+    double *owned_values = NULL;
+    double *owned_timestamps = NULL;
+    CHECK(rsmdf_read_channel_alloc(file, index, &owned_values, &owned_timestamps) == length);
+    for (int i = 0; i < 4; i++) {
+        CHECK(owned_values[i] == values[i]);
+        CHECK(owned_timestamps[i] == timestamps[i]);
+    }
+    for (int64_t i = 1; i < length; i++) {
+        CHECK(owned_timestamps[i] >= owned_timestamps[i - 1]);
+        CHECK(!isnan(owned_values[i]));
+    }
+    rsmdf_free_buffer(owned_values, length);
+    rsmdf_free_buffer(owned_timestamps, length);
+
+    CHECK(rsmdf_find_channel(file, "missing") == -1);
+    CHECK(strcmp(rsmdf_last_error(), "Channel not found") == 0);
+    CHECK(rsmdf_channel_unit(file, 1000000) == NULL);
+
+    rsmdf_close(file);
+    return 0;
+}
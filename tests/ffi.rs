@@ -0,0 +1,40 @@
+//! Compiles `tests/ffi.c` against the shared library and runs it.
+
+#![cfg(unix)]
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn c_program() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // Integration tests live next to the library in target/<profile>/deps
+    let executable = env::current_exe().unwrap();
+    let deps = executable.parent().unwrap();
+    let program = deps.join("rsmdf_ffi_test");
+
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .arg(root.join("tests/ffi.c"))
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg("-L")
+        .arg(deps)
+        .arg(format!("-Wl,-rpath,{}", deps.display()))
+        .args(["-lrsmdf", "-lm", "-o"])
+        .arg(&program)
+        .status()
+        .expect("No C compiler found, set CC");
+    assert!(status.success(), "Compiling tests/ffi.c failed");
+
+    let output = Command::new(&program)
+        .arg(root.join("example_files/ASAP2_Demo_V171.mf4"))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}